@@ -0,0 +1,70 @@
+//! Local stand-in for a webhook receiver, for trying out subscriptions.
+//!
+//! ```sh
+//! WEBHOOK_SECRET=whsec_... cargo run --example webhook_receiver
+//! ```
+//!
+//! Register `http://<host>:4000/hook` as the subscription's `target_url`
+//! (`host.docker.internal` when the backend runs in docker compose). Each
+//! delivery is printed with whether its signature checks out. Set
+//! `WEBHOOK_FAIL=1` to answer every delivery with a 500 and watch the backend
+//! retry and eventually dead-letter it.
+
+use axum::{
+    Router,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+
+fn expected_signature(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn receive(headers: HeaderMap, body: String) -> StatusCode {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string()
+    };
+    let event = header("x-zoo-event");
+    let delivery = header("x-zoo-delivery");
+    let timestamp = header("x-zoo-timestamp");
+    let signature = header("x-zoo-signature");
+
+    let verdict = match env::var("WEBHOOK_SECRET") {
+        Ok(secret) if expected_signature(&secret, &timestamp, &body) == signature => "valid",
+        Ok(_) => "INVALID",
+        Err(_) => "not checked (WEBHOOK_SECRET unset)",
+    };
+
+    println!("delivery {} [{}] signature {}", delivery, event, verdict);
+    println!("{}", body);
+
+    if env::var("WEBHOOK_FAIL").is_ok_and(|v| v == "1") {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else if verdict == "INVALID" {
+        StatusCode::UNAUTHORIZED
+    } else {
+        StatusCode::OK
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let addr = env::var("WEBHOOK_RECEIVER_ADDR").unwrap_or_else(|_| "0.0.0.0:4000".to_string());
+    let app = Router::new().route("/hook", post(receive));
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    println!("Webhook receiver listening on http://{}/hook", addr);
+    axum::serve(listener, app).await.unwrap();
+}
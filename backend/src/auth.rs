@@ -0,0 +1,57 @@
+use crate::config::{ApiKeyConfig, AuthConfig};
+use axum::http::{HeaderMap, StatusCode};
+use tracing::debug;
+
+/// Read examinations, their attachments and treatments
+pub const MEDICAL_READ: &str = "medical:read";
+/// Record and correct examinations, prescribe treatments and record doses
+pub const MEDICAL_WRITE: &str = "medical:write";
+
+/// List, add and remove webhook subscriptions and see their deliveries;
+/// deliveries go to whatever URL is registered, so this is not public
+pub const WEBHOOKS_MANAGE: &str = "webhooks:manage";
+
+/// Every scope a key can be given.
+pub const SCOPES: &[&str] = &[MEDICAL_READ, MEDICAL_WRITE, WEBHOOKS_MANAGE];
+
+pub const MIN_API_KEY_LENGTH: usize = 16;
+pub const MAX_API_KEY_LENGTH: usize = 256;
+
+/// Compare without stopping at the first difference, so response times
+/// don't tell how much of a guessed key was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The configured key sent in `X-API-Key`, if any.
+pub fn api_key<'a>(auth: &'a AuthConfig, headers: &HeaderMap) -> Option<&'a ApiKeyConfig> {
+    let sent = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_API_KEY_LENGTH)?;
+    auth.api_keys
+        .iter()
+        .find(|api_key| constant_time_eq(api_key.key.as_bytes(), sent.as_bytes()))
+}
+
+/// Let the request through only with a key that has `scope`: `401` without
+/// a known key, `403` when the key lacks the scope.
+pub fn require_scope<'a>(
+    auth: &'a AuthConfig,
+    headers: &HeaderMap,
+    scope: &str,
+) -> Result<&'a ApiKeyConfig, (StatusCode, String)> {
+    let api_key = api_key(auth, headers).ok_or((
+        StatusCode::UNAUTHORIZED,
+        format!("An X-API-Key with the {} scope is required", scope),
+    ))?;
+    if !api_key.scopes.iter().any(|s| s == scope) {
+        debug!(key = %api_key.name, scope, "API key lacks scope");
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("This API key doesn't have the {} scope", scope),
+        ));
+    }
+    Ok(api_key)
+}
@@ -0,0 +1,1169 @@
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Read when neither `--config` nor `ZOO_CONFIG` names a file, if it exists
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Parser)]
+#[command(name = "backend", about = "Zoo management REST API")]
+pub struct Cli {
+    /// TOML config file [default: config.toml when present, or ZOO_CONFIG]
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overrides: Overrides,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the server (the default)
+    Serve,
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Load and validate the configuration, print it with secrets hidden, and exit
+    Check,
+}
+
+/// Command-line flags; they win over the config file and the environment.
+#[derive(Debug, Default, Args)]
+pub struct Overrides {
+    /// Address to listen on
+    #[arg(long, global = true)]
+    pub host: Option<String>,
+    /// Port to listen on
+    #[arg(long, global = true)]
+    pub port: Option<u16>,
+    #[arg(long, global = true)]
+    pub db_host: Option<String>,
+    #[arg(long, global = true)]
+    pub db_port: Option<u16>,
+    #[arg(long, global = true)]
+    pub db_user: Option<String>,
+    #[arg(long, global = true)]
+    pub db_name: Option<String>,
+    /// Serve HTTPS with this PEM certificate chain (needs --tls-key)
+    #[arg(long, global = true)]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for --tls-cert
+    #[arg(long, global = true)]
+    pub tls_key: Option<PathBuf>,
+    /// Allowed CORS origin; repeat for several, `*` for any
+    #[arg(long = "cors-origin", global = true)]
+    pub cors_origins: Vec<String>,
+    /// Log filter, same syntax as RUST_LOG
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+    /// `text` or `json`
+    #[arg(long, global = true)]
+    pub log_format: Option<String>,
+}
+
+/// Everything the backend can be configured with.
+///
+/// Loaded from defaults, then the config file, then environment variables,
+/// then command-line flags, each overriding the one before.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub cors: CorsConfig,
+    pub security: SecurityConfig,
+    pub logging: LoggingConfig,
+    pub idempotency: IdempotencyConfig,
+    pub limits: LimitsConfig,
+    pub rate_limit: RateLimitConfig,
+    pub auth: AuthConfig,
+    pub measurements: MeasurementsConfig,
+    pub features: FeatureConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Seconds in-flight requests get to finish after SIGTERM/SIGINT
+    pub shutdown_drain_seconds: u64,
+    pub tls: TlsConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            shutdown_drain_seconds: 30,
+            tls: TlsConfig::default(),
+        }
+    }
+}
+
+/// HTTPS for the server itself, when there is no proxy terminating TLS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    pub enabled: bool,
+    /// PEM certificate chain, leaf first
+    pub cert_path: Option<PathBuf>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: Option<PathBuf>,
+    /// How often the files are checked for a rotated certificate
+    pub reload_interval_seconds: u64,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: None,
+            key_path: None,
+            reload_interval_seconds: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    /// Required; best given as `DB_PASSWORD` rather than in the file
+    pub password: Option<String>,
+    pub name: String,
+    /// CA certificate (`.pem`, `.crt` or `.der`) the SQL Server certificate
+    /// must chain to, besides the system trust store
+    pub ca_certificate: Option<PathBuf>,
+    /// Accept any SQL Server certificate. For local development against a
+    /// self-signed server only
+    pub trust_server_certificate: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            host: "sqlserver".to_string(),
+            port: 1433,
+            user: "SA".to_string(),
+            password: None,
+            name: "zoo_db".to_string(),
+            ca_certificate: None,
+            trust_server_certificate: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins browsers may call the API from, e.g. `http://localhost:5173`;
+    /// `*` allows any
+    pub allowed_origins: Vec<String>,
+    /// Methods cross-origin requests may use
+    pub allowed_methods: Vec<String>,
+    /// Request headers cross-origin requests may send
+    pub allowed_headers: Vec<String>,
+    /// Response headers scripts on the calling page may read
+    pub exposed_headers: Vec<String>,
+    /// Let browsers send cookies and `Authorization`; needs explicit origins
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight response
+    pub max_age_seconds: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        let list = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Self {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: list(&["GET", "POST", "PUT", "PATCH", "DELETE"]),
+            allowed_headers: list(&[
+                "content-type",
+                "if-match",
+                "if-none-match",
+                "idempotency-key",
+                "last-event-id",
+                "x-api-key",
+                "x-request-id",
+            ]),
+            exposed_headers: list(&[
+                "etag",
+                "deprecation",
+                "link",
+                "idempotent-replayed",
+                "x-request-id",
+            ]),
+            allow_credentials: false,
+            max_age_seconds: 600,
+        }
+    }
+}
+
+/// Headers that tell browsers how to treat responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// Add `Content-Security-Policy`, `X-Content-Type-Options`,
+    /// `X-Frame-Options` and `Referrer-Policy` to every response
+    pub headers: bool,
+    /// Policy for API responses; `/docs` gets one that lets Swagger UI run
+    pub content_security_policy: String,
+    /// Whether clients only ever reach the backend over HTTPS through a proxy
+    /// in front of it; turns on `Strict-Transport-Security`, as does
+    /// `server.tls`
+    pub behind_tls: bool,
+    pub hsts_max_age_seconds: u64,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            headers: true,
+            content_security_policy: "default-src 'none'; frame-ancestors 'none'".to_string(),
+            behind_tls: false,
+            hsts_max_age_seconds: 365 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected `text` or `json`".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Filter directives, same syntax as `RUST_LOG`
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdempotencyConfig {
+    /// How long a stored `Idempotency-Key` response is replayed for
+    pub ttl_seconds: i32,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Caps on what a single request may send.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Largest request body accepted, in bytes
+    pub max_body_bytes: usize,
+    /// Longest `description` accepted, in characters; the other text fields
+    /// are capped at their `VARCHAR(250)` columns
+    pub max_description_length: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 64 * 1024,
+            max_description_length: 4000,
+        }
+    }
+}
+
+/// Token buckets per client, one for reads and one for writes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// GET/HEAD requests refilled per minute
+    pub reads_per_minute: u32,
+    /// Reads that can be made at once after being idle
+    pub read_burst: u32,
+    /// POST/PUT/PATCH/DELETE requests refilled per minute
+    pub writes_per_minute: u32,
+    pub write_burst: u32,
+    /// Take the client address from `X-Forwarded-For`; only behind a proxy
+    /// that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reads_per_minute: 600,
+            read_burst: 100,
+            writes_per_minute: 60,
+            write_burst: 20,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+/// API keys and the scopes they grant. Only the medical records and the
+/// webhook subscriptions ask for a scope; the rest of the API is open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub api_keys: Vec<ApiKeyConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Who holds the key, for the logs
+    pub name: String,
+    /// Sent by clients in `X-API-Key`
+    pub key: String,
+    /// e.g. `medical:read`, `medical:write`, `webhooks:manage`
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// When a weight trend counts as sudden weight loss.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MeasurementsConfig {
+    /// Drop from the highest weight in the window that raises an alert, in percent
+    pub weight_loss_alert_percent: f64,
+    /// Days before the latest weighing to compare against
+    pub weight_loss_window_days: i64,
+}
+
+impl Default for MeasurementsConfig {
+    fn default() -> Self {
+        Self {
+            weight_loss_alert_percent: 10.0,
+            weight_loss_window_days: 30,
+        }
+    }
+}
+
+/// Parts of the backend that can be switched off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureConfig {
+    /// Send queued webhook deliveries
+    pub webhook_delivery: bool,
+    /// Serve `/docs` and `/openapi.json`
+    pub api_docs: bool,
+    /// Serve `/metrics`
+    pub metrics: bool,
+    /// Serve the deprecated pre-v1 routes
+    pub legacy_routes: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            webhook_delivery: true,
+            api_docs: true,
+            metrics: true,
+            legacy_routes: true,
+        }
+    }
+}
+
+fn env_value(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn string_from_env(name: &str, target: &mut String) {
+    if let Some(value) = env_value(name) {
+        *target = value;
+    }
+}
+
+/// Comma-separated, e.g. `CORS_ALLOWED_ORIGINS=https://a.example,https://b.example`.
+fn list_from_env(name: &str, target: &mut Vec<String>) {
+    if let Some(value) = env_value(name) {
+        *target = value
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+}
+
+/// `name:key:scope,scope` entries separated by `;`, e.g.
+/// `API_KEYS=clinic:0123456789abcdef:medical:read,medical:write`.
+fn api_keys_from_env(name: &str, target: &mut Vec<ApiKeyConfig>, errors: &mut Vec<String>) {
+    let Some(value) = env_value(name) else {
+        return;
+    };
+    let mut keys = Vec::new();
+    for entry in value.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts = entry.splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(key_name), Some(key), scopes) => keys.push(ApiKeyConfig {
+                name: key_name.trim().to_string(),
+                key: key.trim().to_string(),
+                scopes: scopes
+                    .unwrap_or("")
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            }),
+            _ => {
+                let shown = entry.split(':').next().unwrap_or("");
+                errors.push(format!(
+                    "{}: entry '{}' must be name:key:scopes",
+                    name, shown
+                ));
+            }
+        }
+    }
+    *target = keys;
+}
+
+fn parse_from_env<T>(name: &str, target: &mut T, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Some(value) = env_value(name) {
+        match value.trim().parse() {
+            Ok(parsed) => *target = parsed,
+            Err(e) => errors.push(format!("{}: invalid value '{}': {}", name, value, e)),
+        }
+    }
+}
+
+fn flag_from_env(name: &str, target: &mut bool, errors: &mut Vec<String>) {
+    if let Some(value) = env_value(name) {
+        match value.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => *target = true,
+            "0" | "false" | "no" | "off" => *target = false,
+            _ => errors.push(format!(
+                "{}: invalid value '{}': expected true or false",
+                name, value
+            )),
+        }
+    }
+}
+
+impl Config {
+    /// Load every layer and validate the result. All problems found are
+    /// returned together rather than stopping at the first.
+    pub fn load(cli: &Cli) -> Result<Config, Vec<String>> {
+        let mut errors = Vec::new();
+
+        let file = cli
+            .config
+            .clone()
+            .or_else(|| env_value("ZOO_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                Path::new(DEFAULT_CONFIG_FILE)
+                    .exists()
+                    .then(|| PathBuf::from(DEFAULT_CONFIG_FILE))
+            });
+
+        // A broken file still lets the other layers be checked
+        let mut config = match file {
+            Some(path) => Self::from_file(&path).unwrap_or_else(|e| {
+                errors.push(e);
+                Config::default()
+            }),
+            None => Config::default(),
+        };
+
+        config.apply_env(&mut errors);
+        config.apply_overrides(&cli.overrides, &mut errors);
+        errors.extend(config.validate());
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: could not read config file: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        string_from_env("SERVER_HOST", &mut self.server.host);
+        parse_from_env("SERVER_PORT", &mut self.server.port, errors);
+        parse_from_env(
+            "SHUTDOWN_DRAIN_SECONDS",
+            &mut self.server.shutdown_drain_seconds,
+            errors,
+        );
+        flag_from_env("TLS_ENABLED", &mut self.server.tls.enabled, errors);
+        if let Some(path) = env_value("TLS_CERT_PATH") {
+            self.server.tls.cert_path = Some(PathBuf::from(path));
+        }
+        if let Some(path) = env_value("TLS_KEY_PATH") {
+            self.server.tls.key_path = Some(PathBuf::from(path));
+        }
+        parse_from_env(
+            "TLS_RELOAD_INTERVAL_SECONDS",
+            &mut self.server.tls.reload_interval_seconds,
+            errors,
+        );
+
+        string_from_env("DB_HOST", &mut self.database.host);
+        parse_from_env("DB_PORT", &mut self.database.port, errors);
+        string_from_env("DB_USER", &mut self.database.user);
+        if let Some(password) = env_value("DB_PASSWORD") {
+            self.database.password = Some(password);
+        }
+        string_from_env("DB_NAME", &mut self.database.name);
+        if let Some(path) = env_value("DB_CA_CERT") {
+            self.database.ca_certificate = Some(PathBuf::from(path));
+        }
+        flag_from_env(
+            "DB_TRUST_SERVER_CERTIFICATE",
+            &mut self.database.trust_server_certificate,
+            errors,
+        );
+
+        list_from_env("CORS_ALLOWED_ORIGINS", &mut self.cors.allowed_origins);
+        list_from_env("CORS_ALLOWED_METHODS", &mut self.cors.allowed_methods);
+        list_from_env("CORS_ALLOWED_HEADERS", &mut self.cors.allowed_headers);
+        list_from_env("CORS_EXPOSED_HEADERS", &mut self.cors.exposed_headers);
+        flag_from_env(
+            "CORS_ALLOW_CREDENTIALS",
+            &mut self.cors.allow_credentials,
+            errors,
+        );
+        parse_from_env(
+            "CORS_MAX_AGE_SECONDS",
+            &mut self.cors.max_age_seconds,
+            errors,
+        );
+
+        flag_from_env("SECURITY_HEADERS", &mut self.security.headers, errors);
+        string_from_env(
+            "CONTENT_SECURITY_POLICY",
+            &mut self.security.content_security_policy,
+        );
+        flag_from_env("BEHIND_TLS", &mut self.security.behind_tls, errors);
+        parse_from_env(
+            "HSTS_MAX_AGE_SECONDS",
+            &mut self.security.hsts_max_age_seconds,
+            errors,
+        );
+
+        string_from_env("RUST_LOG", &mut self.logging.level);
+        parse_from_env("LOG_FORMAT", &mut self.logging.format, errors);
+
+        parse_from_env(
+            "IDEMPOTENCY_TTL_SECONDS",
+            &mut self.idempotency.ttl_seconds,
+            errors,
+        );
+
+        parse_from_env("MAX_BODY_BYTES", &mut self.limits.max_body_bytes, errors);
+        parse_from_env(
+            "MAX_DESCRIPTION_LENGTH",
+            &mut self.limits.max_description_length,
+            errors,
+        );
+
+        flag_from_env("RATE_LIMIT_ENABLED", &mut self.rate_limit.enabled, errors);
+        parse_from_env(
+            "RATE_LIMIT_READS_PER_MINUTE",
+            &mut self.rate_limit.reads_per_minute,
+            errors,
+        );
+        parse_from_env(
+            "RATE_LIMIT_READ_BURST",
+            &mut self.rate_limit.read_burst,
+            errors,
+        );
+        parse_from_env(
+            "RATE_LIMIT_WRITES_PER_MINUTE",
+            &mut self.rate_limit.writes_per_minute,
+            errors,
+        );
+        parse_from_env(
+            "RATE_LIMIT_WRITE_BURST",
+            &mut self.rate_limit.write_burst,
+            errors,
+        );
+        flag_from_env(
+            "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            &mut self.rate_limit.trust_forwarded_for,
+            errors,
+        );
+
+        api_keys_from_env("API_KEYS", &mut self.auth.api_keys, errors);
+
+        parse_from_env(
+            "WEIGHT_LOSS_ALERT_PERCENT",
+            &mut self.measurements.weight_loss_alert_percent,
+            errors,
+        );
+        parse_from_env(
+            "WEIGHT_LOSS_WINDOW_DAYS",
+            &mut self.measurements.weight_loss_window_days,
+            errors,
+        );
+
+        flag_from_env(
+            "FEATURE_WEBHOOK_DELIVERY",
+            &mut self.features.webhook_delivery,
+            errors,
+        );
+        flag_from_env("FEATURE_API_DOCS", &mut self.features.api_docs, errors);
+        flag_from_env("FEATURE_METRICS", &mut self.features.metrics, errors);
+        flag_from_env(
+            "FEATURE_LEGACY_ROUTES",
+            &mut self.features.legacy_routes,
+            errors,
+        );
+    }
+
+    fn apply_overrides(&mut self, overrides: &Overrides, errors: &mut Vec<String>) {
+        if let Some(host) = &overrides.host {
+            self.server.host = host.clone();
+        }
+        if let Some(port) = overrides.port {
+            self.server.port = port;
+        }
+        if let Some(host) = &overrides.db_host {
+            self.database.host = host.clone();
+        }
+        if let Some(port) = overrides.db_port {
+            self.database.port = port;
+        }
+        if let Some(user) = &overrides.db_user {
+            self.database.user = user.clone();
+        }
+        if let Some(name) = &overrides.db_name {
+            self.database.name = name.clone();
+        }
+        if let Some(path) = &overrides.tls_cert {
+            self.server.tls.enabled = true;
+            self.server.tls.cert_path = Some(path.clone());
+        }
+        if let Some(path) = &overrides.tls_key {
+            self.server.tls.enabled = true;
+            self.server.tls.key_path = Some(path.clone());
+        }
+        if !overrides.cors_origins.is_empty() {
+            self.cors.allowed_origins = overrides.cors_origins.clone();
+        }
+        if let Some(level) = &overrides.log_level {
+            self.logging.level = level.clone();
+        }
+        if let Some(format) = &overrides.log_format {
+            match format.parse() {
+                Ok(format) => self.logging.format = format,
+                Err(e) => errors.push(format!("--log-format: invalid value '{}': {}", format, e)),
+            }
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.server.host.trim().is_empty() {
+            errors.push("server.host must not be empty".to_string());
+        }
+        if self.server.port == 0 {
+            errors.push("server.port must be between 1 and 65535".to_string());
+        }
+        let tls = &self.server.tls;
+        if tls.enabled {
+            match (&tls.cert_path, &tls.key_path) {
+                (Some(cert), Some(key)) => {
+                    if let Err(e) = crate::tls::load(cert, key) {
+                        errors.push(format!("server.tls: {}", e));
+                    }
+                }
+                _ => {
+                    errors.push("server.tls.enabled needs both cert_path and key_path".to_string())
+                }
+            }
+            if tls.reload_interval_seconds == 0 {
+                errors.push("server.tls.reload_interval_seconds must be positive".to_string());
+            }
+        }
+
+        if self.database.host.trim().is_empty() {
+            errors.push("database.host must not be empty".to_string());
+        }
+        if self.database.port == 0 {
+            errors.push("database.port must be between 1 and 65535".to_string());
+        }
+        if self.database.user.trim().is_empty() {
+            errors.push("database.user must not be empty".to_string());
+        }
+        if self.database.name.trim().is_empty() {
+            errors.push("database.name must not be empty".to_string());
+        }
+        if self
+            .database
+            .password
+            .as_deref()
+            .is_none_or(|p| p.is_empty())
+        {
+            errors.push(
+                "database.password is required (set DB_PASSWORD or [database] password)"
+                    .to_string(),
+            );
+        }
+
+        if let Some(ca) = &self.database.ca_certificate {
+            if self.database.trust_server_certificate {
+                errors.push(
+                    "database.ca_certificate and database.trust_server_certificate are exclusive"
+                        .to_string(),
+                );
+            }
+            let extension = ca
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !["pem", "crt", "der"].contains(&extension.as_str()) {
+                errors.push(format!(
+                    "database.ca_certificate: {} must be a .pem, .crt or .der file",
+                    ca.display()
+                ));
+            } else if !ca.is_file() {
+                errors.push(format!(
+                    "database.ca_certificate: {} does not exist",
+                    ca.display()
+                ));
+            }
+        }
+
+        let origins = &self.cors.allowed_origins;
+        if origins.iter().any(|o| o == "*") && origins.len() > 1 {
+            errors
+                .push("cors.allowed_origins: `*` can't be combined with other origins".to_string());
+        }
+        for origin in origins.iter().filter(|o| *o != "*") {
+            if let Err(e) = validate_origin(origin) {
+                errors.push(format!("cors.allowed_origins: '{}' {}", origin, e));
+            }
+        }
+        if self.cors.allow_credentials && origins.iter().any(|o| o == "*") {
+            errors
+                .push("cors.allow_credentials needs explicit allowed_origins, not `*`".to_string());
+        }
+        for method in &self.cors.allowed_methods {
+            if method.parse::<axum::http::Method>().is_err() {
+                errors.push(format!(
+                    "cors.allowed_methods: '{}' is not a method",
+                    method
+                ));
+            }
+        }
+        for (key, headers) in [
+            ("allowed_headers", &self.cors.allowed_headers),
+            ("exposed_headers", &self.cors.exposed_headers),
+        ] {
+            for name in headers {
+                if name.parse::<axum::http::HeaderName>().is_err() {
+                    errors.push(format!("cors.{}: '{}' is not a header name", key, name));
+                }
+            }
+        }
+
+        if axum::http::HeaderValue::from_str(&self.security.content_security_policy).is_err() {
+            errors.push("security.content_security_policy is not a valid header value".to_string());
+        }
+
+        if let Err(e) = tracing_subscriber::EnvFilter::try_new(&self.logging.level) {
+            errors.push(format!(
+                "logging.level: invalid filter '{}': {}",
+                self.logging.level, e
+            ));
+        }
+
+        if self.idempotency.ttl_seconds <= 0 {
+            errors.push("idempotency.ttl_seconds must be positive".to_string());
+        }
+
+        if self.limits.max_body_bytes == 0 {
+            errors.push("limits.max_body_bytes must be positive".to_string());
+        }
+        if self.limits.max_description_length == 0 {
+            errors.push("limits.max_description_length must be positive".to_string());
+        }
+
+        let rate_limit = &self.rate_limit;
+        if rate_limit.enabled {
+            for (key, value) in [
+                ("reads_per_minute", rate_limit.reads_per_minute),
+                ("read_burst", rate_limit.read_burst),
+                ("writes_per_minute", rate_limit.writes_per_minute),
+                ("write_burst", rate_limit.write_burst),
+            ] {
+                if value == 0 {
+                    errors.push(format!("rate_limit.{} must be positive", key));
+                }
+            }
+        }
+
+        let mut names = std::collections::HashSet::new();
+        let mut keys = std::collections::HashSet::new();
+        for api_key in &self.auth.api_keys {
+            if api_key.name.trim().is_empty() {
+                errors.push("auth.api_keys: every key needs a name".to_string());
+            } else if !names.insert(api_key.name.as_str()) {
+                errors.push(format!(
+                    "auth.api_keys: name '{}' is used twice",
+                    api_key.name
+                ));
+            }
+            let length = api_key.key.chars().count();
+            if !(crate::auth::MIN_API_KEY_LENGTH..=crate::auth::MAX_API_KEY_LENGTH)
+                .contains(&length)
+                || api_key
+                    .key
+                    .chars()
+                    .any(|c| c.is_whitespace() || c.is_control())
+            {
+                errors.push(format!(
+                    "auth.api_keys: key of '{}' must be {} to {} characters without spaces",
+                    api_key.name,
+                    crate::auth::MIN_API_KEY_LENGTH,
+                    crate::auth::MAX_API_KEY_LENGTH
+                ));
+            } else if !keys.insert(api_key.key.as_str()) {
+                errors.push(format!(
+                    "auth.api_keys: key of '{}' is also given to another name",
+                    api_key.name
+                ));
+            }
+            for scope in &api_key.scopes {
+                if !crate::auth::SCOPES.contains(&scope.as_str()) {
+                    errors.push(format!(
+                        "auth.api_keys: unknown scope '{}' for '{}'; expected one of {}",
+                        scope,
+                        api_key.name,
+                        crate::auth::SCOPES.join(", ")
+                    ));
+                }
+            }
+        }
+
+        let measurements = &self.measurements;
+        if !(measurements.weight_loss_alert_percent > 0.0
+            && measurements.weight_loss_alert_percent < 100.0)
+        {
+            errors.push(
+                "measurements.weight_loss_alert_percent must be between 0 and 100".to_string(),
+            );
+        }
+        if !(1..=crate::models::MAX_TREND_WINDOW_DAYS)
+            .contains(&measurements.weight_loss_window_days)
+        {
+            errors.push(format!(
+                "measurements.weight_loss_window_days must be between 1 and {}",
+                crate::models::MAX_TREND_WINDOW_DAYS
+            ));
+        }
+
+        errors
+    }
+
+    /// Whether clients reach the backend over HTTPS only, so HSTS is safe.
+    pub fn https_only(&self) -> bool {
+        self.server.tls.enabled || self.security.behind_tls
+    }
+
+    /// The configuration as TOML with secrets masked, for `config check`.
+    pub fn redacted_toml(&self) -> String {
+        let mut shown = self.clone();
+        if shown.database.password.is_some() {
+            shown.database.password = Some("********".to_string());
+        }
+        for api_key in &mut shown.auth.api_keys {
+            api_key.key = "********".to_string();
+        }
+        toml::to_string_pretty(&shown).unwrap_or_default()
+    }
+}
+
+/// An origin is a scheme, host and optional port, with nothing after it.
+fn validate_origin(origin: &str) -> Result<(), &'static str> {
+    let url = reqwest::Url::parse(origin).map_err(|_| "is not a valid URL")?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err("must use http or https");
+    }
+    if url.host_str().is_none() {
+        return Err("has no host");
+    }
+    if url.path() != "/" || url.query().is_some() || origin.ends_with('/') {
+        return Err("must not have a path or trailing slash");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Tests that set environment variables hold this, as the environment
+    /// is shared by the test threads.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_env<T>(vars: &[(&str, &str)], test: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: every test that touches the environment holds ENV_LOCK
+        unsafe {
+            for (name, value) in vars {
+                env::set_var(name, value);
+            }
+        }
+        let result = test();
+        unsafe {
+            for (name, _) in vars {
+                env::remove_var(name);
+            }
+        }
+        result
+    }
+
+    fn valid() -> Config {
+        let mut config = Config::default();
+        config.database.password = Some("secret".to_string());
+        config
+    }
+
+    fn file(contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "zoo-config-test-{}-{}.toml",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn defaults_with_a_password_are_valid() {
+        assert_eq!(valid().validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn a_missing_password_is_rejected() {
+        let errors = Config::default().validate();
+        assert!(errors.iter().any(|e| e.starts_with("database.password")));
+    }
+
+    #[test]
+    fn invalid_values_are_all_reported() {
+        let mut config = valid();
+        config.server.port = 0;
+        config.database.host = " ".to_string();
+        config.cors.allowed_origins = vec!["*".to_string(), "https://zoo.example/".to_string()];
+        config.idempotency.ttl_seconds = 0;
+        config.rate_limit.enabled = true;
+        config.rate_limit.write_burst = 0;
+        config.measurements.weight_loss_alert_percent = 100.0;
+        config.measurements.weight_loss_window_days = 0;
+
+        let errors = config.validate();
+        for expected in [
+            "server.port",
+            "database.host",
+            "cors.allowed_origins: `*`",
+            "cors.allowed_origins: 'https://zoo.example/'",
+            "idempotency.ttl_seconds",
+            "rate_limit.write_burst",
+            "measurements.weight_loss_alert_percent",
+            "measurements.weight_loss_window_days",
+        ] {
+            assert!(
+                errors.iter().any(|e| e.starts_with(expected)),
+                "no error for {} in {:?}",
+                expected,
+                errors
+            );
+        }
+        assert_eq!(errors.len(), 8, "{:?}", errors);
+    }
+
+    #[test]
+    fn api_keys_are_checked() {
+        let key = |name: &str, key: &str, scope: &str| ApiKeyConfig {
+            name: name.to_string(),
+            key: key.to_string(),
+            scopes: vec![scope.to_string()],
+        };
+        let mut config = valid();
+        config.auth.api_keys = vec![
+            key("clinic", "0123456789abcdef", "medical:read"),
+            key("clinic", "fedcba9876543210", "medical:write"),
+            key("short", "0123", "medical:read"),
+            key("keeper", "0123456789abcdef", "feeding:write"),
+        ];
+
+        let errors = config.validate();
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("name 'clinic' is used twice"))
+        );
+        assert!(errors.iter().any(|e| e.contains("key of 'short' must be")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("key of 'keeper' is also given"))
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("unknown scope 'feeding:write'"))
+        );
+    }
+
+    #[test]
+    fn the_file_overrides_the_defaults() {
+        let path = file("[server]\nport = 4000\n\n[database]\nname = \"zoo_test\"\n");
+        let config = Config::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.server.port, 4000);
+        assert_eq!(config.database.name, "zoo_test");
+        assert_eq!(config.database.host, DatabaseConfig::default().host);
+    }
+
+    #[test]
+    fn unknown_keys_in_the_file_are_rejected() {
+        let path = file("[server]\nprot = 4000\n");
+        let error = Config::from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).ok();
+
+        assert!(error.contains("prot"), "{}", error);
+    }
+
+    #[test]
+    fn the_environment_overrides_the_file_and_flags_override_both() {
+        let path = file("[server]\nport = 4000\nhost = \"127.0.0.1\"\n");
+        let mut config = Config::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let errors = with_env(
+            &[
+                ("SERVER_PORT", "5000"),
+                (
+                    "CORS_ALLOWED_ORIGINS",
+                    "https://a.example, https://b.example",
+                ),
+            ],
+            || {
+                let mut errors = Vec::new();
+                config.apply_env(&mut errors);
+                errors
+            },
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.server.port, 5000);
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(
+            config.cors.allowed_origins,
+            ["https://a.example", "https://b.example"]
+        );
+
+        let overrides = Overrides {
+            port: Some(6000),
+            ..Overrides::default()
+        };
+        let mut errors = Vec::new();
+        config.apply_overrides(&overrides, &mut errors);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.server.port, 6000);
+        assert_eq!(config.server.host, "127.0.0.1");
+    }
+
+    #[test]
+    fn invalid_environment_values_are_reported() {
+        let mut config = valid();
+        let errors = with_env(
+            &[("SERVER_PORT", "eighty"), ("TLS_ENABLED", "maybe")],
+            || {
+                let mut errors = Vec::new();
+                config.apply_env(&mut errors);
+                errors
+            },
+        );
+
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.starts_with("SERVER_PORT: invalid value 'eighty'"))
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.starts_with("TLS_ENABLED: invalid value 'maybe'"))
+        );
+        assert_eq!(config.server.port, ServerConfig::default().port);
+    }
+
+    #[test]
+    fn api_keys_are_read_from_the_environment() {
+        let mut keys = Vec::new();
+        let mut errors = Vec::new();
+        with_env(
+            &[(
+                "ZOO_TEST_API_KEYS",
+                "clinic:0123456789abcdef:medical:read,medical:write; broken",
+            )],
+            || api_keys_from_env("ZOO_TEST_API_KEYS", &mut keys, &mut errors),
+        );
+
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].name, "clinic");
+        assert_eq!(keys[0].key, "0123456789abcdef");
+        assert_eq!(keys[0].scopes, ["medical:read", "medical:write"]);
+        assert_eq!(
+            errors,
+            ["ZOO_TEST_API_KEYS: entry 'broken' must be name:key:scopes"]
+        );
+    }
+}
@@ -1,7 +1,21 @@
-use std::env;
-use tiberius::{AuthMethod, Client, Config};
+use crate::config::DatabaseConfig;
+use crate::metrics::METRICS;
+use axum::http::StatusCode;
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tiberius::{AuthMethod, Client, Config, ExecuteResult, QueryStream, Row, ToSql};
 use tokio::net::TcpStream;
-use tokio_util::compat::TokioAsyncWriteCompatExt;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+use tracing::{Instrument, Span, debug, debug_span, error, info, warn};
+
+/// Longest statement text kept in a query span
+const MAX_STATEMENT_LENGTH: usize = 120;
+
+/// Connection attempts at startup back off from the first delay up to the max
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const STARTUP_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Database connection wrapper
 #[derive(Clone)]
@@ -11,45 +25,69 @@ pub struct Database {
     user: String,
     password: String,
     database: String,
+    trust: ServerCertificate,
+}
+
+/// How the SQL Server certificate is checked
+#[derive(Clone)]
+enum ServerCertificate {
+    /// Against the system trust store
+    SystemRoots,
+    /// Against the system trust store and this CA
+    Ca(PathBuf),
+    /// Not at all
+    TrustAll,
 }
 
 impl Database {
     /// Create a new database connection configuration
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let host = env::var("DB_HOST").unwrap_or_else(|_| "sqlserver".to_string());
-        let port = env::var("DB_PORT")
-            .unwrap_or_else(|_| "1433".to_string())
-            .parse::<u16>()?;
-        let user = env::var("DB_USER").unwrap_or_else(|_| "SA".to_string());
-        let password = env::var("DB_PASSWORD").expect("DB_PASSWORD must be set");
-        let database = env::var("DB_NAME").unwrap_or_else(|_| "zoo_db".to_string());
-
-        println!("Database config: {}:{}/{}", host, port, database);
-
-        Ok(Self {
-            host,
-            port,
-            user,
-            password,
-            database,
-        })
+    pub fn new(config: &DatabaseConfig) -> Self {
+        info!(host = %config.host, port = config.port, database = %config.name, "Database configured");
+
+        let trust = match (&config.ca_certificate, config.trust_server_certificate) {
+            (Some(ca), _) => ServerCertificate::Ca(ca.clone()),
+            (None, true) => {
+                warn!("The SQL Server certificate is not verified (trust_server_certificate)");
+                ServerCertificate::TrustAll
+            }
+            (None, false) => ServerCertificate::SystemRoots,
+        };
+
+        Self {
+            host: config.host.clone(),
+            port: config.port,
+            user: config.user.clone(),
+            password: config.password.clone().unwrap_or_default(),
+            database: config.name.clone(),
+            trust,
+        }
     }
 
     /// Create a new database connection
-    pub async fn connect(
-        &self,
-    ) -> Result<Client<tokio_util::compat::Compat<TcpStream>>, Box<dyn std::error::Error>> {
+    pub async fn connect(&self) -> Result<DbClient, Box<dyn std::error::Error>> {
+        let span = debug_span!("db.connect", host = %self.host, port = self.port);
+        let started = Instant::now();
+        let result = self.open().instrument(span.clone()).await;
+        log_timing(&span, started, result.as_ref().err(), "connect");
+        METRICS.observe_db_connect(started, result.is_err());
+        Ok(DbClient { inner: result? })
+    }
+
+    async fn open(&self) -> Result<Client<Compat<TcpStream>>, Box<dyn std::error::Error>> {
         let mut config = Config::new();
         config.host(&self.host);
         config.port(self.port);
         config.authentication(AuthMethod::sql_server(&self.user, &self.password));
-        config.trust_cert();
+        match &self.trust {
+            ServerCertificate::SystemRoots => {}
+            ServerCertificate::Ca(path) => config.trust_cert_ca(path.display()),
+            ServerCertificate::TrustAll => config.trust_cert(),
+        }
         config.database(&self.database);
 
         let tcp = TcpStream::connect(format!("{}:{}", self.host, self.port)).await?;
         let client = Client::connect(config, tcp.compat_write()).await?;
 
-        println!("Connected to SQL Server successfully");
         Ok(client)
     }
 
@@ -58,9 +96,221 @@ impl Database {
         let mut client = self.connect().await?;
 
         // Simple query to test connection
-        client.simple_query("SELECT 1").await?.into_row().await?;
+        client
+            .inner
+            .simple_query("SELECT 1")
+            .await?
+            .into_row()
+            .await?;
+        client.close().await?;
 
-        println!("Database connection test successful");
+        debug!("Database connection test successful");
         Ok(())
     }
+
+    /// Retry `test_connection` with exponential backoff until the database
+    /// answers. SQL Server in docker compose accepts connections some time
+    /// after its container starts, so the server starts without waiting.
+    pub async fn wait_until_available(&self) {
+        let mut delay = FIRST_RETRY_DELAY;
+        loop {
+            let error =
+                match tokio::time::timeout(STARTUP_CHECK_TIMEOUT, self.test_connection()).await {
+                    Ok(Ok(())) => {
+                        info!("Database available");
+                        return;
+                    }
+                    Ok(Err(e)) => e.to_string(),
+                    Err(_) => format!("no answer within {}s", STARTUP_CHECK_TIMEOUT.as_secs()),
+                };
+            warn!(
+                error = %error,
+                retry_in_secs = delay.as_secs(),
+                "Database not available yet"
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+}
+
+/// A database connection whose statements are logged with their timing, as
+/// child spans of the request being handled.
+///
+/// Timing covers sending the statement until the server starts answering;
+/// reading the rows of a `query` afterwards is not included.
+pub struct DbClient {
+    inner: Client<Compat<TcpStream>>,
+}
+
+impl DbClient {
+    /// Log out and close the connection. Dropping a client closes the socket
+    /// without telling the server; long-running tasks close theirs with this.
+    pub async fn close(self) -> tiberius::Result<()> {
+        self.inner.close().await
+    }
+
+    pub async fn query<'a, 'b>(
+        &'a mut self,
+        query: impl Into<Cow<'b, str>>,
+        params: &'b [&'b dyn ToSql],
+    ) -> tiberius::Result<QueryStream<'a>>
+    where
+        'a: 'b,
+    {
+        let query = query.into();
+        let span = debug_span!("db.query", statement = %statement_summary(&query));
+        let started = Instant::now();
+        let result = self
+            .inner
+            .query(query, params)
+            .instrument(span.clone())
+            .await;
+        log_timing(&span, started, result.as_ref().err(), "query");
+        METRICS.observe_db_statement("query", started, result.is_err());
+        result
+    }
+
+    pub async fn execute<'a>(
+        &mut self,
+        query: impl Into<Cow<'a, str>>,
+        params: &[&dyn ToSql],
+    ) -> tiberius::Result<ExecuteResult> {
+        let query = query.into();
+        let span = debug_span!("db.execute", statement = %statement_summary(&query));
+        let started = Instant::now();
+        let result = self
+            .inner
+            .execute(query, params)
+            .instrument(span.clone())
+            .await;
+        log_timing(&span, started, result.as_ref().err(), "execute");
+        METRICS.observe_db_statement("execute", started, result.is_err());
+        result
+    }
+
+    /// Run a statement without parameters as a plain batch. Transaction
+    /// statements go this way: run through `execute`, they would be inside
+    /// `sp_executesql`, which refuses to return with a transaction open.
+    async fn batch(&mut self, statement: &str) -> tiberius::Result<()> {
+        let span = debug_span!("db.batch", statement = %statement_summary(statement));
+        let started = Instant::now();
+        let result = async {
+            self.inner
+                .simple_query(statement)
+                .await?
+                .into_results()
+                .await
+        }
+        .instrument(span.clone())
+        .await;
+        log_timing(&span, started, result.as_ref().err(), "batch");
+        METRICS.observe_db_statement("batch", started, result.is_err());
+        result.map(|_| ())
+    }
+}
+
+/// Run `work` in a transaction, committed when it returns `Ok` and rolled
+/// back when it returns an error.
+///
+/// Transactions don't nest: `work` must not call anything that starts one.
+pub async fn in_transaction<T>(
+    client: &mut DbClient,
+    work: impl AsyncFnOnce(&mut DbClient) -> Result<T, (StatusCode, String)>,
+) -> Result<T, (StatusCode, String)> {
+    client.batch("BEGIN TRANSACTION").await.map_err(|e| {
+        error!(error = %e, "Transaction error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Transaction error: {}", e),
+        )
+    })?;
+
+    match work(client).await {
+        Ok(value) => {
+            client.batch("COMMIT TRANSACTION").await.map_err(|e| {
+                error!(error = %e, "Commit error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Commit error: {}", e),
+                )
+            })?;
+            Ok(value)
+        }
+        Err(failure) => {
+            // A failed statement may already have ended the transaction
+            if let Err(e) = client
+                .batch("IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION")
+                .await
+            {
+                warn!(error = %e, "Rollback error");
+            }
+            Err(failure)
+        }
+    }
+}
+
+/// Run a query and return the rows of its first result.
+pub async fn fetch_rows(
+    client: &mut DbClient,
+    query: &str,
+    params: &[&dyn ToSql],
+) -> Result<Vec<Row>, (StatusCode, String)> {
+    let stream = client.query(query, params).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })
+}
+
+/// `ISNULL(MAX(id),0)+1` of a table; `table` and `id_column` are inlined, so
+/// only pass constants.
+pub async fn next_id(
+    client: &mut DbClient,
+    table: &str,
+    id_column: &str,
+) -> Result<i32, (StatusCode, String)> {
+    let query = format!(
+        "SELECT ISNULL(MAX({}),0)+1 AS next_id FROM {}",
+        id_column, table
+    );
+    let rows = fetch_rows(client, &query, &[]).await?;
+    rows.first().and_then(|r| r.get::<i32, _>(0)).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to compute next id".to_string(),
+    ))
+}
+
+/// The statement on one line, cut to `MAX_STATEMENT_LENGTH`. Parameter values
+/// are never logged.
+fn statement_summary(query: &str) -> String {
+    let mut summary = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((cut, _)) = summary.char_indices().nth(MAX_STATEMENT_LENGTH) {
+        summary.truncate(cut);
+        summary.push_str("...");
+    }
+    summary
+}
+
+fn log_timing<E: std::fmt::Display>(
+    span: &Span,
+    started: Instant,
+    error: Option<&E>,
+    operation: &str,
+) {
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    match error {
+        Some(e) => warn!(parent: span, elapsed_ms, error = %e, "Database {} failed", operation),
+        None => debug!(parent: span, elapsed_ms, "Database {} finished", operation),
+    }
 }
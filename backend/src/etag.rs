@@ -0,0 +1,123 @@
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+
+/// Strong entity tag for a row version, e.g. `"2031"`.
+pub fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", version)).expect("row version is a valid header value")
+}
+
+/// Whether the request carries an `If-Match` header at all.
+pub fn has_if_match(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::IF_MATCH)
+}
+
+/// Enforce `If-Match` against the current row version.
+///
+/// Requests without the header are let through so existing clients keep
+/// working; clients that send it get a 412 when the row changed underneath.
+pub fn check_if_match(headers: &HeaderMap, current: i64) -> Result<(), (StatusCode, String)> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(());
+    };
+
+    let value = value.to_str().unwrap_or("");
+    if value.trim() == "*" || list_contains(value, current, false) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::PRECONDITION_FAILED,
+            format!(
+                "Resource has been modified; current ETag is \"{}\"",
+                current
+            ),
+        ))
+    }
+}
+
+/// Whether `If-None-Match` matches the current row version, meaning the
+/// client's cached copy is still fresh and a 304 can be sent instead.
+pub fn if_none_match(headers: &HeaderMap, current: i64) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim() == "*" || list_contains(v, current, true))
+        .unwrap_or(false)
+}
+
+/// Check a comma-separated list of entity tags for `version`. Weak tags
+/// (`W/"..."`) only count when `allow_weak` is set, as `If-Match` requires
+/// the strong comparison.
+fn list_contains(list: &str, version: i64, allow_weak: bool) -> bool {
+    let wanted = version.to_string();
+    list.split(',').map(str::trim).any(|tag| {
+        let (weak, tag) = match tag.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, tag),
+        };
+        (allow_weak || !weak) && tag.trim_matches('"') == wanted
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn etag_is_the_quoted_version() {
+        assert_eq!(etag(2031), "\"2031\"");
+    }
+
+    #[test]
+    fn list_contains_finds_a_tag_in_a_comma_separated_list() {
+        assert!(list_contains("\"7\"", 7, false));
+        assert!(list_contains("\"5\", \"7\" ,\"9\"", 7, false));
+        assert!(!list_contains("\"5\", \"9\"", 7, false));
+        assert!(!list_contains("\"70\"", 7, false));
+        assert!(!list_contains("", 7, false));
+    }
+
+    #[test]
+    fn list_contains_compares_weak_tags_only_when_allowed() {
+        assert!(!list_contains("W/\"7\"", 7, false));
+        assert!(list_contains("W/\"7\"", 7, true));
+        assert!(list_contains("W/\"5\", \"7\"", 7, false));
+    }
+
+    #[test]
+    fn if_match_accepts_the_current_version_or_any() {
+        assert!(check_if_match(&HeaderMap::new(), 7).is_ok());
+        assert!(check_if_match(&headers(header::IF_MATCH, "\"7\""), 7).is_ok());
+        assert!(check_if_match(&headers(header::IF_MATCH, " * "), 7).is_ok());
+        assert!(check_if_match(&headers(header::IF_MATCH, "\"3\", \"7\""), 7).is_ok());
+    }
+
+    #[test]
+    fn if_match_rejects_stale_and_weak_tags() {
+        for value in ["\"6\"", "W/\"7\"", "\"6\", W/\"7\""] {
+            let (status, _) = check_if_match(&headers(header::IF_MATCH, value), 7).unwrap_err();
+            assert_eq!(
+                status,
+                StatusCode::PRECONDITION_FAILED,
+                "If-Match: {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn if_none_match_uses_the_weak_comparison() {
+        assert!(!if_none_match(&HeaderMap::new(), 7));
+        assert!(if_none_match(&headers(header::IF_NONE_MATCH, "W/\"7\""), 7));
+        assert!(if_none_match(&headers(header::IF_NONE_MATCH, "*"), 7));
+        assert!(if_none_match(
+            &headers(header::IF_NONE_MATCH, "\"1\", \"7\""),
+            7
+        ));
+        assert!(!if_none_match(&headers(header::IF_NONE_MATCH, "\"8\""), 7));
+    }
+}
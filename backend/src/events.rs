@@ -0,0 +1,256 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// How many past events are kept for `Last-Event-ID` resume
+const HISTORY_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Animal,
+    Care,
+    AnimalCare,
+}
+
+impl EntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Animal => "animal",
+            EntityKind::Care => "care",
+            EntityKind::AnimalCare => "animal_care",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "animal" | "animals" => Some(EntityKind::Animal),
+            "care" | "cares" => Some(EntityKind::Care),
+            "animal_care" | "animal-care" | "animal-cares" => Some(EntityKind::AnimalCare),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deactivated,
+    Deleted,
+}
+
+impl ChangeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+            ChangeAction::Deactivated => "deactivated",
+            ChangeAction::Deleted => "deleted",
+        }
+    }
+}
+
+/// A change to a record, as broadcast to live subscribers.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    pub id: u64,
+    pub entity: EntityKind,
+    pub action: ChangeAction,
+    pub entity_id: i32,
+    pub occurred_at: NaiveDateTime,
+    /// The record after the change, when there still is one
+    pub data: Option<serde_json::Value>,
+}
+
+impl ChangeEvent {
+    /// Event type name, e.g. `animal.created`
+    pub fn event_type(&self) -> String {
+        format!("{}.{}", self.entity.as_str(), self.action.as_str())
+    }
+}
+
+/// In-process fan-out of change events to connected clients.
+///
+/// Keeps a bounded history so reconnecting clients can catch up on what they
+/// missed instead of refetching everything.
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    sender: broadcast::Sender<ChangeEvent>,
+    history: Mutex<VecDeque<ChangeEvent>>,
+    next_id: AtomicU64,
+}
+
+/// Events a reconnecting client missed.
+pub enum Replay {
+    Events(Vec<ChangeEvent>),
+    /// The requested id is no longer (or never was) in the history, e.g.
+    /// after a server restart. The client has to refetch its data.
+    Gap,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(HISTORY_CAPACITY);
+        Self {
+            inner: Arc::new(Inner {
+                sender,
+                history: Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+                next_id: AtomicU64::new(1),
+            }),
+        }
+    }
+
+    pub fn publish<T: Serialize>(
+        &self,
+        entity: EntityKind,
+        action: ChangeAction,
+        entity_id: i32,
+        data: Option<&T>,
+    ) {
+        // Ids are assigned under the history lock so the history and the
+        // broadcast see events in the same order as their ids.
+        let mut history = self.inner.history.lock().unwrap();
+        let event = ChangeEvent {
+            id: self.inner.next_id.fetch_add(1, Ordering::SeqCst),
+            entity,
+            action,
+            entity_id,
+            occurred_at: Utc::now().naive_utc(),
+            data: data.and_then(|d| serde_json::to_value(d).ok()),
+        };
+
+        if history.len() == HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(event.clone());
+
+        // No receivers just means nobody is listening right now
+        let _ = self.inner.sender.send(event);
+    }
+
+    /// Publish a change that leaves no record to send, e.g. a deletion.
+    pub fn publish_without_data(&self, entity: EntityKind, action: ChangeAction, entity_id: i32) {
+        self.publish::<()>(entity, action, entity_id, None);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.inner.sender.subscribe()
+    }
+
+    /// Events published after `last_event_id`.
+    pub fn replay_after(&self, last_event_id: u64) -> Replay {
+        let history = self.inner.history.lock().unwrap();
+        let latest = self.inner.next_id.load(Ordering::SeqCst) - 1;
+
+        if last_event_id > latest {
+            return Replay::Gap;
+        }
+        if let Some(oldest) = history.front()
+            && last_event_id + 1 < oldest.id
+        {
+            return Replay::Gap;
+        }
+
+        Replay::Events(
+            history
+                .iter()
+                .filter(|e| e.id > last_event_id)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publish(bus: &EventBus, count: usize) {
+        for entity_id in 1..=count as i32 {
+            bus.publish(
+                EntityKind::Animal,
+                ChangeAction::Updated,
+                entity_id,
+                Some(&entity_id),
+            );
+        }
+    }
+
+    fn replayed_ids(bus: &EventBus, last_event_id: u64) -> Vec<u64> {
+        match bus.replay_after(last_event_id) {
+            Replay::Events(events) => events.iter().map(|e| e.id).collect(),
+            Replay::Gap => panic!("expected events after {}, got a gap", last_event_id),
+        }
+    }
+
+    #[test]
+    fn a_new_bus_has_nothing_to_replay() {
+        assert!(replayed_ids(&EventBus::new(), 0).is_empty());
+    }
+
+    #[test]
+    fn replay_returns_the_events_after_the_last_id_in_order() {
+        let bus = EventBus::new();
+        publish(&bus, 5);
+
+        assert_eq!(replayed_ids(&bus, 0), [1, 2, 3, 4, 5]);
+        assert_eq!(replayed_ids(&bus, 3), [4, 5]);
+        assert!(replayed_ids(&bus, 5).is_empty());
+
+        let Replay::Events(events) = bus.replay_after(4) else {
+            panic!("expected the last event");
+        };
+        assert_eq!(events[0].entity_id, 5);
+        assert_eq!(events[0].event_type(), "animal.updated");
+        assert_eq!(events[0].data, Some(serde_json::json!(5)));
+    }
+
+    #[test]
+    fn an_id_that_was_never_published_is_a_gap() {
+        let bus = EventBus::new();
+        publish(&bus, 2);
+        assert!(matches!(bus.replay_after(3), Replay::Gap));
+    }
+
+    #[test]
+    fn an_id_evicted_from_the_history_is_a_gap() {
+        let bus = EventBus::new();
+        publish(&bus, HISTORY_CAPACITY + 2);
+
+        // Events 1 and 2 are gone, so a client that saw only event 1 missed one
+        assert!(matches!(bus.replay_after(0), Replay::Gap));
+        assert!(matches!(bus.replay_after(1), Replay::Gap));
+
+        let ids = replayed_ids(&bus, 2);
+        assert_eq!(ids.len(), HISTORY_CAPACITY);
+        assert_eq!(ids.first(), Some(&3));
+        assert_eq!(ids.last(), Some(&(HISTORY_CAPACITY as u64 + 2)));
+    }
+
+    #[test]
+    fn subscribers_receive_what_is_published() {
+        let bus = EventBus::new();
+        let mut receiver = bus.subscribe();
+        bus.publish_without_data(EntityKind::Care, ChangeAction::Deleted, 7);
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.id, 1);
+        assert_eq!(event.event_type(), "care.deleted");
+        assert_eq!(event.data, None);
+    }
+}
@@ -1,17 +1,32 @@
-use crate::db::Database;
+use crate::config::Config;
+use crate::db::{Database, DbClient, in_transaction};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::idempotency::{self, Begin};
 use crate::models::{AnimalCare, CreateAnimalCare};
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use chrono::NaiveDate;
+use std::sync::Arc;
+use tracing::error;
 
+#[utoipa::path(
+    get,
+    path = "/animal-cares",
+    tag = "animal-cares",
+    summary = "List all animal-care records",
+    responses(
+        (status = 200, description = "All animal-care records", body = Vec<AnimalCare>),
+    )
+)]
 pub async fn get_animal_cares(
     State(db): State<Database>,
 ) -> Result<Json<Vec<AnimalCare>>, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
@@ -21,7 +36,7 @@ pub async fn get_animal_cares(
     let query = "SELECT date_of_care, fk_Cares_cares_id, fk_Animal_animal_id, animal_care_id FROM Animal_Care_have ORDER BY animal_care_id";
 
     let stream = client.query(query, &[]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -29,7 +44,7 @@ pub async fn get_animal_cares(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -49,12 +64,25 @@ pub async fn get_animal_cares(
     Ok(Json(animal_cares))
 }
 
+#[utoipa::path(
+    get,
+    path = "/animal-cares/{id}",
+    tag = "animal-cares",
+    summary = "Get animal-care record by id",
+    params(
+        ("id" = i32, Path, description = "Animal-care id"),
+    ),
+    responses(
+        (status = 200, description = "The animal-care record", body = AnimalCare),
+        (status = 404, description = "Record not found", body = String),
+    )
+)]
 pub async fn get_animal_care_by_id(
     State(db): State<Database>,
     Path(id): Path<i32>,
 ) -> Result<Json<AnimalCare>, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
@@ -64,7 +92,7 @@ pub async fn get_animal_care_by_id(
     let query = "SELECT date_of_care, fk_Cares_cares_id, fk_Animal_animal_id, animal_care_id FROM Animal_Care_have WHERE animal_care_id = @P1";
 
     let stream = client.query(query, &[&id]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -72,7 +100,7 @@ pub async fn get_animal_care_by_id(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -95,22 +123,22 @@ pub async fn get_animal_care_by_id(
     }
 }
 
-pub async fn get_animal_care_by_animal_id(
-    State(db): State<Database>,
-    Path(id): Path<i32>,
-) -> Result<Json<AnimalCare>, (StatusCode, String)> {
+async fn find_animal_cares_by_animal_id(
+    db: &Database,
+    id: i32,
+) -> Result<Vec<AnimalCare>, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query = "SELECT date_of_care, fk_Cares_cares_id, fk_Animal_animal_id, animal_care_id FROM Animal_Care_have WHERE fk_Animal_animal_id = @P1";
+    let query = "SELECT date_of_care, fk_Cares_cares_id, fk_Animal_animal_id, animal_care_id FROM Animal_Care_have WHERE fk_Animal_animal_id = @P1 ORDER BY date_of_care DESC, animal_care_id DESC";
 
     let stream = client.query(query, &[&id]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -118,41 +146,130 @@ pub async fn get_animal_care_by_animal_id(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
         )
     })?;
 
-    if let Some(row) = rows.first() {
-        let animal_care = AnimalCare {
+    Ok(rows
+        .iter()
+        .map(|row| AnimalCare {
             date_of_care: row.get(0),
             fk_cares_cares_id: row.get::<i32, _>(1).unwrap_or(0),
             fk_animal_animal_id: row.get::<i32, _>(2).unwrap_or(0),
             animal_care_id: row.get::<i32, _>(3).unwrap_or(0),
-        };
-        Ok(Json(animal_care))
-    } else {
-        Err((
+        })
+        .collect())
+}
+
+/// Legacy route: answers with a single care record of the animal.
+pub async fn get_animal_care_by_animal_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<AnimalCare>, (StatusCode, String)> {
+    find_animal_cares_by_animal_id(&db, id)
+        .await?
+        .into_iter()
+        .next()
+        .map(Json)
+        .ok_or((
             StatusCode::NOT_FOUND,
             format!("Animal care with id {} not found", id),
         ))
-    }
 }
 
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/cares",
+    tag = "animal-cares",
+    summary = "List the cares given to an animal, most recent first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 200, description = "Care records of the animal", body = Vec<AnimalCare>),
+    )
+)]
+pub async fn get_cares_of_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<AnimalCare>>, (StatusCode, String)> {
+    Ok(Json(find_animal_cares_by_animal_id(&db, id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animal-cares",
+    tag = "animal-cares",
+    summary = "Log a care for an animal",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a create is retried"),
+    ),
+    request_body = CreateAnimalCare,
+    responses(
+        (status = 201, description = "Care logged for the animal", body = AnimalCare),
+        (status = 409, description = "Same Idempotency-Key still in progress", body = String),
+        (status = 422, description = "Idempotency-Key reused with another body", body = String),
+    )
+)]
 pub async fn add_animal_care(
     State(db): State<Database>,
+    State(events): State<EventBus>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
     Json(payload): Json<CreateAnimalCare>,
-) -> Result<(StatusCode, Json<AnimalCare>), (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
+    let reservation = match idempotency::begin(
+        &mut client,
+        &headers,
+        "POST /animal-cares/add",
+        &payload,
+        config.idempotency.ttl_seconds,
+    )
+    .await?
+    {
+        Begin::Replay(response) => return Ok(response),
+        Begin::Proceed(reservation) => reservation,
+    };
+
+    let result = in_transaction(&mut client, async |client| {
+        let created = insert_animal_care(client, payload).await?;
+        idempotency::store(client, reservation.as_ref(), StatusCode::CREATED, &created).await?;
+        Ok(created)
+    })
+    .await;
+    let created = match result {
+        Ok(created) => created,
+        Err(failure) => {
+            idempotency::release(&mut client, reservation).await;
+            return Err(failure);
+        }
+    };
+
+    events.publish(
+        EntityKind::AnimalCare,
+        ChangeAction::Created,
+        created.animal_care_id,
+        Some(&created),
+    );
+
+    Ok((StatusCode::CREATED, Json(created)).into_response())
+}
+
+async fn insert_animal_care(
+    client: &mut DbClient,
+    payload: CreateAnimalCare,
+) -> Result<AnimalCare, (StatusCode, String)> {
     // Parse date
     let parsed_date: Option<NaiveDate> = if let Some(d) = &payload.date_of_care {
         if d.contains('/') {
@@ -166,14 +283,14 @@ pub async fn add_animal_care(
 
     let id_query = "SELECT ISNULL(MAX(animal_care_id),0)+1 AS next_id FROM Animal_Care_have";
     let id_stream = client.query(id_query, &[]).await.map_err(|e| {
-        eprintln!("ID query error: {}", e);
+        error!(error = %e, "ID query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID query error: {}", e),
         )
     })?;
     let id_rows = id_stream.into_first_result().await.map_err(|e| {
-        eprintln!("ID result error: {}", e);
+        error!(error = %e, "ID result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID result error: {}", e),
@@ -201,7 +318,7 @@ pub async fn add_animal_care(
         )
         .await
         .map_err(|e| {
-            eprintln!("Insert error: {}", e);
+            error!(error = %e, "Insert error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Insert error: {}", e),
@@ -215,5 +332,5 @@ pub async fn add_animal_care(
         fk_animal_animal_id: payload.fk_animal_animal_id,
     };
 
-    Ok((StatusCode::CREATED, Json(created)))
+    Ok(created)
 }
@@ -0,0 +1,343 @@
+use crate::db::{Database, DbClient, in_transaction};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::etag::etag;
+use crate::handlers::animals::{animal_write_precondition, find_animal};
+use crate::handlers::movements::{NewMovement, record_movement};
+use crate::models::{Animal, AnimalRevision, AnimalRevisionWithChanges, in_collection_sql};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDateTime;
+use tiberius::Row;
+use tracing::error;
+
+const REVISION_COLUMNS: &str = "animal_revision_id, fk_Animal_animal_id, revision_number, name, specie, habitat, description, country_of_origin, date_of_birth, is_active, change_type, reverted_from_revision, changed_at, fk_Species_species_id, status, status_date, status_reason, fk_Sire_Animal_id, fk_Sire_External_Animal_id, fk_Dam_Animal_id, fk_Dam_External_Animal_id";
+
+fn revision_from_row(row: &Row) -> AnimalRevision {
+    AnimalRevision {
+        animal_revision_id: row.get::<i32, _>(0).unwrap_or(0),
+        fk_animal_animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        revision_number: row.get::<i32, _>(2).unwrap_or(0),
+        name: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        specie: row.get::<&str, _>(4).unwrap_or("").to_string(),
+        habitat: row.get::<&str, _>(5).map(|s| s.to_string()),
+        description: row.get::<&str, _>(6).map(|s| s.to_string()),
+        country_of_origin: row.get::<&str, _>(7).map(|s| s.to_string()),
+        date_of_birth: row.get(8),
+        is_active: row.get::<bool, _>(9).unwrap_or(false),
+        change_type: row.get::<&str, _>(10).unwrap_or("").to_string(),
+        reverted_from_revision: row.get::<i32, _>(11),
+        changed_at: row.get(12),
+        species_id: row.get::<i32, _>(13),
+        status: row.get::<&str, _>(14).unwrap_or("").to_string(),
+        status_date: row.get(15),
+        status_reason: row.get::<&str, _>(16).map(|s| s.to_string()),
+        sire_id: row.get::<i32, _>(17),
+        sire_external_id: row.get::<i32, _>(18),
+        dam_id: row.get::<i32, _>(19),
+        dam_external_id: row.get::<i32, _>(20),
+    }
+}
+
+/// Snapshot the current state of an animal into `Animal_Revision`.
+///
+/// Called by every handler that writes to `Animal`, right after the write
+/// and in the same transaction, so the history can't miss a change.
+pub async fn record_animal_revision(
+    client: &mut DbClient,
+    animal_id: i32,
+    change_type: &str,
+    reverted_from_revision: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let insert_query = format!(
+        r#"
+        INSERT INTO Animal_Revision (animal_revision_id, fk_Animal_animal_id, revision_number, name, specie, fk_Species_species_id, habitat, description, country_of_origin, date_of_birth, is_active, status, status_date, status_reason, fk_Sire_Animal_id, fk_Sire_External_Animal_id, fk_Dam_Animal_id, fk_Dam_External_Animal_id, change_type, reverted_from_revision, changed_at)
+        SELECT
+            (SELECT ISNULL(MAX(animal_revision_id),0)+1 FROM Animal_Revision),
+            a.animal_id,
+            (SELECT ISNULL(MAX(revision_number),0)+1 FROM Animal_Revision WHERE fk_Animal_animal_id = a.animal_id),
+            a.name, s.common_name_pt, a.fk_Species_species_id, a.habitat, a.description, a.country_of_origin, a.date_of_birth,
+            CASE WHEN a.status IN {} THEN 1 ELSE 0 END, a.status, a.status_date, a.status_reason,
+            a.fk_Sire_Animal_id, a.fk_Sire_External_Animal_id, a.fk_Dam_Animal_id, a.fk_Dam_External_Animal_id,
+            @P2, @P3, SYSUTCDATETIME()
+        FROM Animal a
+        LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id
+        WHERE a.animal_id = @P1
+    "#,
+        in_collection_sql()
+    );
+
+    client
+        .execute(
+            insert_query,
+            &[&animal_id, &change_type, &reverted_from_revision],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Revision insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Revision insert error: {}", e),
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Latest revision of an animal recorded at or before `as_of`.
+pub async fn find_animal_revision_as_of(
+    client: &mut DbClient,
+    animal_id: i32,
+    as_of: NaiveDateTime,
+) -> Result<Option<AnimalRevision>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT TOP 1 {} FROM Animal_Revision WHERE fk_Animal_animal_id = @P1 AND changed_at <= @P2 ORDER BY revision_number DESC",
+        REVISION_COLUMNS
+    );
+
+    let stream = client.query(query, &[&animal_id, &as_of]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(rows.first().map(revision_from_row))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/revisions",
+    tag = "animals",
+    summary = "List animal revisions with diffs",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 200, description = "Revisions with the fields each one changed", body = Vec<AnimalRevisionWithChanges>),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_animal_revisions(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<AnimalRevisionWithChanges>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "SELECT {} FROM Animal_Revision WHERE fk_Animal_animal_id = @P1 ORDER BY revision_number",
+        REVISION_COLUMNS
+    );
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    if rows.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No revisions found for animal with id {}", id),
+        ));
+    }
+
+    let revisions: Vec<AnimalRevision> = rows.iter().map(revision_from_row).collect();
+
+    let with_changes = revisions
+        .iter()
+        .enumerate()
+        .map(|(i, revision)| AnimalRevisionWithChanges {
+            revision: revision.clone(),
+            changes: revision.diff(i.checked_sub(1).map(|p| &revisions[p])),
+        })
+        .collect();
+
+    Ok(Json(with_changes))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/revisions/{revision}/revert",
+    tag = "animals",
+    summary = "Revert animal to a revision",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("revision" = i32, Path, description = "Revision number to restore"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being changed; 412 if it is stale"),
+    ),
+    responses(
+        (status = 200, description = "Animal as restored, with its new ETag", body = Animal),
+        (status = 404, description = "Animal or revision not found", body = String),
+        (status = 412, description = "If-Match is stale", body = String),
+    )
+)]
+pub async fn revert_animal(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path((id, revision_number)): Path<(i32, i32)>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "SELECT {} FROM Animal_Revision WHERE fk_Animal_animal_id = @P1 AND revision_number = @P2",
+        REVISION_COLUMNS
+    );
+
+    let stream = client
+        .query(query, &[&id, &revision_number])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Query error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Query error: {}", e),
+            )
+        })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    let target = rows.first().map(revision_from_row).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Revision {} of animal {} not found", revision_number, id),
+    ))?;
+
+    let expected_version = animal_write_precondition(&mut client, &headers, id).await?;
+
+    // Reverting restores the animal's data; changing its status is not
+    // something a revert should do implicitly, and parents are only set
+    // through their own endpoint, which checks the pedigree. A species
+    // deleted since the revision leaves the current one in place.
+    let update_query = format!(
+        r#"
+        UPDATE Animal
+        SET name = @P2,
+            fk_Species_species_id = COALESCE((SELECT species_id FROM Species WHERE species_id = @P3), fk_Species_species_id),
+            habitat = @P4,
+            description = @P5,
+            country_of_origin = @P6,
+            date_of_birth = @P7
+        WHERE animal_id = @P1 AND status IN {}
+          AND (@P8 IS NULL OR CAST(row_version AS BIGINT) = @P8)
+    "#,
+        in_collection_sql()
+    );
+
+    in_transaction(&mut client, async |client| {
+        // A restored habitat is a move like any other, so it goes to the log
+        let previous_habitat = find_animal(client, id)
+            .await?
+            .and_then(|animal| animal.habitat);
+
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &target.name,
+                    &target.species_id,
+                    &target.habitat,
+                    &target.description,
+                    &target.country_of_origin,
+                    &target.date_of_birth,
+                    &expected_version,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Revert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Revert error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            if expected_version.is_some() {
+                return Err((
+                    StatusCode::PRECONDITION_FAILED,
+                    format!("Animal with id {} was modified concurrently", id),
+                ));
+            }
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found or no longer in the collection", id),
+            ));
+        }
+
+        record_animal_revision(client, id, "reverted", Some(revision_number)).await?;
+        if previous_habitat.as_deref().map(str::trim) != target.habitat.as_deref().map(str::trim) {
+            record_movement(
+                client,
+                NewMovement {
+                    animal_id: id,
+                    movement_type: "internal",
+                    from_enclosure_id: None,
+                    from_location: previous_habitat,
+                    to_enclosure_id: None,
+                    to_location: target.habitat.clone(),
+                    institution: None,
+                    is_loan: false,
+                    moved_on: None,
+                    reason: Some(format!("Reverted to revision {}", revision_number)),
+                    responsible_staff: None,
+                },
+            )
+            .await?;
+        }
+        Ok(())
+    })
+    .await?;
+
+    let animal = find_animal(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch reverted animal".to_string(),
+    ))?;
+    events.publish(EntityKind::Animal, ChangeAction::Updated, id, Some(&animal));
+
+    match animal.version {
+        Some(version) => Ok(([(header::ETAG, etag(version))], Json(animal)).into_response()),
+        None => Ok(Json(animal).into_response()),
+    }
+}
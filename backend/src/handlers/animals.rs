@@ -1,27 +1,263 @@
-use crate::db::Database;
-use crate::models::{Animal, CreateAnimal, UpdateAnimal};
+use crate::config::Config;
+use crate::db::{Database, DbClient, in_transaction};
+use crate::etag::{check_if_match, etag, has_if_match, if_none_match};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::handlers::animal_revisions::{find_animal_revision_as_of, record_animal_revision};
+use crate::handlers::enclosures::leave_enclosure;
+use crate::handlers::movements::{NewMovement, record_movement};
+use crate::handlers::species::resolve_species;
+use crate::idempotency::{self, Begin};
+use crate::models::{
+    ANIMAL_STATUSES, Animal, AnimalListQuery, AsOfQuery, ChangeAnimalStatus, CreateAnimal,
+    IN_COLLECTION_STATUSES, INITIAL_STATUSES, UpdateAnimal, in_collection_sql, status_transition_allowed,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
 use axum::{
     Json,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
+
+/// Animals with their species name; callers add the `WHERE`/`ORDER BY`.
+const ANIMAL_SELECT: &str = "SELECT a.animal_id, a.name, s.common_name_pt, a.habitat, a.description, a.country_of_origin, a.date_of_birth, CAST(a.row_version AS BIGINT), a.fk_Species_species_id, a.status, a.status_date, a.status_reason, a.fk_Sire_Animal_id, a.fk_Sire_External_Animal_id, a.fk_Dam_Animal_id, a.fk_Dam_External_Animal_id FROM Animal a LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id";
+
+fn animal_from_row(row: &Row) -> Animal {
+    Animal {
+        animal_id: row.get::<i32, _>(0).unwrap_or(0),
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        specie: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        habitat: row.get::<&str, _>(3).map(|s| s.to_string()),
+        description: row.get::<&str, _>(4).map(|s| s.to_string()),
+        country_of_origin: row.get::<&str, _>(5).map(|s| s.to_string()),
+        date_of_birth: row.get(6),
+        version: row.get::<i64, _>(7),
+        species_id: row.get::<i32, _>(8),
+        status: row.get::<&str, _>(9).unwrap_or("").to_string(),
+        status_date: row.get(10),
+        status_reason: row.get::<&str, _>(11).map(|s| s.to_string()),
+        sire_id: row.get::<i32, _>(12),
+        sire_external_id: row.get::<i32, _>(13),
+        dam_id: row.get::<i32, _>(14),
+        dam_external_id: row.get::<i32, _>(15),
+    }
+}
+
+/// An animal in any status, or `None` if it doesn't exist.
+pub async fn find_animal(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<Animal>, (StatusCode, String)> {
+    let query = format!("{} WHERE a.animal_id = @P1", ANIMAL_SELECT);
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(rows.first().map(animal_from_row))
+}
+
+/// Whether an animal in `status` is physically at the zoo, and so can be
+/// in an enclosure.
+pub fn status_on_site(status: &str) -> bool {
+    matches!(status, "arrived" | "born" | "active")
+}
+
+/// Move an animal to another lifecycle status, if the state machine allows
+/// it, and return the status it had. Animals that leave the zoo, even on
+/// loan, leave their enclosure too.
+///
+/// Doesn't start a transaction, so callers can change the status together
+/// with whatever goes with it; `transition_animal_status` is the standalone
+/// version.
+pub async fn apply_status_transition(
+    client: &mut DbClient,
+    id: i32,
+    status: &str,
+    date: Option<NaiveDate>,
+    reason: Option<&str>,
+) -> Result<String, (StatusCode, String)> {
+    let current = find_animal(client, id)
+        .await?
+        .map(|animal| animal.status)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ))?;
+
+    if !status_transition_allowed(&current, status) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Animal with id {} can't go from '{}' to '{}'",
+                id, current, status
+            ),
+        ));
+    }
+
+    let query = "UPDATE Animal SET status = @P2, status_date = ISNULL(@P3, CAST(SYSUTCDATETIME() AS DATE)), status_reason = @P4 WHERE animal_id = @P1 AND status = @P5";
+
+    let rows_affected = client
+        .execute(query, &[&id, &status, &date, &reason, &current.as_str()])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Animal with id {} was modified concurrently", id),
+        ));
+    }
+
+    if !status_on_site(status) {
+        leave_enclosure(client, id).await?;
+    }
+    record_animal_revision(client, id, "status_changed", None).await?;
+
+    Ok(current)
+}
+
+/// `apply_status_transition` in a transaction of its own.
+pub async fn transition_animal_status(
+    client: &mut DbClient,
+    id: i32,
+    status: &str,
+    date: Option<NaiveDate>,
+    reason: Option<&str>,
+) -> Result<String, (StatusCode, String)> {
+    in_transaction(client, async |client| {
+        apply_status_transition(client, id, status, date, reason).await
+    })
+    .await
+}
+
+/// Current row version of an animal in the collection, or `None` if it doesn't exist.
+pub async fn current_animal_version(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<i64>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT CAST(row_version AS BIGINT) FROM Animal WHERE animal_id = @P1 AND status IN {}",
+        in_collection_sql()
+    );
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Version query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Version query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(rows.first().and_then(|r| r.get::<i64, _>(0)))
+}
+
+/// Resolve the `If-Match` precondition for a write to an active animal.
+///
+/// Returns the version the write must still see (so it can be checked again
+/// atomically in the `WHERE` clause), or `None` when the client sent no
+/// `If-Match` header.
+pub async fn animal_write_precondition(
+    client: &mut DbClient,
+    headers: &HeaderMap,
+    id: i32,
+) -> Result<Option<i64>, (StatusCode, String)> {
+    if !has_if_match(headers) {
+        return Ok(None);
+    }
+
+    let current = current_animal_version(client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Animal with id {} not found or no longer in the collection", id),
+    ))?;
+    check_if_match(headers, current)?;
+
+    Ok(Some(current))
+}
 
+#[utoipa::path(
+    get,
+    path = "/animals",
+    tag = "animals",
+    summary = "List animals, by default those in the collection",
+    params(AnimalListQuery),
+    responses(
+        (status = 200, description = "Animals with the requested statuses", body = Vec<Animal>),
+        (status = 400, description = "Unknown status", body = String),
+    )
+)]
 pub async fn get_animals(
     State(db): State<Database>,
+    Query(params): Query<AnimalListQuery>,
 ) -> Result<Json<Vec<Animal>>, (StatusCode, String)> {
+    // Statuses are checked against the known list, so they can be inlined
+    let status_filter = match params.status.as_deref().map(str::trim) {
+        None | Some("") => format!("a.status IN {}", in_collection_sql()),
+        Some("all") => "1 = 1".to_string(),
+        Some(list) => {
+            let mut statuses = Vec::new();
+            for status in list.split(',').map(str::trim) {
+                if !ANIMAL_STATUSES.contains(&status) {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Unknown status '{}'; expected all or any of {}",
+                            status,
+                            ANIMAL_STATUSES.join(", ")
+                        ),
+                    ));
+                }
+                statuses.push(format!("'{}'", status));
+            }
+            format!("a.status IN ({})", statuses.join(", "))
+        }
+    };
+
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query = "SELECT animal_id, name, specie, habitat, description, country_of_origin, date_of_birth FROM Animal WHERE is_active = 1 ORDER BY animal_id";
+    let query = format!(
+        "{} WHERE {} ORDER BY a.animal_id",
+        ANIMAL_SELECT, status_filter
+    );
 
     let stream = client.query(query, &[]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -29,7 +265,7 @@ pub async fn get_animals(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -38,36 +274,80 @@ pub async fn get_animals(
 
     let animals: Vec<Animal> = rows
         .iter()
-        .map(|row| Animal {
-            animal_id: row.get::<i32, _>(0).unwrap_or(0),
-            name: row.get::<&str, _>(1).unwrap_or("").to_string(),
-            specie: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            habitat: row.get::<&str, _>(3).map(|s| s.to_string()),
-            description: row.get::<&str, _>(4).map(|s| s.to_string()),
-            country_of_origin: row.get::<&str, _>(5).map(|s| s.to_string()),
-            date_of_birth: row.get(6),
-        })
+        .map(animal_from_row)
         .collect();
 
     Ok(Json(animals))
 }
 
+#[utoipa::path(
+    get,
+    path = "/animals/{id}",
+    tag = "animals",
+    summary = "Get animal by id",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        AsOfQuery,
+        ("If-None-Match" = Option<String>, Header, description = "Answer 304 if the ETag still matches"),
+    ),
+    responses(
+        (status = 200, description = "The animal, with its ETag", body = Animal),
+        (status = 304, description = "Not modified"),
+        (status = 400, description = "Invalid as_of", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
 pub async fn get_animal_by_id(
     State(db): State<Database>,
     Path(id): Path<i32>,
-) -> Result<Json<Animal>, (StatusCode, String)> {
+    Query(params): Query<AsOfQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    // Accept either a date (meaning the end of that day) or a full timestamp
+    let as_of: Option<NaiveDateTime> = match &params.as_of {
+        Some(s) => Some(
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|d| d.and_hms_micro_opt(23, 59, 59, 999_999))
+                })
+                .ok_or((
+                    StatusCode::BAD_REQUEST,
+                    "as_of must be YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS".to_string(),
+                ))?,
+        ),
+        None => None,
+    };
+
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query = "SELECT animal_id, name, specie, habitat, description, country_of_origin, date_of_birth FROM Animal WHERE animal_id = @P1 AND is_active = 1";
+    if let Some(as_of) = as_of {
+        return match find_animal_revision_as_of(&mut client, id, as_of).await? {
+            Some(revision) if revision.status != "removed" => {
+                Ok(Json(revision.into_animal()).into_response())
+            }
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found as of {}", id, as_of),
+            )),
+        };
+    }
+
+    let query = format!(
+        "{} WHERE a.animal_id = @P1 AND a.status <> 'removed'",
+        ANIMAL_SELECT
+    );
 
     let stream = client.query(query, &[&id]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -75,7 +355,7 @@ pub async fn get_animal_by_id(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -83,16 +363,14 @@ pub async fn get_animal_by_id(
     })?;
 
     if let Some(row) = rows.first() {
-        let animal = Animal {
-            animal_id: row.get::<i32, _>(0).unwrap_or(0),
-            name: row.get::<&str, _>(1).unwrap_or("").to_string(),
-            specie: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            habitat: row.get::<&str, _>(3).map(|s| s.to_string()),
-            description: row.get::<&str, _>(4).map(|s| s.to_string()),
-            country_of_origin: row.get::<&str, _>(5).map(|s| s.to_string()),
-            date_of_birth: row.get(6),
+        let animal = animal_from_row(row);
+        let Some(version) = animal.version else {
+            return Ok(Json(animal).into_response());
         };
-        Ok(Json(animal))
+        if if_none_match(&headers, version) {
+            return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag(version))]).into_response());
+        }
+        Ok(([(header::ETAG, etag(version))], Json(animal)).into_response())
     } else {
         Err((
             StatusCode::NOT_FOUND,
@@ -101,10 +379,29 @@ pub async fn get_animal_by_id(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/animals",
+    tag = "animals",
+    summary = "Add new animal",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a create is retried"),
+    ),
+    request_body = CreateAnimal,
+    responses(
+        (status = 201, description = "Animal created", body = Animal),
+        (status = 400, description = "Invalid animal", body = String),
+        (status = 409, description = "Same Idempotency-Key still in progress", body = String),
+        (status = 422, description = "Idempotency-Key reused with another body", body = String),
+    )
+)]
 pub async fn add_animal(
     State(db): State<Database>,
+    State(events): State<EventBus>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
     Json(payload): Json<CreateAnimal>,
-) -> Result<(StatusCode, Json<Animal>), (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
 
     if payload.name.trim().is_empty() {
         return Err((
@@ -112,21 +409,84 @@ pub async fn add_animal(
             "Name is required and cannot be empty".to_string(),
         ));
     }
-    if payload.specie.trim().is_empty() {
+    if payload.species_id.is_none()
+        && payload.specie.as_deref().is_none_or(|s| s.trim().is_empty())
+    {
         return Err((
             StatusCode::BAD_REQUEST,
-            "Specie is required and cannot be empty".to_string(),
+            "Specie or species_id is required".to_string(),
         ));
     }
+    if let Some(status) = &payload.status
+        && !INITIAL_STATUSES.contains(&status.as_str())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "New animals can't have status '{}'; expected one of {}",
+                status,
+                INITIAL_STATUSES.join(", ")
+            ),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length("specie", payload.specie.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length("habitat", payload.habitat.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length(
+        "country_of_origin",
+        payload.country_of_origin.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "description",
+        payload.description.as_deref().unwrap_or(""),
+        config.limits.max_description_length,
+    )?;
 
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
+    let reservation = match idempotency::begin(
+        &mut client,
+        &headers,
+        "POST /animals/add",
+        &payload,
+        config.idempotency.ttl_seconds,
+    )
+    .await?
+    {
+        Begin::Replay(response) => return Ok(response),
+        Begin::Proceed(reservation) => reservation,
+    };
+
+    let result = in_transaction(&mut client, async |client| {
+        let created = insert_animal(client, payload).await?;
+        idempotency::store(client, reservation.as_ref(), StatusCode::CREATED, &created).await?;
+        Ok(created)
+    })
+    .await;
+    let created = match result {
+        Ok(created) => created,
+        Err(failure) => {
+            idempotency::release(&mut client, reservation).await;
+            return Err(failure);
+        }
+    };
+
+    events.publish(EntityKind::Animal, ChangeAction::Created, created.animal_id, Some(&created));
+
+    Ok((StatusCode::CREATED, Json(created)).into_response())
+}
+
+async fn insert_animal(
+    client: &mut DbClient,
+    payload: CreateAnimal,
+) -> Result<Animal, (StatusCode, String)> {
     let parsed_date: Option<NaiveDate> = if let Some(d) = &payload.date_of_birth {
         if d.contains('/') {
             NaiveDate::parse_from_str(d, "%d/%m/%Y").ok()
@@ -137,16 +497,24 @@ pub async fn add_animal(
         None
     };
 
+    let (species_id, _) =
+        resolve_species(client, payload.species_id, payload.specie.as_deref())
+            .await?
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "Specie or species_id is required".to_string(),
+            ))?;
+
     let id_query = "SELECT ISNULL(MAX(animal_id),0)+1 AS next_id FROM Animal";
     let id_stream = client.query(id_query, &[]).await.map_err(|e| {
-        eprintln!("ID query error: {}", e);
+        error!(error = %e, "ID query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID query error: {}", e),
         )
     })?;
     let id_rows = id_stream.into_first_result().await.map_err(|e| {
-        eprintln!("ID result error: {}", e);
+        error!(error = %e, "ID result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID result error: {}", e),
@@ -158,8 +526,8 @@ pub async fn add_animal(
     ))?;
 
     let insert_query = r#"
-        INSERT INTO Animal (animal_id, name, specie, habitat, description, country_of_origin, date_of_birth, is_active)
-        VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, 1)
+        INSERT INTO Animal (animal_id, name, fk_Species_species_id, habitat, description, country_of_origin, date_of_birth, status, status_date)
+        VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, CAST(SYSUTCDATETIME() AS DATE))
     "#;
 
     client
@@ -168,122 +536,174 @@ pub async fn add_animal(
             &[
                 &new_id,                    // @P1
                 &payload.name,              // @P2
-                &payload.specie,            // @P3
+                &species_id,                // @P3
                 &payload.habitat,           // @P4
                 &payload.description,       // @P5
                 &payload.country_of_origin, // @P6
                 &parsed_date,               // @P7
+                &payload.status.as_deref().unwrap_or("active"), // @P8
             ],
         )
         .await
         .map_err(|e| {
-            eprintln!("Insert error: {}", e);
+            error!(error = %e, "Insert error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Insert error: {}", e),
             )
         })?;
 
-    let created = Animal {
-        animal_id: new_id,
-        name: payload.name,
-        specie: payload.specie,
-        habitat: payload.habitat,
-        description: payload.description,
-        country_of_origin: payload.country_of_origin,
-        date_of_birth: parsed_date,
-    };
+    record_animal_revision(client, new_id, "created", None).await?;
 
-    Ok((StatusCode::CREATED, Json(created)))
+    find_animal(client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created animal".to_string(),
+    ))
 }
 
-pub async fn deactivate_animal(
-    State(db): State<Database>,
-    Path(id): Path<i32>,
+/// Take a record created by mistake out of the collection. Animals that die
+/// or leave go through their status instead.
+async fn remove_animal(
+    db: &Database,
+    events: &EventBus,
+    id: i32,
+    headers: &HeaderMap,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query = "UPDATE Animal SET is_active = 0 WHERE animal_id = @P1 AND is_active = 1";
+    let expected_version = animal_write_precondition(&mut client, headers, id).await?;
 
-    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
-        eprintln!("Update error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Update error: {}", e),
-        )
-    })?;
+    let query = format!(
+        "UPDATE Animal SET status = 'removed', status_date = CAST(SYSUTCDATETIME() AS DATE), status_reason = NULL WHERE animal_id = @P1 AND status IN {} AND (@P2 IS NULL OR CAST(row_version AS BIGINT) = @P2)",
+        in_collection_sql()
+    );
 
-    if rows_affected.total() == 0 {
-        return Err((
-            StatusCode::NOT_FOUND,
-            format!("Animal with id {} not found or already inactive", id),
-        ));
-    }
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client.execute(query, &[&id, &expected_version]).await.map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+
+        if rows_affected.total() == 0 {
+            if expected_version.is_some() {
+                return Err((
+                    StatusCode::PRECONDITION_FAILED,
+                    format!("Animal with id {} was modified concurrently", id),
+                ));
+            }
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found or no longer in the collection", id),
+            ));
+        }
+
+        leave_enclosure(client, id).await?;
+        record_animal_revision(client, id, "deactivated", None).await
+    })
+    .await?;
+    events.publish_without_data(EntityKind::Animal, ChangeAction::Deactivated, id);
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn delete_animal(
+pub async fn deactivate_animal(
     State(db): State<Database>,
+    State(events): State<EventBus>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Database connection error: {}", e),
-        )
-    })?;
-
-    let query = "UPDATE Animal SET is_active = 0 WHERE animal_id = @P1 AND is_active = 1";
-
-    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
-        eprintln!("Delete error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Delete error: {}", e),
-        )
-    })?;
-
-    if rows_affected.total() == 0 {
-        return Err((
-            StatusCode::NOT_FOUND,
-            format!("Animal with id {} not found or already inactive", id),
-        ));
-    }
+    remove_animal(&db, &events, id, &headers).await
+}
 
-    Ok(StatusCode::NO_CONTENT)
+#[utoipa::path(
+    delete,
+    path = "/animals/{id}",
+    tag = "animals",
+    summary = "Delete animal (soft delete, kept for history)",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being changed; 412 if it is stale"),
+    ),
+    responses(
+        (status = 204, description = "Animal deactivated"),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 412, description = "If-Match is stale", body = String),
+    )
+)]
+pub async fn delete_animal(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    remove_animal(&db, &events, id, &headers).await
 }
 
 
+#[utoipa::path(
+    patch,
+    path = "/animals/{id}",
+    tag = "animals",
+    summary = "Update animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being changed; 412 if it is stale"),
+    ),
+    request_body = UpdateAnimal,
+    responses(
+        (status = 200, description = "Updated animal, with its new ETag", body = Animal),
+        (status = 400, description = "Invalid animal", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 412, description = "If-Match is stale", body = String),
+    )
+)]
 pub async fn update_animal(
     State(db): State<Database>,
+    State(events): State<EventBus>,
+    State(config): State<Arc<Config>>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateAnimal>,
-) -> Result<Json<Animal>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
 
-    if let Some(ref name) = payload.name {
-        if name.trim().is_empty() {
-            return Err((StatusCode::BAD_REQUEST, "Name cannot be empty".to_string()));
-        }
+    if let Some(ref name) = payload.name
+        && name.trim().is_empty()
+    {
+        return Err((StatusCode::BAD_REQUEST, "Name cannot be empty".to_string()));
     }
-    if let Some(ref specie) = payload.specie {
-        if specie.trim().is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                "Specie cannot be empty".to_string(),
-            ));
-        }
+    if let Some(ref specie) = payload.specie
+        && specie.trim().is_empty()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Specie cannot be empty".to_string(),
+        ));
     }
+    check_length("name", payload.name.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length("specie", payload.specie.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length("habitat", payload.habitat.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length(
+        "country_of_origin",
+        payload.country_of_origin.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "description",
+        payload.description.as_deref().unwrap_or(""),
+        config.limits.max_description_length,
+    )?;
 
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
@@ -300,80 +720,177 @@ pub async fn update_animal(
         None
     };
 
-    let update_query = r#"
+    let expected_version = animal_write_precondition(&mut client, &headers, id).await?;
+
+    let species_id =
+        resolve_species(&mut client, payload.species_id, payload.specie.as_deref())
+            .await?
+            .map(|(species_id, _)| species_id);
+
+    // Habitat changes go to the movements log, so the old one isn't lost
+    let previous_habitat = match &payload.habitat {
+        Some(_) => find_animal(&mut client, id)
+            .await?
+            .and_then(|animal| animal.habitat),
+        None => None,
+    };
+
+    let update_query = format!(
+        r#"
         UPDATE Animal 
         SET name = COALESCE(@P2, name),
-            specie = COALESCE(@P3, specie),
+            fk_Species_species_id = COALESCE(@P3, fk_Species_species_id),
             habitat = COALESCE(@P4, habitat),
             description = COALESCE(@P5, description),
             country_of_origin = COALESCE(@P6, country_of_origin),
             date_of_birth = COALESCE(@P7, date_of_birth)
-        WHERE animal_id = @P1 AND is_active = 1
-    "#;
+        WHERE animal_id = @P1 AND status IN {}
+          AND (@P8 IS NULL OR CAST(row_version AS BIGINT) = @P8)
+    "#,
+        in_collection_sql()
+    );
+
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &payload.name,
+                    &species_id,
+                    &payload.habitat,
+                    &payload.description,
+                    &payload.country_of_origin,
+                    &parsed_date,
+                    &expected_version,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Update error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Update error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            if expected_version.is_some() {
+                return Err((
+                    StatusCode::PRECONDITION_FAILED,
+                    format!("Animal with id {} was modified concurrently", id),
+                ));
+            }
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found or no longer in the collection", id),
+            ));
+        }
 
-    let rows_affected = client
-        .execute(
-            update_query,
-            &[
-                &id,
-                &payload.name,
-                &payload.specie,
-                &payload.habitat,
-                &payload.description,
-                &payload.country_of_origin,
-                &parsed_date,
-            ],
-        )
-        .await
-        .map_err(|e| {
-            eprintln!("Update error: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Update error: {}", e),
+        record_animal_revision(client, id, "updated", None).await?;
+        if let Some(habitat) = &payload.habitat
+            && previous_habitat.as_deref().map(str::trim) != Some(habitat.trim())
+        {
+            record_movement(
+                client,
+                NewMovement {
+                    animal_id: id,
+                    movement_type: "internal",
+                    from_enclosure_id: None,
+                    from_location: previous_habitat,
+                    to_enclosure_id: None,
+                    to_location: Some(habitat.clone()),
+                    institution: None,
+                    is_loan: false,
+                    moved_on: None,
+                    reason: None,
+                    responsible_staff: None,
+                },
             )
-        })?;
+            .await?;
+        }
+        Ok(())
+    })
+    .await?;
+
+    if let Some(animal) = find_animal(&mut client, id).await? {
+        events.publish(EntityKind::Animal, ChangeAction::Updated, id, Some(&animal));
+        match animal.version {
+            Some(version) => Ok(([(header::ETAG, etag(version))], Json(animal)).into_response()),
+            None => Ok(Json(animal).into_response()),
+        }
+    } else {
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to fetch updated animal".to_string(),
+        ))
+    }
+}
 
-    if rows_affected.total() == 0 {
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/status",
+    tag = "animals",
+    summary = "Change the lifecycle status of an animal",
+    description = "Allowed changes: arrived or born to active, transferred, deceased or removed; active to on_loan, transferred, released, deceased or removed; on_loan to active, transferred, deceased or removed; transferred or released back to arrived.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = ChangeAnimalStatus,
+    responses(
+        (status = 200, description = "The animal with its new status", body = Animal),
+        (status = 400, description = "Invalid status change", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "The animal can't go to that status from its current one", body = String),
+    )
+)]
+pub async fn change_animal_status(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ChangeAnimalStatus>,
+) -> Result<Json<Animal>, (StatusCode, String)> {
+    if !ANIMAL_STATUSES.contains(&payload.status.as_str()) {
         return Err((
-            StatusCode::NOT_FOUND,
-            format!("Animal with id {} not found or inactive", id),
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown status '{}'; expected one of {}",
+                payload.status,
+                ANIMAL_STATUSES.join(", ")
+            ),
         ));
     }
+    let date = parse_date("date", payload.date.as_deref())?;
+    check_length("reason", payload.reason.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
 
-    let query = "SELECT animal_id, name, specie, habitat, description, country_of_origin, date_of_birth FROM Animal WHERE animal_id = @P1 AND is_active = 1";
-    let stream = client.query(query, &[&id]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Query error: {}", e),
+            format!("Database connection error: {}", e),
         )
     })?;
 
-    let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Result error: {}", e),
-        )
-    })?;
+    transition_animal_status(
+        &mut client,
+        id,
+        &payload.status,
+        date,
+        payload.reason.as_deref(),
+    )
+    .await?;
 
-    if let Some(row) = rows.first() {
-        let animal = Animal {
-            animal_id: row.get::<i32, _>(0).unwrap_or(0),
-            name: row.get::<&str, _>(1).unwrap_or("").to_string(),
-            specie: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            habitat: row.get::<&str, _>(3).map(|s| s.to_string()),
-            description: row.get::<&str, _>(4).map(|s| s.to_string()),
-            country_of_origin: row.get::<&str, _>(5).map(|s| s.to_string()),
-            date_of_birth: row.get(6),
-        };
-        Ok(Json(animal))
+    let animal = find_animal(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated animal".to_string(),
+    ))?;
+    if IN_COLLECTION_STATUSES.contains(&animal.status.as_str()) {
+        events.publish(EntityKind::Animal, ChangeAction::Updated, id, Some(&animal));
     } else {
-        Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to fetch updated animal".to_string(),
-        ))
+        events.publish_without_data(EntityKind::Animal, ChangeAction::Deactivated, id);
     }
+
+    Ok(Json(animal))
 }
 
 pub async fn initial_page() -> &'static str {
@@ -1,27 +1,102 @@
-use crate::db::Database;
+use crate::config::Config;
+use crate::db::{Database, DbClient, in_transaction};
+use crate::etag::{check_if_match, etag, has_if_match, if_none_match};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::idempotency::{self, Begin};
 use crate::models::{Care, CreateCare, UpdateCare};
+use crate::validation::{VARCHAR_LENGTH, check_length};
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
 
+const CARE_COLUMNS: &str = "type_of_care, description, frequency, cares_id, CAST(row_version AS BIGINT)";
+
+fn care_from_row(row: &Row) -> Care {
+    Care {
+        type_of_care: row.get::<&str, _>(0).unwrap_or("").to_string(),
+        description: row.get::<&str, _>(1).map(|s| s.to_string()),
+        frequency: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        cares_id: row.get::<i32, _>(3).unwrap_or(0),
+        version: row.get::<i64, _>(4),
+    }
+}
+
+/// Current row version of a care, or `None` if it doesn't exist.
+async fn current_care_version(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<i64>, (StatusCode, String)> {
+    let query = "SELECT CAST(row_version AS BIGINT) FROM Cares WHERE cares_id = @P1";
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Version query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Version query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(rows.first().and_then(|r| r.get::<i64, _>(0)))
+}
+
+/// Resolve the `If-Match` precondition for a write to a care. See
+/// `animal_write_precondition` in the animals handlers.
+async fn care_write_precondition(
+    client: &mut DbClient,
+    headers: &HeaderMap,
+    id: i32,
+) -> Result<Option<i64>, (StatusCode, String)> {
+    if !has_if_match(headers) {
+        return Ok(None);
+    }
+
+    let current = current_care_version(client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Care with id {} not found", id),
+    ))?;
+    check_if_match(headers, current)?;
+
+    Ok(Some(current))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cares",
+    tag = "cares",
+    summary = "List all cares",
+    responses(
+        (status = 200, description = "All cares", body = Vec<Care>),
+    )
+)]
 pub async fn get_cares(
     State(db): State<Database>,
 ) -> Result<Json<Vec<Care>>, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query =
-        "SELECT type_of_care, description, frequency, cares_id FROM Cares ORDER BY cares_id";
+    let query = format!("SELECT {} FROM Cares ORDER BY cares_id", CARE_COLUMNS);
 
     let stream = client.query(query, &[]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -29,7 +104,7 @@ pub async fn get_cares(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -38,34 +113,44 @@ pub async fn get_cares(
 
     let care: Vec<Care> = rows
         .iter()
-        .map(|row| Care {
-            type_of_care: row.get::<&str, _>(0).unwrap_or("").to_string(),
-            description: row.get::<&str, _>(1).map(|s| s.to_string()),
-            frequency: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            cares_id: row.get::<i32, _>(3).unwrap_or(0),
-        })
+        .map(care_from_row)
         .collect();
 
     Ok(Json(care))
 }
 
+#[utoipa::path(
+    get,
+    path = "/cares/{id}",
+    tag = "cares",
+    summary = "Get care by id",
+    params(
+        ("id" = i32, Path, description = "Care id"),
+        ("If-None-Match" = Option<String>, Header, description = "Answer 304 if the ETag still matches"),
+    ),
+    responses(
+        (status = 200, description = "The care, with its ETag", body = Care),
+        (status = 304, description = "Not modified"),
+        (status = 404, description = "Care not found", body = String),
+    )
+)]
 pub async fn get_care_by_id(
     State(db): State<Database>,
     Path(id): Path<i32>,
-) -> Result<Json<Care>, (StatusCode, String)> {
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let query =
-        "SELECT type_of_care, description, frequency, cares_id FROM Cares WHERE cares_id = @P1";
+    let query = format!("SELECT {} FROM Cares WHERE cares_id = @P1", CARE_COLUMNS);
 
     let stream = client.query(query, &[&id]).await.map_err(|e| {
-        eprintln!("Query error: {}", e);
+        error!(error = %e, "Query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Query error: {}", e),
@@ -73,7 +158,7 @@ pub async fn get_care_by_id(
     })?;
 
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -81,13 +166,14 @@ pub async fn get_care_by_id(
     })?;
 
     if let Some(row) = rows.first() {
-        let care = Care {
-            type_of_care: row.get::<&str, _>(0).unwrap_or("").to_string(),
-            description: row.get::<&str, _>(1).map(|s| s.to_string()),
-            frequency: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            cares_id: row.get::<i32, _>(3).unwrap_or(0),
+        let care = care_from_row(row);
+        let Some(version) = care.version else {
+            return Ok(Json(care).into_response());
         };
-        Ok(Json(care))
+        if if_none_match(&headers, version) {
+            return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag(version))]).into_response());
+        }
+        Ok(([(header::ETAG, etag(version))], Json(care)).into_response())
     } else {
         Err((
             StatusCode::NOT_FOUND,
@@ -96,10 +182,29 @@ pub async fn get_care_by_id(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/cares",
+    tag = "cares",
+    summary = "Add new care",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a create is retried"),
+    ),
+    request_body = CreateCare,
+    responses(
+        (status = 201, description = "Care created", body = Care),
+        (status = 400, description = "Invalid care", body = String),
+        (status = 409, description = "Same Idempotency-Key still in progress", body = String),
+        (status = 422, description = "Idempotency-Key reused with another body", body = String),
+    )
+)]
 pub async fn add_care(
     State(db): State<Database>,
+    State(events): State<EventBus>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
     Json(payload): Json<CreateCare>,
-) -> Result<(StatusCode, Json<Care>), (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
 
     if payload.type_of_care.trim().is_empty() {
         return Err((
@@ -113,25 +218,68 @@ pub async fn add_care(
             "Frequency is required and cannot be empty".to_string(),
         ));
     }
+    check_length("type_of_care", &payload.type_of_care, VARCHAR_LENGTH)?;
+    check_length("frequency", &payload.frequency, VARCHAR_LENGTH)?;
+    check_length(
+        "description",
+        payload.description.as_deref().unwrap_or(""),
+        config.limits.max_description_length,
+    )?;
 
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
+    let reservation = match idempotency::begin(
+        &mut client,
+        &headers,
+        "POST /cares/add",
+        &payload,
+        config.idempotency.ttl_seconds,
+    )
+    .await?
+    {
+        Begin::Replay(response) => return Ok(response),
+        Begin::Proceed(reservation) => reservation,
+    };
+
+    let result = in_transaction(&mut client, async |client| {
+        let created = insert_care(client, payload).await?;
+        idempotency::store(client, reservation.as_ref(), StatusCode::CREATED, &created).await?;
+        Ok(created)
+    })
+    .await;
+    let created = match result {
+        Ok(created) => created,
+        Err(failure) => {
+            idempotency::release(&mut client, reservation).await;
+            return Err(failure);
+        }
+    };
+
+    events.publish(EntityKind::Care, ChangeAction::Created, created.cares_id, Some(&created));
+
+    Ok((StatusCode::CREATED, Json(created)).into_response())
+}
+
+async fn insert_care(
+    client: &mut DbClient,
+    payload: CreateCare,
+) -> Result<Care, (StatusCode, String)> {
     let id_query = "SELECT ISNULL(MAX(cares_id),0)+1 AS next_id FROM Cares";
     let id_stream = client.query(id_query, &[]).await.map_err(|e| {
-        eprintln!("ID query error: {}", e);
+        error!(error = %e, "ID query error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID query error: {}", e),
         )
     })?;
     let id_rows = id_stream.into_first_result().await.map_err(|e| {
-        eprintln!("ID result error: {}", e);
+        error!(error = %e, "ID result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("ID result error: {}", e),
@@ -159,59 +307,98 @@ pub async fn add_care(
         )
         .await
         .map_err(|e| {
-            eprintln!("Insert error: {}", e);
+            error!(error = %e, "Insert error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Insert error: {}", e),
             )
         })?;
 
+    let version = current_care_version(client, new_id).await?;
+
     let created = Care {
         cares_id: new_id,
         type_of_care: payload.type_of_care,
         description: payload.description,
         frequency: payload.frequency,
+        version,
     };
 
-    Ok((StatusCode::CREATED, Json(created)))
+    Ok(created)
 }
 
+#[utoipa::path(
+    put,
+    path = "/cares/{id}",
+    tag = "cares",
+    summary = "Update care",
+    params(
+        ("id" = i32, Path, description = "Care id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being changed; 412 if it is stale"),
+    ),
+    request_body = UpdateCare,
+    responses(
+        (status = 200, description = "Updated care, with its new ETag", body = Care),
+        (status = 400, description = "Invalid care", body = String),
+        (status = 404, description = "Care not found", body = String),
+        (status = 412, description = "If-Match is stale", body = String),
+    )
+)]
 pub async fn update_care(
     State(db): State<Database>,
+    State(events): State<EventBus>,
+    State(config): State<Arc<Config>>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateCare>,
-) -> Result<Json<Care>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     if payload.type_of_care.trim().is_empty() {
         return Err((StatusCode::BAD_REQUEST, "Type of care cannot be empty".to_string()));
     }
     if payload.frequency.trim().is_empty() {
         return Err((StatusCode::BAD_REQUEST, "Frequency cannot be empty".to_string()));
     }
+    check_length("type_of_care", &payload.type_of_care, VARCHAR_LENGTH)?;
+    check_length("frequency", &payload.frequency, VARCHAR_LENGTH)?;
+    check_length(
+        "description",
+        payload.description.as_deref().unwrap_or(""),
+        config.limits.max_description_length,
+    )?;
 
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
+    let expected_version = care_write_precondition(&mut client, &headers, id).await?;
+
     let update_query = r#"
         UPDATE Cares
         SET type_of_care = @P2,
             description = @P3,
             frequency = @P4
         WHERE cares_id = @P1
+          AND (@P5 IS NULL OR CAST(row_version AS BIGINT) = @P5)
     "#;
 
     let rows_affected = client
         .execute(
             update_query,
-            &[&id, &payload.type_of_care, &payload.description, &payload.frequency],
+            &[
+                &id,
+                &payload.type_of_care,
+                &payload.description,
+                &payload.frequency,
+                &expected_version,
+            ],
         )
         .await
         .map_err(|e| {
-            eprintln!("Update care error: {}", e);
+            error!(error = %e, "Update care error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Update care error: {}", e),
@@ -219,19 +406,25 @@ pub async fn update_care(
         })?;
 
     if rows_affected.total() == 0 {
+        if expected_version.is_some() {
+            return Err((
+                StatusCode::PRECONDITION_FAILED,
+                format!("Care with id {} was modified concurrently", id),
+            ));
+        }
         return Err((StatusCode::NOT_FOUND, format!("Care with id {} not found", id)));
     }
 
-    let select_query = "SELECT type_of_care, description, frequency, cares_id FROM Cares WHERE cares_id = @P1";
+    let select_query = format!("SELECT {} FROM Cares WHERE cares_id = @P1", CARE_COLUMNS);
     let stream = client.query(select_query, &[&id]).await.map_err(|e| {
-        eprintln!("Select updated care error: {}", e);
+        error!(error = %e, "Select updated care error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Select updated care error: {}", e),
         )
     })?;
     let rows = stream.into_first_result().await.map_err(|e| {
-        eprintln!("Result error: {}", e);
+        error!(error = %e, "Result error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Result error: {}", e),
@@ -239,33 +432,51 @@ pub async fn update_care(
     })?;
 
     if let Some(row) = rows.first() {
-        let care = Care {
-            type_of_care: row.get::<&str, _>(0).unwrap_or("").to_string(),
-            description: row.get::<&str, _>(1).map(|s| s.to_string()),
-            frequency: row.get::<&str, _>(2).unwrap_or("").to_string(),
-            cares_id: row.get::<i32, _>(3).unwrap_or(0),
-        };
-        Ok(Json(care))
+        let care = care_from_row(row);
+        events.publish(EntityKind::Care, ChangeAction::Updated, id, Some(&care));
+        match care.version {
+            Some(version) => Ok(([(header::ETAG, etag(version))], Json(care)).into_response()),
+            None => Ok(Json(care).into_response()),
+        }
     } else {
         Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch updated care".to_string()))
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/cares/{id}",
+    tag = "cares",
+    summary = "Delete care",
+    params(
+        ("id" = i32, Path, description = "Care id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being changed; 412 if it is stale"),
+    ),
+    responses(
+        (status = 204, description = "Care deleted"),
+        (status = 404, description = "Care not found", body = String),
+        (status = 412, description = "If-Match is stale", body = String),
+    )
+)]
 pub async fn delete_care(
     State(db): State<Database>,
+    State(events): State<EventBus>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let mut client = db.connect().await.map_err(|e| {
-        eprintln!("Database connection error: {}", e);
+        error!(error = %e, "Database connection error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database connection error: {}", e),
         )
     })?;
 
-    let delete_query = "DELETE FROM Cares WHERE cares_id = @P1";
-    let rows_affected = client.execute(delete_query, &[&id]).await.map_err(|e| {
-        eprintln!("Delete care error: {}", e);
+    let expected_version = care_write_precondition(&mut client, &headers, id).await?;
+
+    let delete_query = "DELETE FROM Cares WHERE cares_id = @P1 AND (@P2 IS NULL OR CAST(row_version AS BIGINT) = @P2)";
+    let rows_affected = client.execute(delete_query, &[&id, &expected_version]).await.map_err(|e| {
+        error!(error = %e, "Delete care error");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Delete care error: {}", e),
@@ -273,8 +484,16 @@ pub async fn delete_care(
     })?;
 
     if rows_affected.total() == 0 {
+        if expected_version.is_some() {
+            return Err((
+                StatusCode::PRECONDITION_FAILED,
+                format!("Care with id {} was modified concurrently", id),
+            ));
+        }
         return Err((StatusCode::NOT_FOUND, format!("Care with id {} not found", id)));
     }
 
+    events.publish_without_data(EntityKind::Care, ChangeAction::Deleted, id);
+
     Ok(StatusCode::NO_CONTENT)
 }
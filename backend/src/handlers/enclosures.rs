@@ -0,0 +1,972 @@
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::handlers::animals::{find_animal, status_on_site};
+use crate::handlers::movements::{NewMovement, record_movement};
+use crate::models::{
+    Enclosure, EnclosureAssignment, EnclosureOccupancy, IN_COLLECTION_STATUSES, MoveAnimal,
+    SaveEnclosure, SpeciesCount, in_collection_sql,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::collections::HashMap;
+use tiberius::Row;
+use tracing::error;
+
+const ENCLOSURE_COLUMNS: &str = "enclosure_id, name, zone, biome, capacity, mixed_species";
+
+/// Assignments with their enclosure's name; callers add the `WHERE`/`ORDER BY`.
+const ASSIGNMENT_SELECT: &str = "SELECT ea.enclosure_assignment_id, ea.fk_Animal_animal_id, ea.fk_Enclosure_enclosure_id, e.name, ea.assigned_at, ea.removed_at, ea.reason FROM Enclosure_Assignment ea JOIN Enclosure e ON e.enclosure_id = ea.fk_Enclosure_enclosure_id";
+
+fn enclosure_from_row(row: &Row) -> Enclosure {
+    Enclosure {
+        enclosure_id: row.get::<i32, _>(0).unwrap_or(0),
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        zone: row.get::<&str, _>(2).map(|s| s.to_string()),
+        biome: row.get::<&str, _>(3).map(|s| s.to_string()),
+        capacity: row.get::<i32, _>(4).unwrap_or(0),
+        mixed_species: row.get::<bool, _>(5).unwrap_or(false),
+        allowed_species_ids: Vec::new(),
+    }
+}
+
+fn assignment_from_row(row: &Row) -> EnclosureAssignment {
+    EnclosureAssignment {
+        enclosure_assignment_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        enclosure_id: row.get::<i32, _>(2).unwrap_or(0),
+        enclosure_name: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        assigned_at: row.get(4),
+        removed_at: row.get(5),
+        reason: row.get::<&str, _>(6).map(|s| s.to_string()),
+    }
+}
+
+/// An active animal in an enclosure.
+struct Occupant {
+    species_id: Option<i32>,
+    specie: String,
+}
+
+async fn find_enclosure(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<Enclosure>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Enclosure WHERE enclosure_id = @P1 AND is_active = 1",
+        ENCLOSURE_COLUMNS
+    );
+
+    let rows = fetch_rows(client, &query, &[&id]).await?;
+
+    let Some(mut enclosure) = rows.first().map(enclosure_from_row) else {
+        return Ok(None);
+    };
+    enclosure.allowed_species_ids = find_allowed_species(client, Some(id))
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    Ok(Some(enclosure))
+}
+
+/// Allowed species by enclosure id, for one enclosure or all of them.
+async fn find_allowed_species(
+    client: &mut DbClient,
+    enclosure_id: Option<i32>,
+) -> Result<HashMap<i32, Vec<i32>>, (StatusCode, String)> {
+    let query = "SELECT fk_Enclosure_enclosure_id, fk_Species_species_id FROM Enclosure_Species WHERE @P1 IS NULL OR fk_Enclosure_enclosure_id = @P1 ORDER BY fk_Species_species_id";
+
+    let rows = fetch_rows(client, query, &[&enclosure_id]).await?;
+
+    let mut allowed: HashMap<i32, Vec<i32>> = HashMap::new();
+    for row in &rows {
+        if let (Some(enclosure_id), Some(species_id)) = (row.get::<i32, _>(0), row.get::<i32, _>(1))
+        {
+            allowed.entry(enclosure_id).or_default().push(species_id);
+        }
+    }
+    Ok(allowed)
+}
+
+/// Active animals currently assigned to an enclosure.
+async fn find_occupants(
+    client: &mut DbClient,
+    enclosure_id: i32,
+) -> Result<Vec<Occupant>, (StatusCode, String)> {
+    let query = format!(
+        r#"
+        SELECT a.fk_Species_species_id, s.common_name_pt
+        FROM Enclosure_Assignment ea
+        JOIN Animal a ON a.animal_id = ea.fk_Animal_animal_id AND a.status IN {}
+        LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id
+        WHERE ea.fk_Enclosure_enclosure_id = @P1 AND ea.removed_at IS NULL
+    "#,
+        in_collection_sql()
+    );
+
+    let rows = fetch_rows(client, &query, &[&enclosure_id]).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Occupant {
+            species_id: row.get::<i32, _>(0),
+            specie: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        })
+        .collect())
+}
+
+/// The enclosure's capacity and species rules must hold for `occupants`.
+fn check_rules(enclosure: &Enclosure, occupants: &[Occupant]) -> Result<(), (StatusCode, String)> {
+    if occupants.len() > enclosure.capacity as usize {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Enclosure '{}' holds at most {} animal(s), {} would be in it",
+                enclosure.name,
+                enclosure.capacity,
+                occupants.len()
+            ),
+        ));
+    }
+    if !enclosure.allowed_species_ids.is_empty()
+        && let Some(occupant) = occupants.iter().find(|o| {
+            o.species_id
+                .is_none_or(|id| !enclosure.allowed_species_ids.contains(&id))
+        })
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Enclosure '{}' doesn't take species '{}'",
+                enclosure.name, occupant.specie
+            ),
+        ));
+    }
+    if !enclosure.mixed_species
+        && let Some(first) = occupants.first()
+        && let Some(other) = occupants.iter().find(|o| o.species_id != first.species_id)
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Enclosure '{}' doesn't mix species: it would hold both '{}' and '{}'",
+                enclosure.name, first.specie, other.specie
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_enclosure(payload: &SaveEnclosure) -> Result<(), (StatusCode, String)> {
+    if payload.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Name is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length(
+        "zone",
+        payload.zone.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "biome",
+        payload.biome.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    if payload.capacity <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "capacity must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn check_name_free(
+    client: &mut DbClient,
+    name: &str,
+    enclosure_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let query = "SELECT enclosure_id FROM Enclosure WHERE name = @P1 AND is_active = 1";
+
+    let rows = fetch_rows(client, query, &[&name.trim()]).await?;
+
+    match rows.first().and_then(|r| r.get::<i32, _>(0)) {
+        Some(owner) if Some(owner) != enclosure_id => Err((
+            StatusCode::CONFLICT,
+            format!("Enclosure {} is already named '{}'", owner, name.trim()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn replace_allowed_species(
+    client: &mut DbClient,
+    enclosure_id: i32,
+    species_ids: &[i32],
+) -> Result<(), (StatusCode, String)> {
+    client
+        .execute(
+            "DELETE FROM Enclosure_Species WHERE fk_Enclosure_enclosure_id = @P1",
+            &[&enclosure_id],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Enclosure species delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Enclosure species delete error: {}", e),
+            )
+        })?;
+
+    let mut inserted: Vec<i32> = Vec::new();
+    for &species_id in species_ids {
+        if inserted.contains(&species_id) {
+            continue;
+        }
+        client
+            .execute(
+                "INSERT INTO Enclosure_Species (fk_Enclosure_enclosure_id, fk_Species_species_id) VALUES (@P1, @P2)",
+                &[&enclosure_id, &species_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Enclosure species insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Enclosure species insert error: {}", e),
+                )
+            })?;
+        inserted.push(species_id);
+    }
+
+    Ok(())
+}
+
+async fn check_species_exist(
+    client: &mut DbClient,
+    species_ids: &[i32],
+) -> Result<(), (StatusCode, String)> {
+    for &species_id in species_ids {
+        let rows = fetch_rows(
+            client,
+            "SELECT 1 FROM Species WHERE species_id = @P1",
+            &[&species_id],
+        )
+        .await?;
+        if rows.is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unknown species_id {}", species_id),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// End the animal's current stay in an enclosure, if it has one. Returns
+/// whether there was one.
+pub async fn leave_enclosure(
+    client: &mut DbClient,
+    animal_id: i32,
+) -> Result<bool, (StatusCode, String)> {
+    let query = "UPDATE Enclosure_Assignment SET removed_at = SYSUTCDATETIME() WHERE fk_Animal_animal_id = @P1 AND removed_at IS NULL";
+
+    let rows_affected = client.execute(query, &[&animal_id]).await.map_err(|e| {
+        error!(error = %e, "Assignment update error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Assignment update error: {}", e),
+        )
+    })?;
+
+    Ok(rows_affected.total() > 0)
+}
+
+pub async fn find_current_assignment(
+    client: &mut DbClient,
+    animal_id: i32,
+) -> Result<Option<EnclosureAssignment>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE ea.fk_Animal_animal_id = @P1 AND ea.removed_at IS NULL",
+        ASSIGNMENT_SELECT
+    );
+
+    let rows = fetch_rows(client, &query, &[&animal_id]).await?;
+
+    Ok(rows.first().map(assignment_from_row))
+}
+
+/// Hold the enclosure until the transaction ends, so changes to what is in
+/// it, or to its rules, are checked one at a time.
+async fn lock_enclosure(client: &mut DbClient, id: i32) -> Result<(), (StatusCode, String)> {
+    fetch_rows(
+        client,
+        "SELECT enclosure_id FROM Enclosure WITH (UPDLOCK, HOLDLOCK) WHERE enclosure_id = @P1",
+        &[&id],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Check that an animal may move into an enclosure: it exists, isn't there
+/// already, and the enclosure's rules still hold with it inside. Whether it
+/// is on site is left to the caller, as arrivals check before the animal's
+/// status changes.
+///
+/// Call it in the transaction that moves the animal: it locks the enclosure,
+/// so two moves can't both take its last place.
+pub async fn check_can_enter(
+    client: &mut DbClient,
+    animal_id: i32,
+    enclosure_id: i32,
+) -> Result<Enclosure, (StatusCode, String)> {
+    let animal_query = r#"
+        SELECT a.fk_Species_species_id, s.common_name_pt
+        FROM Animal a
+        LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id
+        WHERE a.animal_id = @P1
+    "#;
+    let rows = fetch_rows(client, animal_query, &[&animal_id]).await?;
+    let animal = rows
+        .first()
+        .map(|row| Occupant {
+            species_id: row.get::<i32, _>(0),
+            specie: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        })
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", animal_id),
+        ))?;
+
+    lock_enclosure(client, enclosure_id).await?;
+    let enclosure = find_enclosure(client, enclosure_id).await?.ok_or((
+        StatusCode::BAD_REQUEST,
+        format!("Unknown enclosure_id {}", enclosure_id),
+    ))?;
+
+    if let Some(current) = find_current_assignment(client, animal_id).await?
+        && current.enclosure_id == enclosure.enclosure_id
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Animal with id {} is already in enclosure '{}'",
+                animal_id, enclosure.name
+            ),
+        ));
+    }
+
+    let mut occupants = find_occupants(client, enclosure.enclosure_id).await?;
+    occupants.push(animal);
+    check_rules(&enclosure, &occupants)?;
+
+    Ok(enclosure)
+}
+
+/// End the animal's current stay, if any, and start one in `enclosure`.
+/// Call `check_can_enter` first.
+pub async fn enter_enclosure(
+    client: &mut DbClient,
+    animal_id: i32,
+    enclosure: &Enclosure,
+    reason: Option<&str>,
+) -> Result<EnclosureAssignment, (StatusCode, String)> {
+    leave_enclosure(client, animal_id).await?;
+
+    let new_id = next_id(client, "Enclosure_Assignment", "enclosure_assignment_id").await?;
+
+    let insert_query = r#"
+        INSERT INTO Enclosure_Assignment (enclosure_assignment_id, fk_Animal_animal_id, fk_Enclosure_enclosure_id, assigned_at, removed_at, reason)
+        VALUES (@P1, @P2, @P3, SYSUTCDATETIME(), NULL, @P4)
+    "#;
+
+    client
+        .execute(
+            insert_query,
+            &[&new_id, &animal_id, &enclosure.enclosure_id, &reason],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    find_current_assignment(client, animal_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch the new assignment".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/enclosures",
+    tag = "enclosures",
+    summary = "List enclosures",
+    responses(
+        (status = 200, description = "Enclosures by name", body = Vec<Enclosure>),
+    )
+)]
+pub async fn get_enclosures(
+    State(db): State<Database>,
+) -> Result<Json<Vec<Enclosure>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "SELECT {} FROM Enclosure WHERE is_active = 1 ORDER BY name",
+        ENCLOSURE_COLUMNS
+    );
+
+    let rows = fetch_rows(&mut client, &query, &[]).await?;
+
+    let mut allowed = find_allowed_species(&mut client, None).await?;
+    let enclosures = rows
+        .iter()
+        .map(enclosure_from_row)
+        .map(|mut enclosure| {
+            enclosure.allowed_species_ids =
+                allowed.remove(&enclosure.enclosure_id).unwrap_or_default();
+            enclosure
+        })
+        .collect();
+
+    Ok(Json(enclosures))
+}
+
+#[utoipa::path(
+    get,
+    path = "/enclosures/{id}",
+    tag = "enclosures",
+    summary = "Get enclosure by id",
+    params(
+        ("id" = i32, Path, description = "Enclosure id"),
+    ),
+    responses(
+        (status = 200, description = "The enclosure", body = Enclosure),
+        (status = 404, description = "Enclosure not found", body = String),
+    )
+)]
+pub async fn get_enclosure_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Enclosure>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_enclosure(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Enclosure with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/enclosures",
+    tag = "enclosures",
+    summary = "Add enclosure",
+    request_body = SaveEnclosure,
+    responses(
+        (status = 201, description = "Enclosure created", body = Enclosure),
+        (status = 400, description = "Invalid enclosure", body = String),
+        (status = 409, description = "Another enclosure has the name", body = String),
+    )
+)]
+pub async fn add_enclosure(
+    State(db): State<Database>,
+    Json(payload): Json<SaveEnclosure>,
+) -> Result<(StatusCode, Json<Enclosure>), (StatusCode, String)> {
+    validate_enclosure(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let allowed_species_ids = payload.allowed_species_ids.as_deref().unwrap_or_default();
+    check_species_exist(&mut client, allowed_species_ids).await?;
+    check_name_free(&mut client, &payload.name, None).await?;
+
+    let new_id = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Enclosure", "enclosure_id").await?;
+
+        let insert_query = r#"
+            INSERT INTO Enclosure (enclosure_id, name, zone, biome, capacity, mixed_species, is_active)
+            VALUES (@P1, @P2, @P3, @P4, @P5, @P6, 1)
+        "#;
+
+        client
+            .execute(
+                insert_query,
+                &[
+                    &new_id,
+                    &payload.name.trim(),
+                    &payload.zone,
+                    &payload.biome,
+                    &payload.capacity,
+                    &payload.mixed_species.unwrap_or(false),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        replace_allowed_species(client, new_id, allowed_species_ids).await?;
+
+        Ok(new_id)
+    })
+    .await?;
+
+    let created = find_enclosure(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created enclosure".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/enclosures/{id}",
+    tag = "enclosures",
+    summary = "Update enclosure",
+    params(
+        ("id" = i32, Path, description = "Enclosure id"),
+    ),
+    request_body = SaveEnclosure,
+    responses(
+        (status = 200, description = "Updated enclosure", body = Enclosure),
+        (status = 400, description = "Invalid enclosure", body = String),
+        (status = 404, description = "Enclosure not found", body = String),
+        (status = 409, description = "Name taken, or the animals in it would break the new rules", body = String),
+    )
+)]
+pub async fn update_enclosure(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveEnclosure>,
+) -> Result<Json<Enclosure>, (StatusCode, String)> {
+    validate_enclosure(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    in_transaction(&mut client, async |client| {
+        // Moves into the enclosure wait, so its animals are checked against
+        // the rules that end up saved
+        lock_enclosure(client, id).await?;
+        let current = find_enclosure(client, id).await?.ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Enclosure with id {} not found", id),
+        ))?;
+        if let Some(species_ids) = &payload.allowed_species_ids {
+            check_species_exist(client, species_ids).await?;
+        }
+        check_name_free(client, &payload.name, Some(id)).await?;
+
+        let updated = Enclosure {
+            enclosure_id: id,
+            name: payload.name.trim().to_string(),
+            zone: payload.zone.clone(),
+            biome: payload.biome.clone(),
+            capacity: payload.capacity,
+            mixed_species: payload.mixed_species.unwrap_or(current.mixed_species),
+            allowed_species_ids: payload
+                .allowed_species_ids
+                .clone()
+                .unwrap_or(current.allowed_species_ids),
+        };
+        let occupants = find_occupants(client, id).await?;
+        check_rules(&updated, &occupants)?;
+
+        let update_query = r#"
+            UPDATE Enclosure
+            SET name = @P2,
+                zone = @P3,
+                biome = @P4,
+                capacity = @P5,
+                mixed_species = @P6
+            WHERE enclosure_id = @P1 AND is_active = 1
+        "#;
+
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &updated.name,
+                    &updated.zone,
+                    &updated.biome,
+                    &updated.capacity,
+                    &updated.mixed_species,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Update error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Update error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Enclosure with id {} not found", id),
+            ));
+        }
+
+        if let Some(species_ids) = &payload.allowed_species_ids {
+            replace_allowed_species(client, id, species_ids).await?;
+        }
+        Ok(())
+    })
+    .await?;
+
+    find_enclosure(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated enclosure".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/enclosures/{id}",
+    tag = "enclosures",
+    summary = "Delete enclosure (soft delete, kept for move history)",
+    params(
+        ("id" = i32, Path, description = "Enclosure id"),
+    ),
+    responses(
+        (status = 204, description = "Enclosure deactivated"),
+        (status = 404, description = "Enclosure not found", body = String),
+        (status = 409, description = "Animals are still in the enclosure", body = String),
+    )
+)]
+pub async fn delete_enclosure(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let occupants = find_occupants(&mut client, id).await?;
+    if !occupants.is_empty() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Enclosure with id {} still has {} animal(s)",
+                id,
+                occupants.len()
+            ),
+        ));
+    }
+
+    let query = "UPDATE Enclosure SET is_active = 0 WHERE enclosure_id = @P1 AND is_active = 1";
+
+    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Delete error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Delete error: {}", e),
+        )
+    })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Enclosure with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/enclosures/occupancy",
+    tag = "enclosures",
+    summary = "Current occupancy of every enclosure",
+    responses(
+        (status = 200, description = "Active animals per enclosure and species", body = Vec<EnclosureOccupancy>),
+    )
+)]
+pub async fn get_enclosure_occupancy(
+    State(db): State<Database>,
+) -> Result<Json<Vec<EnclosureOccupancy>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        r#"
+        SELECT e.enclosure_id, e.name, e.zone, e.biome, e.capacity,
+               a.fk_Species_species_id, s.common_name_pt, COUNT(a.animal_id)
+        FROM Enclosure e
+        LEFT JOIN Enclosure_Assignment ea
+               ON ea.fk_Enclosure_enclosure_id = e.enclosure_id AND ea.removed_at IS NULL
+        LEFT JOIN Animal a ON a.animal_id = ea.fk_Animal_animal_id AND a.status IN {}
+        LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id
+        WHERE e.is_active = 1
+        GROUP BY e.enclosure_id, e.name, e.zone, e.biome, e.capacity,
+                 a.fk_Species_species_id, s.common_name_pt
+        ORDER BY e.name, s.common_name_pt
+    "#,
+        in_collection_sql()
+    );
+
+    let rows = fetch_rows(&mut client, &query, &[]).await?;
+
+    // One row per enclosure and species, in enclosure order
+    let mut occupancy: Vec<EnclosureOccupancy> = Vec::new();
+    for row in &rows {
+        let enclosure_id = row.get::<i32, _>(0).unwrap_or(0);
+        if occupancy
+            .last()
+            .is_none_or(|o| o.enclosure_id != enclosure_id)
+        {
+            let capacity = row.get::<i32, _>(4).unwrap_or(0);
+            occupancy.push(EnclosureOccupancy {
+                enclosure_id,
+                name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+                zone: row.get::<&str, _>(2).map(|s| s.to_string()),
+                biome: row.get::<&str, _>(3).map(|s| s.to_string()),
+                capacity,
+                occupants: 0,
+                available: capacity,
+                species: Vec::new(),
+            });
+        }
+        let animals = row.get::<i32, _>(7).unwrap_or(0);
+        if animals == 0 {
+            continue;
+        }
+        if let Some(enclosure) = occupancy.last_mut() {
+            enclosure.occupants += animals;
+            enclosure.available -= animals;
+            enclosure.species.push(SpeciesCount {
+                species_id: row.get::<i32, _>(5),
+                specie: row.get::<&str, _>(6).unwrap_or("").to_string(),
+                animals,
+            });
+        }
+    }
+
+    Ok(Json(occupancy))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/enclosures",
+    tag = "enclosures",
+    summary = "Move history of an animal, most recent first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 200, description = "Enclosures the animal has been in", body = Vec<EnclosureAssignment>),
+    )
+)]
+pub async fn get_enclosures_of_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<EnclosureAssignment>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "{} WHERE ea.fk_Animal_animal_id = @P1 ORDER BY ea.assigned_at DESC, ea.enclosure_assignment_id DESC",
+        ASSIGNMENT_SELECT
+    );
+
+    let rows = fetch_rows(&mut client, &query, &[&id]).await?;
+
+    Ok(Json(rows.iter().map(assignment_from_row).collect()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/animals/{id}/enclosure",
+    tag = "enclosures",
+    summary = "Move an animal to an enclosure",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = MoveAnimal,
+    responses(
+        (status = 200, description = "The animal's new stay", body = EnclosureAssignment),
+        (status = 400, description = "Invalid move", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "Not at the zoo, already there, or the enclosure is full or doesn't take the species", body = String),
+    )
+)]
+pub async fn move_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<MoveAnimal>,
+) -> Result<Json<EnclosureAssignment>, (StatusCode, String)> {
+    check_length(
+        "reason",
+        payload.reason.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "responsible_staff",
+        payload.responsible_staff.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let assignment = in_transaction(&mut client, async |client| {
+        let status = find_animal(client, id)
+            .await?
+            .map(|animal| animal.status)
+            .filter(|status| IN_COLLECTION_STATUSES.contains(&status.as_str()))
+            .ok_or((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found or inactive", id),
+            ))?;
+        // Animals away on loan come back through an arrival
+        if !status_on_site(&status) {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Animal with id {} is {} and not at the zoo", id, status),
+            ));
+        }
+
+        let enclosure = check_can_enter(client, id, payload.enclosure_id).await?;
+        let previous = find_current_assignment(client, id).await?;
+        let assignment =
+            enter_enclosure(client, id, &enclosure, payload.reason.as_deref()).await?;
+
+        record_movement(
+            client,
+            NewMovement {
+                animal_id: id,
+                movement_type: "internal",
+                from_enclosure_id: previous.as_ref().map(|p| p.enclosure_id),
+                from_location: previous.map(|p| p.enclosure_name),
+                to_enclosure_id: Some(enclosure.enclosure_id),
+                to_location: Some(enclosure.name),
+                institution: None,
+                is_loan: false,
+                moved_on: None,
+                reason: payload.reason,
+                responsible_staff: payload.responsible_staff,
+            },
+        )
+        .await?;
+
+        Ok(assignment)
+    })
+    .await?;
+
+    Ok(Json(assignment))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/animals/{id}/enclosure",
+    tag = "enclosures",
+    summary = "Take an animal out of its enclosure",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 204, description = "The animal's stay ended"),
+        (status = 404, description = "The animal isn't in an enclosure", body = String),
+    )
+)]
+pub async fn remove_animal_from_enclosure(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    in_transaction(&mut client, async |client| {
+        let current = find_current_assignment(client, id).await?.ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} is not in an enclosure", id),
+        ))?;
+        leave_enclosure(client, id).await?;
+
+        record_movement(
+            client,
+            NewMovement {
+                animal_id: id,
+                movement_type: "internal",
+                from_enclosure_id: Some(current.enclosure_id),
+                from_location: Some(current.enclosure_name),
+                to_enclosure_id: None,
+                to_location: None,
+                institution: None,
+                is_loan: false,
+                moved_on: None,
+                reason: None,
+                responsible_staff: None,
+            },
+        )
+        .await
+    })
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,117 @@
+use crate::events::{ChangeEvent, EntityKind, EventBus, Replay};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use std::convert::Infallible;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+use tokio_util::sync::CancellationToken;
+use utoipa::IntoParams;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventStreamQuery {
+    /// Comma-separated entities to receive, e.g. `animal,care`. All if absent.
+    pub entity: Option<String>,
+    /// Fallback for clients that can't set the `Last-Event-ID` header
+    pub last_event_id: Option<u64>,
+}
+
+fn sse_event(event: &ChangeEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.event_type())
+        .data(serde_json::to_string(event).unwrap_or_default())
+}
+
+/// Tells the client it missed events it can't get back and should refetch.
+fn reset_event() -> Event {
+    Event::default()
+        .event("reset")
+        .data(r#"{"reason":"missed events are no longer available; refetch current state"}"#)
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    summary = "Live changes as server-sent events",
+    params(
+        EventStreamQuery,
+        ("Last-Event-ID" = Option<String>, Header, description = "Resume after this event id"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent change events", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Unknown entity", body = String),
+    )
+)]
+pub async fn stream_events(
+    State(events): State<EventBus>,
+    State(shutdown): State<CancellationToken>,
+    Query(params): Query<EventStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let entities: Option<Vec<EntityKind>> = match &params.entity {
+        Some(list) => Some(
+            list.split(',')
+                .map(|e| {
+                    EntityKind::parse(e).ok_or((
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Unknown entity '{}'; expected animal, care or animal_care",
+                            e.trim()
+                        ),
+                    ))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        None => None,
+    };
+    let wanted = move |event: &ChangeEvent| {
+        entities
+            .as_ref()
+            .is_none_or(|list| list.contains(&event.entity))
+    };
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or(params.last_event_id);
+
+    // Subscribe before reading the history so nothing published in between is
+    // lost; anything seen in both is skipped by id below.
+    let receiver = events.subscribe();
+
+    let mut initial = Vec::new();
+    let mut already_sent = 0;
+    if let Some(last) = last_event_id {
+        match events.replay_after(last) {
+            Replay::Events(missed) => {
+                already_sent = missed.last().map(|e| e.id).unwrap_or(last);
+                initial.extend(missed.iter().filter(|e| wanted(e)).map(sse_event));
+            }
+            Replay::Gap => initial.push(reset_event()),
+        }
+    }
+
+    let live = BroadcastStream::new(receiver).filter_map(move |message| {
+        let event = match message {
+            Ok(event) if event.id > already_sent && wanted(&event) => Some(sse_event(&event)),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(_)) => Some(reset_event()),
+        };
+        async move { event.map(Ok) }
+    });
+
+    // Streams never end on their own; close them on shutdown so the server
+    // can drain. Clients reconnect with Last-Event-ID.
+    let stream = stream::iter(initial.into_iter().map(Ok))
+        .chain(live)
+        .take_until(shutdown.cancelled_owned());
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
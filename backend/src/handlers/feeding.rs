@@ -0,0 +1,1573 @@
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::handlers::animals::find_animal;
+use crate::handlers::inventory::{consume_for_feeding, convert_quantity, return_stock};
+use crate::handlers::species::find_species;
+use crate::models::{
+    DietPlan, DietPlanItem, DietPlanQuery, FEEDING_METHODS, FOOD_UNITS, FeedingDateQuery,
+    FeedingPeriodQuery, FeedingRecord, FeedingSheetEntry, FoodItem, FoodTotal, NewFeedingRecord,
+    RecordedFeeding, SaveDietPlan, SaveFoodItem, in_collection_sql,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use tiberius::Row;
+use tracing::error;
+
+const FOOD_ITEM_COLUMNS: &str = "food_item_id, name, unit, notes";
+
+/// Plans with the name of their animal or species; callers add the
+/// `WHERE`/`ORDER BY`.
+const PLAN_SELECT: &str = "SELECT p.diet_plan_id, p.fk_Animal_animal_id, a.name, p.fk_Species_species_id, s.common_name_pt, p.name, p.valid_from, p.valid_to, p.notes FROM Diet_Plan p LEFT JOIN Animal a ON a.animal_id = p.fk_Animal_animal_id LEFT JOIN Species s ON s.species_id = p.fk_Species_species_id";
+
+const ITEM_SELECT: &str = "SELECT i.diet_plan_item_id, i.fk_Diet_Plan_id, i.fk_Food_Item_id, f.name, f.unit, i.quantity, i.feeding_time, i.feeding_method, i.notes FROM Diet_Plan_Item i JOIN Food_Item f ON f.food_item_id = i.fk_Food_Item_id";
+
+const RECORD_SELECT: &str = "SELECT r.feeding_record_id, r.fk_Animal_animal_id, r.fk_Diet_Plan_Item_id, r.fk_Food_Item_id, f.name, f.unit, r.fed_on, r.quantity_given, r.quantity_refused, r.fed_by, r.notes, r.recorded_at FROM Feeding_Record r JOIN Food_Item f ON f.food_item_id = r.fk_Food_Item_id";
+
+const MAX_PLAN_ITEMS: usize = 50;
+/// Longest period the totals are worked out for
+const MAX_TOTALS_DAYS: i64 = 366;
+
+fn food_item_from_row(row: &Row) -> FoodItem {
+    FoodItem {
+        food_item_id: row.get::<i32, _>(0).unwrap_or(0),
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        unit: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        notes: row.get::<&str, _>(3).map(|s| s.to_string()),
+    }
+}
+
+fn plan_from_row(row: &Row) -> DietPlan {
+    DietPlan {
+        diet_plan_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1),
+        animal_name: row.get::<&str, _>(2).map(|s| s.to_string()),
+        species_id: row.get::<i32, _>(3),
+        species_name: row.get::<&str, _>(4).map(|s| s.to_string()),
+        name: row.get::<&str, _>(5).unwrap_or("").to_string(),
+        valid_from: row.get(6),
+        valid_to: row.get(7),
+        notes: row.get::<&str, _>(8).map(|s| s.to_string()),
+        items: Vec::new(),
+    }
+}
+
+/// An item with the id of its plan.
+fn item_from_row(row: &Row) -> (i32, DietPlanItem) {
+    (
+        row.get::<i32, _>(1).unwrap_or(0),
+        DietPlanItem {
+            diet_plan_item_id: row.get::<i32, _>(0).unwrap_or(0),
+            food_item_id: row.get::<i32, _>(2).unwrap_or(0),
+            food_item_name: row.get::<&str, _>(3).unwrap_or("").to_string(),
+            unit: row.get::<&str, _>(4).unwrap_or("").to_string(),
+            quantity: row.get::<f64, _>(5).unwrap_or(0.0),
+            feeding_time: row.get(6),
+            feeding_method: row.get::<&str, _>(7).unwrap_or("").to_string(),
+            notes: row.get::<&str, _>(8).map(|s| s.to_string()),
+        },
+    )
+}
+
+fn record_from_row(row: &Row) -> FeedingRecord {
+    FeedingRecord {
+        feeding_record_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        diet_plan_item_id: row.get::<i32, _>(2),
+        food_item_id: row.get::<i32, _>(3).unwrap_or(0),
+        food_item_name: row.get::<&str, _>(4).unwrap_or("").to_string(),
+        unit: row.get::<&str, _>(5).unwrap_or("").to_string(),
+        fed_on: row.get(6),
+        quantity_given: row.get::<f64, _>(7).unwrap_or(0.0),
+        quantity_refused: row.get::<f64, _>(8).unwrap_or(0.0),
+        fed_by: row.get::<&str, _>(9).map(|s| s.to_string()),
+        notes: row.get::<&str, _>(10).map(|s| s.to_string()),
+        recorded_at: row.get(11),
+    }
+}
+
+pub async fn find_food_item(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<FoodItem>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Food_Item WHERE food_item_id = @P1",
+        FOOD_ITEM_COLUMNS
+    );
+    let rows = fetch_rows(client, &query, &[&id]).await?;
+    Ok(rows.first().map(food_item_from_row))
+}
+
+fn validate_food_item(payload: &SaveFoodItem) -> Result<(), (StatusCode, String)> {
+    if payload.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "name is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    if !FOOD_UNITS.contains(&payload.unit.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown unit '{}'; expected one of {}",
+                payload.unit,
+                FOOD_UNITS.join(", ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+async fn check_food_item_name_free(
+    client: &mut DbClient,
+    name: &str,
+    food_item_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let rows = fetch_rows(
+        client,
+        "SELECT food_item_id FROM Food_Item WHERE name = @P1",
+        &[&name.trim()],
+    )
+    .await?;
+
+    match rows.first().and_then(|r| r.get::<i32, _>(0)) {
+        Some(owner) if Some(owner) != food_item_id => Err((
+            StatusCode::CONFLICT,
+            format!("Food item {} is already named '{}'", owner, name.trim()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that an inventory item kept of a food item still converts to the
+/// unit it is fed in.
+async fn check_food_item_unit_stocked(
+    client: &mut DbClient,
+    food_item_id: i32,
+    unit: &str,
+) -> Result<(), (StatusCode, String)> {
+    let rows = fetch_rows(
+        client,
+        "SELECT inventory_item_id, unit FROM Inventory_Item WHERE fk_Food_Item_id = @P1",
+        &[&food_item_id],
+    )
+    .await?;
+
+    let Some(row) = rows.first() else {
+        return Ok(());
+    };
+    let stock_unit = row.get::<&str, _>(1).unwrap_or("");
+    if convert_quantity(1.0, unit, stock_unit).is_none() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Inventory item {} counts this food item in {}, which {} doesn't convert to",
+                row.get::<i32, _>(0).unwrap_or(0),
+                stock_unit,
+                unit
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Run a plan query and fill in the items of each plan returned.
+async fn query_plans(
+    client: &mut DbClient,
+    query: &str,
+    params: &[&dyn tiberius::ToSql],
+) -> Result<Vec<DietPlan>, (StatusCode, String)> {
+    let rows = fetch_rows(client, query, params).await?;
+    let mut plans: Vec<DietPlan> = rows.iter().map(plan_from_row).collect();
+    if plans.is_empty() {
+        return Ok(plans);
+    }
+
+    // Ids come from the database, so they can be inlined
+    let ids = plans
+        .iter()
+        .map(|p| p.diet_plan_id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let item_query = format!(
+        "{} WHERE i.fk_Diet_Plan_id IN ({}) ORDER BY i.feeding_time, i.diet_plan_item_id",
+        ITEM_SELECT, ids
+    );
+    let rows = fetch_rows(client, &item_query, &[]).await?;
+
+    let mut items: HashMap<i32, Vec<DietPlanItem>> = HashMap::new();
+    for (plan_id, item) in rows.iter().map(item_from_row) {
+        items.entry(plan_id).or_default().push(item);
+    }
+    for plan in &mut plans {
+        plan.items = items.remove(&plan.diet_plan_id).unwrap_or_default();
+    }
+
+    Ok(plans)
+}
+
+async fn find_plan(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<DietPlan>, (StatusCode, String)> {
+    let query = format!("{} WHERE p.diet_plan_id = @P1", PLAN_SELECT);
+    Ok(query_plans(client, &query, &[&id]).await?.pop())
+}
+
+/// Plans in effect on any day from `from` to `to`.
+async fn plans_between(
+    client: &mut DbClient,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DietPlan>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE p.valid_from <= @P2 AND (p.valid_to IS NULL OR p.valid_to >= @P1)",
+        PLAN_SELECT
+    );
+    query_plans(client, &query, &[&from, &to]).await
+}
+
+/// The plan an animal is fed by on a day: its own, else its species'.
+fn plan_for(
+    plans: &[DietPlan],
+    animal_id: i32,
+    species_id: Option<i32>,
+    date: NaiveDate,
+) -> Option<&DietPlan> {
+    let in_effect = |p: &&DietPlan| {
+        p.valid_from.is_some_and(|from| from <= date) && p.valid_to.is_none_or(|to| to >= date)
+    };
+    plans
+        .iter()
+        .filter(in_effect)
+        .find(|p| p.animal_id == Some(animal_id))
+        .or_else(|| {
+            plans
+                .iter()
+                .filter(in_effect)
+                .find(|p| species_id.is_some() && p.species_id == species_id)
+        })
+}
+
+/// An animal being fed, with where it is now.
+struct KeptAnimal {
+    animal_id: i32,
+    name: String,
+    species_id: Option<i32>,
+    species_name: Option<String>,
+    enclosure_name: Option<String>,
+}
+
+/// Animals in the collection, by name.
+async fn kept_animals(client: &mut DbClient) -> Result<Vec<KeptAnimal>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT a.animal_id, a.name, a.fk_Species_species_id, s.common_name_pt, e.name FROM Animal a LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id LEFT JOIN Enclosure_Assignment ea ON ea.fk_Animal_animal_id = a.animal_id AND ea.removed_at IS NULL LEFT JOIN Enclosure e ON e.enclosure_id = ea.fk_Enclosure_enclosure_id WHERE a.status IN {} ORDER BY a.name, a.animal_id",
+        in_collection_sql()
+    );
+    let rows = fetch_rows(client, &query, &[]).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| KeptAnimal {
+            animal_id: row.get::<i32, _>(0).unwrap_or(0),
+            name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+            species_id: row.get::<i32, _>(2),
+            species_name: row.get::<&str, _>(3).map(|s| s.to_string()),
+            enclosure_name: row.get::<&str, _>(4).map(|s| s.to_string()),
+        })
+        .collect())
+}
+
+/// `HH:MM` or `HH:MM:SS`.
+fn parse_feeding_time(value: &str) -> Result<NaiveTime, (StatusCode, String)> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("feeding_time '{}' must be HH:MM", value),
+            )
+        })
+}
+
+/// A plan item checked and ready to be written.
+struct ValidItem {
+    diet_plan_item_id: Option<i32>,
+    food_item_id: i32,
+    quantity: f64,
+    feeding_time: NaiveTime,
+    feeding_method: String,
+    notes: Option<String>,
+}
+
+/// A plan checked and ready to be written.
+struct ValidDietPlan {
+    valid_from: NaiveDate,
+    valid_to: Option<NaiveDate>,
+    items: Vec<ValidItem>,
+}
+
+fn validate_diet_plan(payload: &SaveDietPlan) -> Result<ValidDietPlan, (StatusCode, String)> {
+    if payload.animal_id.is_some() == payload.species_id.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "A diet plan is for either an animal_id or a species_id".to_string(),
+        ));
+    }
+    if payload.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "name is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+
+    let valid_from = parse_date("valid_from", payload.valid_from.as_deref())?
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let valid_to = parse_date("valid_to", payload.valid_to.as_deref())?;
+    if valid_to.is_some_and(|to| to < valid_from) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "valid_to can't be before valid_from".to_string(),
+        ));
+    }
+
+    if payload.items.is_empty() || payload.items.len() > MAX_PLAN_ITEMS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("A diet plan has 1 to {} items", MAX_PLAN_ITEMS),
+        ));
+    }
+
+    let mut items = Vec::new();
+    for item in &payload.items {
+        if let Some(id) = item.diet_plan_item_id
+            && items
+                .iter()
+                .any(|i: &ValidItem| i.diet_plan_item_id == Some(id))
+        {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("diet_plan_item_id {} is listed twice", id),
+            ));
+        }
+        if !(item.quantity.is_finite() && item.quantity > 0.0) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "quantity must be positive".to_string(),
+            ));
+        }
+        if !FEEDING_METHODS.contains(&item.feeding_method.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unknown feeding_method '{}'; expected one of {}",
+                    item.feeding_method,
+                    FEEDING_METHODS.join(", ")
+                ),
+            ));
+        }
+        check_length("notes", item.notes.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+        items.push(ValidItem {
+            diet_plan_item_id: item.diet_plan_item_id,
+            food_item_id: item.food_item_id,
+            quantity: item.quantity,
+            feeding_time: parse_feeding_time(&item.feeding_time)?,
+            feeding_method: item.feeding_method.clone(),
+            notes: item.notes.clone(),
+        });
+    }
+
+    Ok(ValidDietPlan {
+        valid_from,
+        valid_to,
+        items,
+    })
+}
+
+/// Check that the animal or species and the food items of a plan exist, and
+/// that no other plan of the same animal or species overlaps it.
+async fn check_plan_references(
+    client: &mut DbClient,
+    payload: &SaveDietPlan,
+    plan: &ValidDietPlan,
+    diet_plan_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    if let Some(animal_id) = payload.animal_id
+        && find_animal(client, animal_id)
+            .await?
+            .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown animal_id {}", animal_id),
+        ));
+    }
+    if let Some(species_id) = payload.species_id
+        && find_species(client, species_id).await?.is_none()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown species_id {}", species_id),
+        ));
+    }
+    for item in &plan.items {
+        if find_food_item(client, item.food_item_id).await?.is_none() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unknown food_item_id {}", item.food_item_id),
+            ));
+        }
+    }
+
+    let query = "SELECT TOP 1 diet_plan_id FROM Diet_Plan WHERE (fk_Animal_animal_id = @P1 OR fk_Species_species_id = @P2) AND diet_plan_id <> @P3 AND (@P5 IS NULL OR valid_from <= @P5) AND (valid_to IS NULL OR valid_to >= @P4)";
+    let rows = fetch_rows(
+        client,
+        query,
+        &[
+            &payload.animal_id,
+            &payload.species_id,
+            &diet_plan_id.unwrap_or(0),
+            &plan.valid_from,
+            &plan.valid_to,
+        ],
+    )
+    .await?;
+    if let Some(other) = rows.first().and_then(|r| r.get::<i32, _>(0)) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Diet plan {} is already in effect for some of these days; end it with valid_to first",
+                other
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn count_records_of_items(
+    client: &mut DbClient,
+    item_ids: &[i32],
+) -> Result<i32, (StatusCode, String)> {
+    if item_ids.is_empty() {
+        return Ok(0);
+    }
+    let ids = item_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "SELECT COUNT(*) FROM Feeding_Record WHERE fk_Diet_Plan_Item_id IN ({})",
+        ids
+    );
+    let rows = fetch_rows(client, &query, &[]).await?;
+    Ok(rows.first().and_then(|r| r.get::<i32, _>(0)).unwrap_or(0))
+}
+
+/// Write the items of a plan: update those kept by id, add the new ones and
+/// remove the ones left out. Items already fed from can't be removed.
+async fn save_items(
+    client: &mut DbClient,
+    diet_plan_id: i32,
+    items: &[ValidItem],
+    existing: &[DietPlanItem],
+) -> Result<(), (StatusCode, String)> {
+    for id in items.iter().filter_map(|i| i.diet_plan_item_id) {
+        if !existing.iter().any(|e| e.diet_plan_item_id == id) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Item {} is not part of diet plan {}", id, diet_plan_id),
+            ));
+        }
+    }
+
+    let removed: Vec<i32> = existing
+        .iter()
+        .map(|e| e.diet_plan_item_id)
+        .filter(|id| !items.iter().any(|i| i.diet_plan_item_id == Some(*id)))
+        .collect();
+    if count_records_of_items(client, &removed).await? > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            "Items that were already fed can't be removed; end the plan with valid_to and start a new one".to_string(),
+        ));
+    }
+    for id in &removed {
+        client
+            .execute(
+                "DELETE FROM Diet_Plan_Item WHERE diet_plan_item_id = @P1",
+                &[id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Diet plan item delete error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Diet plan item delete error: {}", e),
+                )
+            })?;
+    }
+
+    for item in items {
+        let (query, id) = match item.diet_plan_item_id {
+            Some(id) => (
+                "UPDATE Diet_Plan_Item SET fk_Diet_Plan_id = @P2, fk_Food_Item_id = @P3, quantity = @P4, feeding_time = @P5, feeding_method = @P6, notes = @P7 WHERE diet_plan_item_id = @P1",
+                id,
+            ),
+            None => (
+                "INSERT INTO Diet_Plan_Item (diet_plan_item_id, fk_Diet_Plan_id, fk_Food_Item_id, quantity, feeding_time, feeding_method, notes) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7)",
+                next_id(client, "Diet_Plan_Item", "diet_plan_item_id").await?,
+            ),
+        };
+        client
+            .execute(
+                query,
+                &[
+                    &id,
+                    &diet_plan_id,
+                    &item.food_item_id,
+                    &item.quantity,
+                    &item.feeding_time,
+                    &item.feeding_method.as_str(),
+                    &item.notes,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Diet plan item save error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Diet plan item save error: {}", e),
+                )
+            })?;
+    }
+
+    Ok(())
+}
+
+async fn check_animal_kept(client: &mut DbClient, id: i32) -> Result<(), (StatusCode, String)> {
+    if find_animal(client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/food-items",
+    tag = "feeding",
+    summary = "List food items",
+    responses(
+        (status = 200, description = "Every food item, by name", body = Vec<FoodItem>),
+    )
+)]
+pub async fn get_food_items(
+    State(db): State<Database>,
+) -> Result<Json<Vec<FoodItem>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!("SELECT {} FROM Food_Item ORDER BY name", FOOD_ITEM_COLUMNS);
+    let rows = fetch_rows(&mut client, &query, &[]).await?;
+
+    Ok(Json(rows.iter().map(food_item_from_row).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/food-items/{id}",
+    tag = "feeding",
+    summary = "Get food item by id",
+    params(
+        ("id" = i32, Path, description = "Food item id"),
+    ),
+    responses(
+        (status = 200, description = "Food item found", body = FoodItem),
+        (status = 404, description = "Food item not found", body = String),
+    )
+)]
+pub async fn get_food_item_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<FoodItem>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_food_item(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Food item with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/food-items",
+    tag = "feeding",
+    summary = "Add a food item",
+    request_body = SaveFoodItem,
+    responses(
+        (status = 201, description = "Food item created", body = FoodItem),
+        (status = 400, description = "Invalid food item", body = String),
+        (status = 409, description = "Name already used", body = String),
+    )
+)]
+pub async fn add_food_item(
+    State(db): State<Database>,
+    Json(payload): Json<SaveFoodItem>,
+) -> Result<(StatusCode, Json<FoodItem>), (StatusCode, String)> {
+    validate_food_item(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_food_item_name_free(&mut client, &payload.name, None).await?;
+    let new_id = next_id(&mut client, "Food_Item", "food_item_id").await?;
+
+    client
+        .execute(
+            "INSERT INTO Food_Item (food_item_id, name, unit, notes) VALUES (@P1, @P2, @P3, @P4)",
+            &[
+                &new_id,
+                &payload.name.trim(),
+                &payload.unit.as_str(),
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let created = find_food_item(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created food item".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/food-items/{id}",
+    tag = "feeding",
+    summary = "Update a food item",
+    params(
+        ("id" = i32, Path, description = "Food item id"),
+    ),
+    request_body = SaveFoodItem,
+    responses(
+        (status = 200, description = "Food item updated", body = FoodItem),
+        (status = 400, description = "Invalid food item", body = String),
+        (status = 404, description = "Food item not found", body = String),
+        (status = 409, description = "Name already used, or the unit doesn't convert to the unit its stock is counted in", body = String),
+    )
+)]
+pub async fn update_food_item(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveFoodItem>,
+) -> Result<Json<FoodItem>, (StatusCode, String)> {
+    validate_food_item(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_food_item_name_free(&mut client, &payload.name, Some(id)).await?;
+    check_food_item_unit_stocked(&mut client, id, &payload.unit).await?;
+
+    let rows_affected = client
+        .execute(
+            "UPDATE Food_Item SET name = @P2, unit = @P3, notes = @P4 WHERE food_item_id = @P1",
+            &[
+                &id,
+                &payload.name.trim(),
+                &payload.unit.as_str(),
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Food item with id {} not found", id),
+        ));
+    }
+
+    let updated = find_food_item(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated food item".to_string(),
+    ))?;
+
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/food-items/{id}",
+    tag = "feeding",
+    summary = "Delete a food item no plan or record uses",
+    params(
+        ("id" = i32, Path, description = "Food item id"),
+    ),
+    responses(
+        (status = 204, description = "Food item deleted"),
+        (status = 404, description = "Food item not found", body = String),
+        (status = 409, description = "Food item is in a diet plan or was fed", body = String),
+    )
+)]
+pub async fn delete_food_item(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = "SELECT (SELECT COUNT(*) FROM Diet_Plan_Item WHERE fk_Food_Item_id = @P1) + (SELECT COUNT(*) FROM Feeding_Record WHERE fk_Food_Item_id = @P1)";
+    let rows = fetch_rows(&mut client, query, &[&id]).await?;
+    let uses = rows.first().and_then(|r| r.get::<i32, _>(0)).unwrap_or(0);
+    if uses > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Food item with id {} is used by {} diet plan item(s) or feeding record(s)",
+                id, uses
+            ),
+        ));
+    }
+
+    let rows_affected = client
+        .execute("DELETE FROM Food_Item WHERE food_item_id = @P1", &[&id])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Food item with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/diet-plans",
+    tag = "feeding",
+    summary = "List diet plans",
+    params(DietPlanQuery),
+    responses(
+        (status = 200, description = "Plans matching the filters, latest first", body = Vec<DietPlan>),
+        (status = 400, description = "Invalid date", body = String),
+    )
+)]
+pub async fn get_diet_plans(
+    State(db): State<Database>,
+    Query(params): Query<DietPlanQuery>,
+) -> Result<Json<Vec<DietPlan>>, (StatusCode, String)> {
+    let active_on = parse_date("active_on", params.active_on.as_deref())?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "{} WHERE (@P1 IS NULL OR p.fk_Animal_animal_id = @P1) AND (@P2 IS NULL OR p.fk_Species_species_id = @P2) AND (@P3 IS NULL OR (p.valid_from <= @P3 AND (p.valid_to IS NULL OR p.valid_to >= @P3))) ORDER BY p.valid_from DESC, p.diet_plan_id DESC",
+        PLAN_SELECT
+    );
+    let plans = query_plans(
+        &mut client,
+        &query,
+        &[&params.animal_id, &params.species_id, &active_on],
+    )
+    .await?;
+
+    Ok(Json(plans))
+}
+
+#[utoipa::path(
+    get,
+    path = "/diet-plans/{id}",
+    tag = "feeding",
+    summary = "Get diet plan by id",
+    params(
+        ("id" = i32, Path, description = "Diet plan id"),
+    ),
+    responses(
+        (status = 200, description = "Diet plan found", body = DietPlan),
+        (status = 404, description = "Diet plan not found", body = String),
+    )
+)]
+pub async fn get_diet_plan_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<DietPlan>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_plan(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Diet plan with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/diet-plans",
+    tag = "feeding",
+    summary = "Add a diet plan for an animal or a species",
+    request_body = SaveDietPlan,
+    responses(
+        (status = 201, description = "Diet plan created", body = DietPlan),
+        (status = 400, description = "Invalid diet plan", body = String),
+        (status = 409, description = "Another plan of the animal or species overlaps it", body = String),
+    )
+)]
+pub async fn add_diet_plan(
+    State(db): State<Database>,
+    Json(payload): Json<SaveDietPlan>,
+) -> Result<(StatusCode, Json<DietPlan>), (StatusCode, String)> {
+    let plan = validate_diet_plan(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_plan_references(&mut client, &payload, &plan, None).await?;
+    let new_id = next_id(&mut client, "Diet_Plan", "diet_plan_id").await?;
+
+    client
+        .execute(
+            "INSERT INTO Diet_Plan (diet_plan_id, fk_Animal_animal_id, fk_Species_species_id, name, valid_from, valid_to, notes) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7)",
+            &[
+                &new_id,
+                &payload.animal_id,
+                &payload.species_id,
+                &payload.name.trim(),
+                &plan.valid_from,
+                &plan.valid_to,
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    save_items(&mut client, new_id, &plan.items, &[]).await?;
+
+    let created = find_plan(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created diet plan".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/diet-plans/{id}",
+    tag = "feeding",
+    summary = "Replace a diet plan",
+    description = "Items sent with their `diet_plan_item_id` are kept, the others added; items left out are removed unless they were already fed.",
+    params(
+        ("id" = i32, Path, description = "Diet plan id"),
+    ),
+    request_body = SaveDietPlan,
+    responses(
+        (status = 200, description = "Diet plan updated", body = DietPlan),
+        (status = 400, description = "Invalid diet plan", body = String),
+        (status = 404, description = "Diet plan not found", body = String),
+        (status = 409, description = "Another plan overlaps it, or a fed item was left out", body = String),
+    )
+)]
+pub async fn update_diet_plan(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveDietPlan>,
+) -> Result<Json<DietPlan>, (StatusCode, String)> {
+    let plan = validate_diet_plan(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let existing = find_plan(&mut client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Diet plan with id {} not found", id),
+    ))?;
+    check_plan_references(&mut client, &payload, &plan, Some(id)).await?;
+
+    client
+        .execute(
+            "UPDATE Diet_Plan SET fk_Animal_animal_id = @P2, fk_Species_species_id = @P3, name = @P4, valid_from = @P5, valid_to = @P6, notes = @P7 WHERE diet_plan_id = @P1",
+            &[
+                &id,
+                &payload.animal_id,
+                &payload.species_id,
+                &payload.name.trim(),
+                &plan.valid_from,
+                &plan.valid_to,
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    save_items(&mut client, id, &plan.items, &existing.items).await?;
+
+    let updated = find_plan(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated diet plan".to_string(),
+    ))?;
+
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/diet-plans/{id}",
+    tag = "feeding",
+    summary = "Delete a diet plan nothing was fed from",
+    params(
+        ("id" = i32, Path, description = "Diet plan id"),
+    ),
+    responses(
+        (status = 204, description = "Diet plan deleted"),
+        (status = 404, description = "Diet plan not found", body = String),
+        (status = 409, description = "Feeds were recorded against the plan; end it with valid_to instead", body = String),
+    )
+)]
+pub async fn delete_diet_plan(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let plan = find_plan(&mut client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Diet plan with id {} not found", id),
+    ))?;
+    let item_ids: Vec<i32> = plan.items.iter().map(|i| i.diet_plan_item_id).collect();
+    let records = count_records_of_items(&mut client, &item_ids).await?;
+    if records > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Diet plan with id {} has {} feeding record(s); end it with valid_to instead",
+                id, records
+            ),
+        ));
+    }
+
+    for query in [
+        "DELETE FROM Diet_Plan_Item WHERE fk_Diet_Plan_id = @P1",
+        "DELETE FROM Diet_Plan WHERE diet_plan_id = @P1",
+    ] {
+        client.execute(query, &[&id]).await.map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/diet-plan",
+    tag = "feeding",
+    summary = "The diet plan an animal is fed by on a day",
+    description = "The animal's own plan, or else the plan of its species.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        FeedingDateQuery,
+    ),
+    responses(
+        (status = 200, description = "Diet plan in effect", body = DietPlan),
+        (status = 400, description = "Invalid date", body = String),
+        (status = 404, description = "Animal not found, or no plan in effect", body = String),
+    )
+)]
+pub async fn get_animal_diet_plan(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<FeedingDateQuery>,
+) -> Result<Json<DietPlan>, (StatusCode, String)> {
+    let date =
+        parse_date("date", params.date.as_deref())?.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let animal = find_animal(&mut client, id)
+        .await?
+        .filter(|a| a.status != "removed")
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ))?;
+
+    let plans = plans_between(&mut client, date, date).await?;
+    plan_for(&plans, id, animal.species_id, date)
+        .cloned()
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("No diet plan for animal {} on {}", id, date),
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/feeding-sheet",
+    tag = "feeding",
+    summary = "Feeding sheet of a day",
+    description = "Every feed due that day for the animals in the collection, by time and enclosure, with what was recorded for it so far.",
+    params(FeedingDateQuery),
+    responses(
+        (status = 200, description = "Feeds of the day", body = Vec<FeedingSheetEntry>),
+        (status = 400, description = "Invalid date", body = String),
+    )
+)]
+pub async fn get_feeding_sheet(
+    State(db): State<Database>,
+    Query(params): Query<FeedingDateQuery>,
+) -> Result<Json<Vec<FeedingSheetEntry>>, (StatusCode, String)> {
+    let date =
+        parse_date("date", params.date.as_deref())?.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let plans = plans_between(&mut client, date, date).await?;
+    let animals = kept_animals(&mut client).await?;
+
+    let query = format!(
+        "{} WHERE r.fed_on = @P1 AND r.fk_Diet_Plan_Item_id IS NOT NULL",
+        RECORD_SELECT
+    );
+    let rows = fetch_rows(&mut client, &query, &[&date]).await?;
+    let mut records: HashMap<(i32, i32), FeedingRecord> = rows
+        .iter()
+        .map(record_from_row)
+        .filter_map(|r| Some(((r.animal_id, r.diet_plan_item_id?), r)))
+        .collect();
+
+    let mut sheet = Vec::new();
+    for animal in &animals {
+        let Some(plan) = plan_for(&plans, animal.animal_id, animal.species_id, date) else {
+            continue;
+        };
+        for item in &plan.items {
+            sheet.push(FeedingSheetEntry {
+                animal_id: animal.animal_id,
+                animal_name: animal.name.clone(),
+                species_name: animal.species_name.clone(),
+                enclosure_name: animal.enclosure_name.clone(),
+                diet_plan_id: plan.diet_plan_id,
+                diet_plan_item_id: item.diet_plan_item_id,
+                feeding_time: item.feeding_time,
+                food_item_id: item.food_item_id,
+                food_item_name: item.food_item_name.clone(),
+                unit: item.unit.clone(),
+                quantity: item.quantity,
+                feeding_method: item.feeding_method.clone(),
+                notes: item.notes.clone(),
+                record: records.remove(&(animal.animal_id, item.diet_plan_item_id)),
+            });
+        }
+    }
+    // Stable, so animals stay by name within an enclosure
+    sheet.sort_by(|a, b| {
+        (a.feeding_time, &a.enclosure_name).cmp(&(b.feeding_time, &b.enclosure_name))
+    });
+
+    Ok(Json(sheet))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/feeding-records",
+    tag = "feeding",
+    summary = "What an animal was fed, most recent first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        FeedingPeriodQuery,
+    ),
+    responses(
+        (status = 200, description = "Feeding records in the period", body = Vec<FeedingRecord>),
+        (status = 400, description = "Invalid date", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_feeding_records_of_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<FeedingPeriodQuery>,
+) -> Result<Json<Vec<FeedingRecord>>, (StatusCode, String)> {
+    let from = parse_date("from", params.from.as_deref())?;
+    let to = parse_date("to", params.to.as_deref())?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_animal_kept(&mut client, id).await?;
+
+    let query = format!(
+        "{} WHERE r.fk_Animal_animal_id = @P1 AND (@P2 IS NULL OR r.fed_on >= @P2) AND (@P3 IS NULL OR r.fed_on <= @P3) ORDER BY r.fed_on DESC, r.feeding_record_id DESC",
+        RECORD_SELECT
+    );
+    let rows = fetch_rows(&mut client, &query, &[&id, &from, &to]).await?;
+
+    Ok(Json(rows.iter().map(record_from_row).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/feeding-records",
+    tag = "feeding",
+    summary = "Record a feed given to an animal",
+    description = "A planned feed is recorded against its `diet_plan_item_id`, once per day; anything else against a `food_item_id`.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = NewFeedingRecord,
+    responses(
+        (status = 201, description = "Feed recorded, with any stock warnings", body = RecordedFeeding),
+        (status = 400, description = "Invalid record, or the item isn't in the animal's plan that day", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "The planned feed was already recorded that day", body = String),
+    )
+)]
+pub async fn add_feeding_record(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<NewFeedingRecord>,
+) -> Result<(StatusCode, Json<RecordedFeeding>), (StatusCode, String)> {
+    check_length(
+        "fed_by",
+        payload.fed_by.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    let today = Utc::now().date_naive();
+    let fed_on = parse_date("fed_on", payload.fed_on.as_deref())?.unwrap_or(today);
+    if fed_on > today {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "fed_on can't be in the future".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let animal = find_animal(&mut client, id)
+        .await?
+        .filter(|a| a.status != "removed")
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ))?;
+
+    let (food_item_id, planned_quantity) = match payload.diet_plan_item_id {
+        Some(item_id) => {
+            let plans = plans_between(&mut client, fed_on, fed_on).await?;
+            let item = plan_for(&plans, id, animal.species_id, fed_on)
+                .and_then(|p| p.items.iter().find(|i| i.diet_plan_item_id == item_id))
+                .ok_or((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Diet plan item {} isn't in the plan of animal {} on {}",
+                        item_id, id, fed_on
+                    ),
+                ))?;
+            if payload
+                .food_item_id
+                .is_some_and(|food_item_id| food_item_id != item.food_item_id)
+            {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Diet plan item {} is of food item {}",
+                        item_id, item.food_item_id
+                    ),
+                ));
+            }
+            (item.food_item_id, Some(item.quantity))
+        }
+        None => {
+            let food_item_id = payload.food_item_id.ok_or((
+                StatusCode::BAD_REQUEST,
+                "Either diet_plan_item_id or food_item_id is required".to_string(),
+            ))?;
+            if find_food_item(&mut client, food_item_id).await?.is_none() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown food_item_id {}", food_item_id),
+                ));
+            }
+            (food_item_id, None)
+        }
+    };
+
+    let quantity_given = payload.quantity_given.or(planned_quantity).ok_or((
+        StatusCode::BAD_REQUEST,
+        "quantity_given is required for feeds outside the plan".to_string(),
+    ))?;
+    let quantity_refused = payload.quantity_refused.unwrap_or(0.0);
+    if !(quantity_given.is_finite() && quantity_given >= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "quantity_given can't be negative".to_string(),
+        ));
+    }
+    if !(quantity_refused.is_finite() && (0.0..=quantity_given).contains(&quantity_refused)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "quantity_refused must be between 0 and quantity_given".to_string(),
+        ));
+    }
+
+    if let Some(item_id) = payload.diet_plan_item_id {
+        let rows = fetch_rows(
+            &mut client,
+            "SELECT feeding_record_id FROM Feeding_Record WHERE fk_Animal_animal_id = @P1 AND fk_Diet_Plan_Item_id = @P2 AND fed_on = @P3",
+            &[&id, &item_id, &fed_on],
+        )
+        .await?;
+        if let Some(existing) = rows.first().and_then(|r| r.get::<i32, _>(0)) {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Diet plan item {} was already recorded on {} as record {}",
+                    item_id, fed_on, existing
+                ),
+            ));
+        }
+    }
+
+    let created = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Feeding_Record", "feeding_record_id").await?;
+
+        client
+            .execute(
+                "INSERT INTO Feeding_Record (feeding_record_id, fk_Animal_animal_id, fk_Diet_Plan_Item_id, fk_Food_Item_id, fed_on, quantity_given, quantity_refused, fed_by, notes, recorded_at) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, @P9, SYSUTCDATETIME())",
+                &[
+                    &new_id,
+                    &id,
+                    &payload.diet_plan_item_id,
+                    &food_item_id,
+                    &fed_on,
+                    &quantity_given,
+                    &quantity_refused,
+                    &payload.fed_by,
+                    &payload.notes,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        let query = format!("{} WHERE r.feeding_record_id = @P1", RECORD_SELECT);
+        let rows = fetch_rows(client, &query, &[&new_id]).await?;
+        let record = rows.first().map(record_from_row).ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to fetch created feeding record".to_string(),
+        ))?;
+
+        let warnings = consume_for_feeding(
+            client,
+            record.food_item_id,
+            &record.unit,
+            record.quantity_given,
+            new_id,
+            record.fed_by.as_deref(),
+        )
+        .await?;
+
+        Ok(RecordedFeeding { record, warnings })
+    })
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/feeding-records/{id}",
+    tag = "feeding",
+    summary = "Delete a feeding record made by mistake",
+    params(
+        ("id" = i32, Path, description = "Feeding record id"),
+    ),
+    responses(
+        (status = 204, description = "Feeding record deleted"),
+        (status = 404, description = "Feeding record not found", body = String),
+    )
+)]
+pub async fn delete_feeding_record(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client
+            .execute(
+                "DELETE FROM Feeding_Record WHERE feeding_record_id = @P1",
+                &[&id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Delete error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Delete error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Feeding record with id {} not found", id),
+            ));
+        }
+
+        return_stock(client, "feeding_record", id).await
+    })
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/feeding-totals",
+    tag = "feeding",
+    summary = "Totals per food item over a period, for procurement",
+    description = "`planned` sums the feeding sheets of each day for the animals kept now; `given`, `refused` and `eaten` sum the feeding records. The period defaults to the 7 days from today.",
+    params(FeedingPeriodQuery),
+    responses(
+        (status = 200, description = "One total per food item planned or fed, by name", body = Vec<FoodTotal>),
+        (status = 400, description = "Invalid period", body = String),
+    )
+)]
+pub async fn get_feeding_totals(
+    State(db): State<Database>,
+    Query(params): Query<FeedingPeriodQuery>,
+) -> Result<Json<Vec<FoodTotal>>, (StatusCode, String)> {
+    let from =
+        parse_date("from", params.from.as_deref())?.unwrap_or_else(|| Utc::now().date_naive());
+    let to = parse_date("to", params.to.as_deref())?.unwrap_or(from + Duration::days(6));
+    if to < from {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "to can't be before from".to_string(),
+        ));
+    }
+    if (to - from).num_days() >= MAX_TOTALS_DAYS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("The period can be at most {} days", MAX_TOTALS_DAYS),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let plans = plans_between(&mut client, from, to).await?;
+    let animals = kept_animals(&mut client).await?;
+
+    let mut totals: BTreeMap<i32, FoodTotal> = BTreeMap::new();
+    let mut date = from;
+    while date <= to {
+        for animal in &animals {
+            let Some(plan) = plan_for(&plans, animal.animal_id, animal.species_id, date) else {
+                continue;
+            };
+            for item in &plan.items {
+                totals
+                    .entry(item.food_item_id)
+                    .or_insert_with(|| FoodTotal {
+                        food_item_id: item.food_item_id,
+                        food_item_name: item.food_item_name.clone(),
+                        unit: item.unit.clone(),
+                        planned: 0.0,
+                        given: 0.0,
+                        refused: 0.0,
+                        eaten: 0.0,
+                    })
+                    .planned += item.quantity;
+            }
+        }
+        date += Duration::days(1);
+    }
+
+    let query = "SELECT r.fk_Food_Item_id, f.name, f.unit, SUM(r.quantity_given), SUM(r.quantity_refused) FROM Feeding_Record r JOIN Food_Item f ON f.food_item_id = r.fk_Food_Item_id WHERE r.fed_on BETWEEN @P1 AND @P2 GROUP BY r.fk_Food_Item_id, f.name, f.unit";
+    let rows = fetch_rows(&mut client, query, &[&from, &to]).await?;
+    for row in &rows {
+        let food_item_id = row.get::<i32, _>(0).unwrap_or(0);
+        let total = totals.entry(food_item_id).or_insert_with(|| FoodTotal {
+            food_item_id,
+            food_item_name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+            unit: row.get::<&str, _>(2).unwrap_or("").to_string(),
+            planned: 0.0,
+            given: 0.0,
+            refused: 0.0,
+            eaten: 0.0,
+        });
+        total.given = row.get::<f64, _>(3).unwrap_or(0.0);
+        total.refused = row.get::<f64, _>(4).unwrap_or(0.0);
+        total.eaten = total.given - total.refused;
+    }
+
+    let mut totals: Vec<FoodTotal> = totals.into_values().collect();
+    totals.sort_by(|a, b| a.food_item_name.cmp(&b.food_item_name));
+
+    Ok(Json(totals))
+}
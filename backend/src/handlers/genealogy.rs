@@ -0,0 +1,1360 @@
+use crate::db::{Database, DbClient, in_transaction};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::handlers::animal_revisions::record_animal_revision;
+use crate::handlers::animals::find_animal;
+use crate::handlers::species::{find_species, resolve_species};
+use crate::models::{
+    Animal, ExternalAnimal, InbreedingQuery, InbreedingResult, PedigreeAnimal, PedigreeQuery,
+    Relative, SaveExternalAnimal, SetParents, StudbookEntry, StudbookQuery,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+use tiberius::Row;
+use tracing::error;
+
+/// External animals with their species name; callers add the `WHERE`/`ORDER BY`.
+const EXTERNAL_ANIMAL_SELECT: &str = "SELECT e.external_animal_id, e.name, e.fk_Species_species_id, s.common_name_pt, e.institution, e.studbook_number, e.date_of_birth FROM External_Animal e LEFT JOIN Species s ON s.species_id = e.fk_Species_species_id";
+
+const DEFAULT_PEDIGREE_DEPTH: i32 = 3;
+const MAX_PEDIGREE_DEPTH: i32 = 10;
+
+fn external_animal_from_row(row: &Row) -> ExternalAnimal {
+    ExternalAnimal {
+        external_animal_id: row.get::<i32, _>(0).unwrap_or(0),
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        species_id: row.get::<i32, _>(2).unwrap_or(0),
+        specie: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        institution: row.get::<&str, _>(4).map(|s| s.to_string()),
+        studbook_number: row.get::<&str, _>(5).map(|s| s.to_string()),
+        date_of_birth: row.get(6),
+    }
+}
+
+async fn find_external_animal(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<ExternalAnimal>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE e.external_animal_id = @P1",
+        EXTERNAL_ANIMAL_SELECT
+    );
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(rows.first().map(external_animal_from_row))
+}
+
+/// Check an external animal and return its parsed date of birth.
+fn validate_external_animal(
+    payload: &SaveExternalAnimal,
+) -> Result<Option<NaiveDate>, (StatusCode, String)> {
+    if payload.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Name is required and cannot be empty".to_string(),
+        ));
+    }
+    if payload.species_id.is_none()
+        && payload
+            .specie
+            .as_deref()
+            .is_none_or(|s| s.trim().is_empty())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Specie or species_id is required".to_string(),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length(
+        "specie",
+        payload.specie.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "institution",
+        payload.institution.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "studbook_number",
+        payload.studbook_number.as_deref().unwrap_or(""),
+        50,
+    )?;
+
+    parse_date("date_of_birth", payload.date_of_birth.as_deref())
+}
+
+/// An animal of the pedigree: one of ours or an external one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Node {
+    Animal(i32),
+    External(i32),
+}
+
+impl Node {
+    fn studbook_id(self) -> String {
+        match self {
+            Node::Animal(id) => format!("A{}", id),
+            Node::External(id) => format!("E{}", id),
+        }
+    }
+}
+
+/// Every animal with its parents, loaded at once: pedigrees are small and
+/// the computations below walk them back and forth.
+struct Pedigree {
+    animals: HashMap<Node, PedigreeAnimal>,
+    children: HashMap<Node, Vec<Node>>,
+    /// Generations between an animal and its oldest known ancestor; always
+    /// more for an animal than for any of its ancestors
+    depths: HashMap<Node, i32>,
+}
+
+impl Pedigree {
+    fn new(animals: Vec<PedigreeAnimal>) -> Self {
+        let animals: HashMap<Node, PedigreeAnimal> = animals
+            .into_iter()
+            .map(|animal| (node_of(&animal), animal))
+            .collect();
+
+        let mut pedigree = Pedigree {
+            animals,
+            children: HashMap::new(),
+            depths: HashMap::new(),
+        };
+
+        let nodes: Vec<Node> = pedigree.animals.keys().copied().collect();
+        for &node in &nodes {
+            for (_, parent) in pedigree.parents(node) {
+                if let Some(parent) = parent {
+                    pedigree.children.entry(parent).or_default().push(node);
+                }
+            }
+        }
+        for children in pedigree.children.values_mut() {
+            children.sort();
+        }
+        for &node in &nodes {
+            pedigree.depth(node, &mut HashSet::new());
+        }
+
+        pedigree
+    }
+
+    /// Known sire and dam of an animal.
+    fn parents(&self, node: Node) -> [(&'static str, Option<Node>); 2] {
+        let Some(animal) = self.animals.get(&node) else {
+            return [("sire", None), ("dam", None)];
+        };
+        let sire = animal
+            .sire_id
+            .map(Node::Animal)
+            .or(animal.sire_external_id.map(Node::External));
+        let dam = animal
+            .dam_id
+            .map(Node::Animal)
+            .or(animal.dam_external_id.map(Node::External));
+        [
+            ("sire", sire.filter(|n| self.animals.contains_key(n))),
+            ("dam", dam.filter(|n| self.animals.contains_key(n))),
+        ]
+    }
+
+    fn depth(&mut self, node: Node, visiting: &mut HashSet<Node>) -> i32 {
+        if let Some(&depth) = self.depths.get(&node) {
+            return depth;
+        }
+        // Only a pedigree edited outside the API can loop; stop there
+        if !visiting.insert(node) {
+            return 0;
+        }
+        let mut depth = 0;
+        for (_, parent) in self.parents(node) {
+            if let Some(parent) = parent {
+                depth = depth.max(self.depth(parent, visiting) + 1);
+            }
+        }
+        visiting.remove(&node);
+        self.depths.insert(node, depth);
+        depth
+    }
+
+    /// Ancestors up to `depth` generations back, once for every line they
+    /// are reached through.
+    fn ancestors(&self, node: Node, depth: i32) -> Vec<Relative> {
+        let mut relatives = Vec::new();
+        let mut frontier = vec![(node, String::new())];
+
+        for generation in 1..=depth {
+            let mut next = Vec::new();
+            for (child, relation) in frontier {
+                for (role, parent) in self.parents(child) {
+                    let Some(parent) = parent else { continue };
+                    let relation = if relation.is_empty() {
+                        role.to_string()
+                    } else {
+                        format!("{}'s {}", relation, role)
+                    };
+                    relatives.push(Relative {
+                        generation,
+                        relation: relation.clone(),
+                        animal: self.animals[&parent].clone(),
+                    });
+                    next.push((parent, relation));
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        relatives
+    }
+
+    /// Descendants up to `depth` generations down, each at the closest
+    /// generation it is reached at.
+    fn descendants(&self, node: Node, depth: i32) -> Vec<Relative> {
+        let mut relatives = Vec::new();
+        let mut seen = HashSet::from([node]);
+        let mut frontier = vec![node];
+
+        for generation in 1..=depth {
+            let mut next = Vec::new();
+            for parent in frontier {
+                for &child in self.children.get(&parent).into_iter().flatten() {
+                    if seen.insert(child) {
+                        relatives.push(Relative {
+                            generation,
+                            relation: descendant_relation(generation),
+                            animal: self.animals[&child].clone(),
+                        });
+                        next.push(child);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        relatives
+    }
+
+    /// The animal and all of its known ancestors.
+    fn lineage(&self, node: Node) -> HashSet<Node> {
+        let mut lineage = HashSet::from([node]);
+        let mut pending = vec![node];
+        while let Some(current) = pending.pop() {
+            for (_, parent) in self.parents(current) {
+                if let Some(parent) = parent
+                    && lineage.insert(parent)
+                {
+                    pending.push(parent);
+                }
+            }
+        }
+        lineage
+    }
+
+    /// Coefficient of kinship: the chance that an allele drawn from each
+    /// animal is identical by descent. Animals with unknown parents are
+    /// taken as unrelated founders.
+    fn kinship(&self, a: Node, b: Node, memo: &mut HashMap<(Node, Node), f64>) -> f64 {
+        let key = if a <= b { (a, b) } else { (b, a) };
+        if let Some(&kinship) = memo.get(&key) {
+            return kinship;
+        }
+        // Only a pedigree edited outside the API can loop; a pair met again
+        // on the way up counts as unrelated
+        memo.insert(key, 0.0);
+
+        let kinship = if a == b {
+            let inbreeding = match self.parents(a) {
+                [(_, Some(sire)), (_, Some(dam))] => self.kinship(sire, dam, memo),
+                _ => 0.0,
+            };
+            (1.0 + inbreeding) / 2.0
+        } else {
+            // Go up from the younger of the two, which can't be an ancestor
+            // of the other
+            let depth = |node: Node| self.depths.get(&node).copied().unwrap_or(0);
+            let (younger, other) = if depth(a) >= depth(b) { (a, b) } else { (b, a) };
+            self.parents(younger)
+                .into_iter()
+                .map(|(_, parent)| parent.map_or(0.0, |parent| self.kinship(parent, other, memo)))
+                .sum::<f64>()
+                / 2.0
+        };
+
+        memo.insert(key, kinship);
+        kinship
+    }
+}
+
+fn node_of(animal: &PedigreeAnimal) -> Node {
+    match animal.animal_id {
+        Some(id) => Node::Animal(id),
+        None => Node::External(animal.external_animal_id.unwrap_or(0)),
+    }
+}
+
+fn descendant_relation(generation: i32) -> String {
+    match generation {
+        1 => "offspring".to_string(),
+        n => format!("{}grand-offspring", "great-".repeat((n - 2) as usize)),
+    }
+}
+
+async fn load_pedigree(client: &mut DbClient) -> Result<Pedigree, (StatusCode, String)> {
+    let mut animals = Vec::new();
+
+    let query = "SELECT a.animal_id, a.name, a.fk_Species_species_id, s.common_name_pt, a.date_of_birth, a.status, a.fk_Sire_Animal_id, a.fk_Sire_External_Animal_id, a.fk_Dam_Animal_id, a.fk_Dam_External_Animal_id FROM Animal a LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id";
+
+    let stream = client.query(query, &[]).await.map_err(|e| {
+        error!(error = %e, "Pedigree query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Pedigree query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    animals.extend(rows.iter().map(|row| PedigreeAnimal {
+        animal_id: row.get::<i32, _>(0),
+        external_animal_id: None,
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        species_id: row.get::<i32, _>(2),
+        specie: row.get::<&str, _>(3).map(|s| s.to_string()),
+        date_of_birth: row.get(4),
+        status: row.get::<&str, _>(5).map(|s| s.to_string()),
+        institution: None,
+        studbook_number: None,
+        sire_id: row.get::<i32, _>(6),
+        sire_external_id: row.get::<i32, _>(7),
+        dam_id: row.get::<i32, _>(8),
+        dam_external_id: row.get::<i32, _>(9),
+    }));
+
+    let stream = client
+        .query(EXTERNAL_ANIMAL_SELECT, &[])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Pedigree query error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Pedigree query error: {}", e),
+            )
+        })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    animals.extend(rows.iter().map(|row| {
+        let external = external_animal_from_row(row);
+        PedigreeAnimal {
+            animal_id: None,
+            external_animal_id: Some(external.external_animal_id),
+            name: external.name,
+            species_id: Some(external.species_id),
+            specie: Some(external.specie),
+            date_of_birth: external.date_of_birth,
+            status: None,
+            institution: external.institution,
+            studbook_number: external.studbook_number,
+            sire_id: None,
+            sire_external_id: None,
+            dam_id: None,
+            dam_external_id: None,
+        }
+    }));
+
+    Ok(Pedigree::new(animals))
+}
+
+fn parse_depth(depth: Option<i32>) -> Result<i32, (StatusCode, String)> {
+    match depth.unwrap_or(DEFAULT_PEDIGREE_DEPTH) {
+        depth @ 1..=MAX_PEDIGREE_DEPTH => Ok(depth),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            format!("depth must be between 1 and {}", MAX_PEDIGREE_DEPTH),
+        )),
+    }
+}
+
+/// One of our animals in the pedigree, or 404. Records created by mistake
+/// are left out.
+fn pedigree_animal(pedigree: &Pedigree, id: i32) -> Result<Node, (StatusCode, String)> {
+    let node = Node::Animal(id);
+    match pedigree.animals.get(&node) {
+        Some(animal) if animal.status.as_deref() != Some("removed") => Ok(node),
+        _ => Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        )),
+    }
+}
+
+/// Check a parent given for `child`: it must exist, be of the same species,
+/// be older, not descend from the child, and not already be recorded as a
+/// parent of the other sex.
+fn check_parent(
+    pedigree: &Pedigree,
+    child: Node,
+    role: &str,
+    parent: Option<Node>,
+) -> Result<(), (StatusCode, String)> {
+    let Some(parent) = parent else {
+        return Ok(());
+    };
+    let parent_animal = match pedigree.animals.get(&parent) {
+        Some(animal) if animal.status.as_deref() != Some("removed") => animal,
+        _ => {
+            let kind = match parent {
+                Node::Animal(_) => "Animal",
+                Node::External(_) => "External animal",
+            };
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{} {} of the {} not found",
+                    kind,
+                    parent.studbook_id(),
+                    role
+                ),
+            ));
+        }
+    };
+    let child_animal = &pedigree.animals[&child];
+
+    if parent == child || pedigree.lineage(parent).contains(&child) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "{} can't be the {} of {}: it would be its own ancestor",
+                parent.studbook_id(),
+                role,
+                child.studbook_id()
+            ),
+        ));
+    }
+    if let (Some(parent_species), Some(child_species)) =
+        (parent_animal.species_id, child_animal.species_id)
+        && parent_species != child_species
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "The {} {} is of another species than {}",
+                role,
+                parent.studbook_id(),
+                child.studbook_id()
+            ),
+        ));
+    }
+    if let (Some(parent_born), Some(child_born)) =
+        (parent_animal.date_of_birth, child_animal.date_of_birth)
+        && parent_born >= child_born
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "The {} {} was born on {}, not before {} ({})",
+                role,
+                parent.studbook_id(),
+                parent_born,
+                child.studbook_id(),
+                child_born
+            ),
+        ));
+    }
+
+    let other_role = if role == "sire" { "dam" } else { "sire" };
+    let used_as_other = pedigree
+        .children
+        .get(&parent)
+        .into_iter()
+        .flatten()
+        .find(|&&offspring| {
+            offspring != child
+                && pedigree
+                    .parents(offspring)
+                    .iter()
+                    .any(|&(r, p)| r == other_role && p == Some(parent))
+        });
+    if let Some(offspring) = used_as_other {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "{} is already the {} of {}, so it can't be a {}",
+                parent.studbook_id(),
+                other_role,
+                offspring.studbook_id(),
+                role
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/external-animals",
+    tag = "genealogy",
+    summary = "List external animals",
+    responses(
+        (status = 200, description = "Parents from other institutions", body = Vec<ExternalAnimal>),
+    )
+)]
+pub async fn get_external_animals(
+    State(db): State<Database>,
+) -> Result<Json<Vec<ExternalAnimal>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!("{} ORDER BY e.external_animal_id", EXTERNAL_ANIMAL_SELECT);
+
+    let stream = client.query(query, &[]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(Json(rows.iter().map(external_animal_from_row).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/external-animals/{id}",
+    tag = "genealogy",
+    summary = "Get external animal by id",
+    params(
+        ("id" = i32, Path, description = "External animal id"),
+    ),
+    responses(
+        (status = 200, description = "The external animal", body = ExternalAnimal),
+        (status = 404, description = "External animal not found", body = String),
+    )
+)]
+pub async fn get_external_animal_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<ExternalAnimal>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_external_animal(&mut client, id)
+        .await?
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("External animal with id {} not found", id),
+        ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/external-animals",
+    tag = "genealogy",
+    summary = "Add external animal",
+    request_body = SaveExternalAnimal,
+    responses(
+        (status = 201, description = "External animal created", body = ExternalAnimal),
+        (status = 400, description = "Invalid external animal", body = String),
+    )
+)]
+pub async fn add_external_animal(
+    State(db): State<Database>,
+    Json(payload): Json<SaveExternalAnimal>,
+) -> Result<(StatusCode, Json<ExternalAnimal>), (StatusCode, String)> {
+    let date_of_birth = validate_external_animal(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let (species_id, _) =
+        resolve_species(&mut client, payload.species_id, payload.specie.as_deref())
+            .await?
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "Specie or species_id is required".to_string(),
+            ))?;
+
+    let id_query = "SELECT ISNULL(MAX(external_animal_id),0)+1 AS next_id FROM External_Animal";
+    let id_stream = client.query(id_query, &[]).await.map_err(|e| {
+        error!(error = %e, "ID query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID query error: {}", e),
+        )
+    })?;
+    let id_rows = id_stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "ID result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID result error: {}", e),
+        )
+    })?;
+    let new_id = id_rows.first().and_then(|r| r.get::<i32, _>(0)).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to compute next id".to_string(),
+    ))?;
+
+    let insert_query = r#"
+        INSERT INTO External_Animal (external_animal_id, name, fk_Species_species_id, institution, studbook_number, date_of_birth)
+        VALUES (@P1, @P2, @P3, @P4, @P5, @P6)
+    "#;
+
+    client
+        .execute(
+            insert_query,
+            &[
+                &new_id,
+                &payload.name.trim(),
+                &species_id,
+                &payload.institution,
+                &payload.studbook_number,
+                &date_of_birth,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let created = find_external_animal(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created external animal".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/external-animals/{id}",
+    tag = "genealogy",
+    summary = "Update external animal",
+    params(
+        ("id" = i32, Path, description = "External animal id"),
+    ),
+    request_body = SaveExternalAnimal,
+    responses(
+        (status = 200, description = "Updated external animal", body = ExternalAnimal),
+        (status = 400, description = "Invalid external animal", body = String),
+        (status = 404, description = "External animal not found", body = String),
+    )
+)]
+pub async fn update_external_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveExternalAnimal>,
+) -> Result<Json<ExternalAnimal>, (StatusCode, String)> {
+    let date_of_birth = validate_external_animal(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let (species_id, _) =
+        resolve_species(&mut client, payload.species_id, payload.specie.as_deref())
+            .await?
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "Specie or species_id is required".to_string(),
+            ))?;
+
+    let update_query = r#"
+        UPDATE External_Animal
+        SET name = @P2,
+            fk_Species_species_id = @P3,
+            institution = @P4,
+            studbook_number = @P5,
+            date_of_birth = @P6
+        WHERE external_animal_id = @P1
+    "#;
+
+    let rows_affected = client
+        .execute(
+            update_query,
+            &[
+                &id,
+                &payload.name.trim(),
+                &species_id,
+                &payload.institution,
+                &payload.studbook_number,
+                &date_of_birth,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("External animal with id {} not found", id),
+        ));
+    }
+
+    let updated = find_external_animal(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated external animal".to_string(),
+    ))?;
+
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/external-animals/{id}",
+    tag = "genealogy",
+    summary = "Delete external animal",
+    params(
+        ("id" = i32, Path, description = "External animal id"),
+    ),
+    responses(
+        (status = 204, description = "External animal deleted"),
+        (status = 404, description = "External animal not found", body = String),
+        (status = 409, description = "It is still the parent of an animal", body = String),
+    )
+)]
+pub async fn delete_external_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // Animals no longer in the collection count too: their pedigree is kept
+    let usage_query = "SELECT COUNT(*) FROM Animal WHERE fk_Sire_External_Animal_id = @P1 OR fk_Dam_External_Animal_id = @P1";
+    let stream = client.query(usage_query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+    let offspring = rows.first().and_then(|r| r.get::<i32, _>(0)).unwrap_or(0);
+    if offspring > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "External animal with id {} is still the parent of {} animal(s)",
+                id, offspring
+            ),
+        ));
+    }
+
+    let query = "DELETE FROM External_Animal WHERE external_animal_id = @P1";
+
+    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Delete error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Delete error: {}", e),
+        )
+    })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("External animal with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/animals/{id}/parents",
+    tag = "genealogy",
+    summary = "Set the sire and dam of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = SetParents,
+    responses(
+        (status = 200, description = "The animal with its parents", body = Animal),
+        (status = 400, description = "Unknown parent, or one that can't be a parent of the animal", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "The parent is already recorded as a parent of the other sex", body = String),
+    )
+)]
+pub async fn set_animal_parents(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SetParents>,
+) -> Result<Json<Animal>, (StatusCode, String)> {
+    if payload.sire_id.is_some() && payload.sire_external_id.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Give either sire_id or sire_external_id, not both".to_string(),
+        ));
+    }
+    if payload.dam_id.is_some() && payload.dam_external_id.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Give either dam_id or dam_external_id, not both".to_string(),
+        ));
+    }
+
+    let sire = payload
+        .sire_id
+        .map(Node::Animal)
+        .or(payload.sire_external_id.map(Node::External));
+    let dam = payload
+        .dam_id
+        .map(Node::Animal)
+        .or(payload.dam_external_id.map(Node::External));
+    if sire.is_some() && sire == dam {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Sire and dam must be different animals".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let pedigree = load_pedigree(&mut client).await?;
+    let child = pedigree_animal(&pedigree, id)?;
+    check_parent(&pedigree, child, "sire", sire)?;
+    check_parent(&pedigree, child, "dam", dam)?;
+
+    let update_query = r#"
+        UPDATE Animal
+        SET fk_Sire_Animal_id = @P2,
+            fk_Sire_External_Animal_id = @P3,
+            fk_Dam_Animal_id = @P4,
+            fk_Dam_External_Animal_id = @P5
+        WHERE animal_id = @P1 AND status <> 'removed'
+    "#;
+
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &payload.sire_id,
+                    &payload.sire_external_id,
+                    &payload.dam_id,
+                    &payload.dam_external_id,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Update error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Update error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found", id),
+            ));
+        }
+
+        record_animal_revision(client, id, "parents_changed", None).await
+    })
+    .await?;
+
+    let animal = find_animal(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated animal".to_string(),
+    ))?;
+    events.publish(EntityKind::Animal, ChangeAction::Updated, id, Some(&animal));
+
+    Ok(Json(animal))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/ancestors",
+    tag = "genealogy",
+    summary = "List the ancestors of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        PedigreeQuery,
+    ),
+    responses(
+        (status = 200, description = "Ancestors, generation by generation; one reached through several lines is listed for each", body = Vec<Relative>),
+        (status = 400, description = "Invalid depth", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_animal_ancestors(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<PedigreeQuery>,
+) -> Result<Json<Vec<Relative>>, (StatusCode, String)> {
+    let depth = parse_depth(params.depth)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let pedigree = load_pedigree(&mut client).await?;
+    let node = pedigree_animal(&pedigree, id)?;
+
+    Ok(Json(pedigree.ancestors(node, depth)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/descendants",
+    tag = "genealogy",
+    summary = "List the descendants of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        PedigreeQuery,
+    ),
+    responses(
+        (status = 200, description = "Descendants, generation by generation", body = Vec<Relative>),
+        (status = 400, description = "Invalid depth", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_animal_descendants(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<PedigreeQuery>,
+) -> Result<Json<Vec<Relative>>, (StatusCode, String)> {
+    let depth = parse_depth(params.depth)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let pedigree = load_pedigree(&mut client).await?;
+    let node = pedigree_animal(&pedigree, id)?;
+
+    Ok(Json(pedigree.descendants(node, depth)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/inbreeding",
+    tag = "genealogy",
+    summary = "Inbreeding coefficient of the offspring of two animals",
+    description = "Computed from the whole recorded pedigree. Animals with unknown parents are taken as unrelated, so an incomplete pedigree underestimates the coefficient.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        InbreedingQuery,
+    ),
+    responses(
+        (status = 200, description = "Coefficient and the ancestors the pair shares", body = InbreedingResult),
+        (status = 400, description = "An animal can't be paired with itself", body = String),
+        (status = 404, description = "Animal or mate not found", body = String),
+    )
+)]
+pub async fn get_inbreeding_coefficient(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<InbreedingQuery>,
+) -> Result<Json<InbreedingResult>, (StatusCode, String)> {
+    if params.mate_id == id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "An animal can't be paired with itself".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let pedigree = load_pedigree(&mut client).await?;
+    let animal = pedigree_animal(&pedigree, id)?;
+    let mate = pedigree_animal(&pedigree, params.mate_id)?;
+
+    let coefficient = pedigree.kinship(animal, mate, &mut HashMap::new());
+
+    let mate_lineage = pedigree.lineage(mate);
+    let mut common: Vec<Node> = pedigree
+        .lineage(animal)
+        .into_iter()
+        .filter(|node| mate_lineage.contains(node))
+        .collect();
+    common.sort();
+
+    Ok(Json(InbreedingResult {
+        animal: pedigree.animals[&animal].clone(),
+        mate: pedigree.animals[&mate].clone(),
+        coefficient,
+        common_ancestors: common
+            .into_iter()
+            .map(|node| pedigree.animals[&node].clone())
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/species/{id}/studbook",
+    tag = "genealogy",
+    summary = "Export the studbook of a species",
+    description = "Every animal of the species, ours and external, with its sire and dam. Founders come first and every animal comes after its parents.",
+    params(
+        ("id" = i32, Path, description = "Species id"),
+        StudbookQuery,
+    ),
+    responses(
+        (status = 200, description = "Studbook as JSON, or as CSV with `format=csv`", body = Vec<StudbookEntry>),
+        (status = 400, description = "Unknown format", body = String),
+        (status = 404, description = "Species not found", body = String),
+    )
+)]
+pub async fn get_species_studbook(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<StudbookQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let csv = match params.format.as_deref().map(str::trim) {
+        None | Some("") | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unknown format '{}'; expected json or csv", other),
+            ));
+        }
+    };
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_species(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Species with id {} not found", id),
+        ));
+    }
+
+    let pedigree = load_pedigree(&mut client).await?;
+
+    let mut nodes: Vec<Node> = pedigree
+        .animals
+        .iter()
+        .filter(|(_, animal)| {
+            animal.species_id == Some(id) && animal.status.as_deref() != Some("removed")
+        })
+        .map(|(&node, _)| node)
+        .collect();
+    nodes.sort_by_key(|node| {
+        let animal = &pedigree.animals[node];
+        (
+            pedigree.depths.get(node).copied().unwrap_or(0),
+            animal.date_of_birth.is_none(),
+            animal.date_of_birth,
+            *node,
+        )
+    });
+
+    let entries: Vec<StudbookEntry> = nodes
+        .into_iter()
+        .map(|node| {
+            let animal = &pedigree.animals[&node];
+            let [(_, sire), (_, dam)] = pedigree.parents(node);
+            StudbookEntry {
+                studbook_id: node.studbook_id(),
+                name: animal.name.clone(),
+                date_of_birth: animal.date_of_birth,
+                sire: sire.map(Node::studbook_id),
+                dam: dam.map(Node::studbook_id),
+                status: animal.status.clone(),
+                institution: animal.institution.clone(),
+                studbook_number: animal.studbook_number.clone(),
+            }
+        })
+        .collect();
+
+    if !csv {
+        return Ok(Json(entries).into_response());
+    }
+
+    let mut body = String::from(
+        "studbook_id,name,date_of_birth,sire,dam,status,institution,studbook_number\n",
+    );
+    for entry in &entries {
+        let fields = [
+            entry.studbook_id.clone(),
+            entry.name.clone(),
+            entry
+                .date_of_birth
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            entry.sire.clone().unwrap_or_default(),
+            entry.dam.clone().unwrap_or_default(),
+            entry.status.clone().unwrap_or_default(),
+            entry.institution.clone().unwrap_or_default(),
+            entry.studbook_number.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        body.push_str(&line.join(","));
+        body.push('\n');
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"studbook-species-{}.csv\"", id),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One of our animals, with its sire and dam among our animals.
+    fn animal(id: i32, sire: Option<i32>, dam: Option<i32>) -> PedigreeAnimal {
+        PedigreeAnimal {
+            animal_id: Some(id),
+            external_animal_id: None,
+            name: format!("Animal {}", id),
+            species_id: Some(1),
+            specie: None,
+            date_of_birth: None,
+            status: Some("active".to_string()),
+            institution: None,
+            studbook_number: None,
+            sire_id: sire,
+            sire_external_id: None,
+            dam_id: dam,
+            dam_external_id: None,
+        }
+    }
+
+    fn kinship(pedigree: &Pedigree, a: i32, b: i32) -> f64 {
+        pedigree.kinship(Node::Animal(a), Node::Animal(b), &mut HashMap::new())
+    }
+
+    /// Founders 1 (sire) and 2 (dam) have 3 and 4; 5 is a founder dam who
+    /// has 6 by 1; 7 is unrelated.
+    fn family() -> Pedigree {
+        Pedigree::new(vec![
+            animal(1, None, None),
+            animal(2, None, None),
+            animal(3, Some(1), Some(2)),
+            animal(4, Some(1), Some(2)),
+            animal(5, None, None),
+            animal(6, Some(1), Some(5)),
+            animal(7, None, None),
+        ])
+    }
+
+    #[test]
+    fn full_siblings_have_a_kinship_of_a_quarter() {
+        assert_eq!(kinship(&family(), 3, 4), 0.25);
+    }
+
+    #[test]
+    fn half_siblings_have_a_kinship_of_an_eighth() {
+        assert_eq!(kinship(&family(), 3, 6), 0.125);
+    }
+
+    #[test]
+    fn unrelated_animals_have_no_kinship() {
+        let pedigree = family();
+        assert_eq!(kinship(&pedigree, 3, 7), 0.0);
+        assert_eq!(kinship(&pedigree, 1, 2), 0.0);
+    }
+
+    #[test]
+    fn parent_and_offspring_have_a_kinship_of_a_quarter() {
+        let pedigree = family();
+        assert_eq!(kinship(&pedigree, 1, 3), 0.25);
+        assert_eq!(kinship(&pedigree, 3, 1), 0.25);
+    }
+
+    #[test]
+    fn inbreeding_raises_the_kinship_with_oneself() {
+        let pedigree = Pedigree::new(vec![
+            animal(1, None, None),
+            animal(2, None, None),
+            animal(3, Some(1), Some(2)),
+            animal(4, Some(1), Some(2)),
+            // Offspring of full siblings is inbred by a quarter
+            animal(8, Some(3), Some(4)),
+            animal(9, None, None),
+        ]);
+
+        assert_eq!(kinship(&pedigree, 9, 9), 0.5);
+        assert_eq!(kinship(&pedigree, 8, 8), (1.0 + 0.25) / 2.0);
+    }
+
+    #[test]
+    fn a_looped_pedigree_still_terminates() {
+        // 1 and 2 are each other's sire, as only a direct edit could make
+        let pedigree = Pedigree::new(vec![
+            animal(1, Some(2), None),
+            animal(2, Some(1), None),
+            animal(3, None, None),
+        ]);
+        assert_eq!(kinship(&pedigree, 1, 3), 0.0);
+        assert!(kinship(&pedigree, 1, 2) > 0.0);
+        assert_eq!(pedigree.lineage(Node::Animal(1)).len(), 2);
+    }
+
+    #[test]
+    fn an_animal_cant_be_its_own_parent() {
+        let pedigree = family();
+        let (status, message) =
+            check_parent(&pedigree, Node::Animal(3), "sire", Some(Node::Animal(3))).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("its own ancestor"), "{}", message);
+    }
+
+    #[test]
+    fn a_descendant_cant_become_a_parent() {
+        let pedigree = family();
+        for descendant in [3, 6] {
+            let (status, message) = check_parent(
+                &pedigree,
+                Node::Animal(1),
+                "sire",
+                Some(Node::Animal(descendant)),
+            )
+            .unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(message.contains("its own ancestor"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn a_sire_cant_also_be_a_dam() {
+        let pedigree = family();
+        let (status, _) =
+            check_parent(&pedigree, Node::Animal(7), "dam", Some(Node::Animal(1))).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(check_parent(&pedigree, Node::Animal(7), "sire", Some(Node::Animal(1))).is_ok());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::db::Database;
+use crate::models::{ComponentHealth, HealthReport, HealthStatus};
+use axum::{Json, extract::State, http::StatusCode};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// How long readiness waits for the database before reporting it down
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+async fn check_database(db: &Database) -> ComponentHealth {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(DATABASE_CHECK_TIMEOUT, db.test_connection()).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!(
+            "no answer within {}s",
+            DATABASE_CHECK_TIMEOUT.as_secs()
+        )),
+    };
+
+    ComponentHealth {
+        status: if error.is_none() {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "operations",
+    summary = "Liveness: the process is serving requests",
+    responses(
+        (status = 200, description = "Alive", body = Object),
+    )
+)]
+pub async fn health_live() -> Json<Value> {
+    Json(json!({ "status": "up" }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "operations",
+    summary = "Readiness: dependencies are reachable",
+    responses(
+        (status = 200, description = "Ready to take traffic", body = HealthReport),
+        (status = 503, description = "A dependency is down", body = HealthReport),
+    )
+)]
+pub async fn health_ready(State(db): State<Database>) -> (StatusCode, Json<HealthReport>) {
+    let mut components = BTreeMap::new();
+    components.insert("database".to_string(), check_database(&db).await);
+
+    let status = if components.values().all(|c| c.status == HealthStatus::Up) {
+        HealthStatus::Up
+    } else {
+        HealthStatus::Down
+    };
+    let code = match status {
+        HealthStatus::Up => StatusCode::OK,
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (code, Json(HealthReport { status, components }))
+}
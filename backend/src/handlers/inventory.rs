@@ -0,0 +1,1193 @@
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::handlers::feeding::find_food_item;
+use crate::models::{
+    ExpiringLot, ExpiringQuery, INVENTORY_CATEGORIES, INVENTORY_UNITS, InventoryItem,
+    InventoryItemQuery, InventoryLot, MANUAL_MOVEMENT_TYPES, NewStockMovement, ReceiveLot,
+    SaveInventoryItem, StockMovement,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{Duration, Utc};
+use tiberius::Row;
+use tracing::error;
+
+/// Items with their usable and expired stock; callers add the `WHERE`, then
+/// `ITEM_GROUP_BY`, then any `HAVING`/`ORDER BY`.
+const ITEM_SELECT: &str = "SELECT i.inventory_item_id, i.name, i.category, i.unit, i.reorder_level, i.fk_Food_Item_id, i.drug_name, i.notes, ISNULL(SUM(CASE WHEN l.expires_on IS NULL OR l.expires_on >= CAST(SYSUTCDATETIME() AS DATE) THEN l.quantity_on_hand END), 0), ISNULL(SUM(CASE WHEN l.expires_on < CAST(SYSUTCDATETIME() AS DATE) THEN l.quantity_on_hand END), 0) FROM Inventory_Item i LEFT JOIN Inventory_Lot l ON l.fk_Inventory_Item_id = i.inventory_item_id";
+
+const ITEM_GROUP_BY: &str = "GROUP BY i.inventory_item_id, i.name, i.category, i.unit, i.reorder_level, i.fk_Food_Item_id, i.drug_name, i.notes";
+
+const LOT_COLUMNS: &str = "inventory_lot_id, fk_Inventory_Item_id, lot_number, expires_on, received_on, quantity_received, quantity_on_hand, supplier, notes";
+
+const MOVEMENT_COLUMNS: &str = "stock_movement_id, fk_Inventory_Item_id, fk_Inventory_Lot_id, movement_type, quantity, moved_at, source, source_id, reason, recorded_by";
+
+const LOT_NUMBER_LENGTH: usize = 100;
+const DEFAULT_EXPIRING_DAYS: i64 = 30;
+const MAX_EXPIRING_DAYS: i64 = 365;
+/// Stock left below this is taken as used up, to absorb float rounding
+const EPSILON: f64 = 1e-9;
+
+fn item_from_row(row: &Row) -> InventoryItem {
+    InventoryItem {
+        inventory_item_id: row.get::<i32, _>(0).unwrap_or(0),
+        name: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        category: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        unit: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        reorder_level: row.get::<f64, _>(4).unwrap_or(0.0),
+        food_item_id: row.get::<i32, _>(5),
+        drug_name: row.get::<&str, _>(6).map(|s| s.to_string()),
+        notes: row.get::<&str, _>(7).map(|s| s.to_string()),
+        quantity_on_hand: row.get::<f64, _>(8).unwrap_or(0.0),
+        quantity_expired: row.get::<f64, _>(9).unwrap_or(0.0),
+    }
+}
+
+fn lot_from_row(row: &Row) -> InventoryLot {
+    InventoryLot {
+        inventory_lot_id: row.get::<i32, _>(0).unwrap_or(0),
+        inventory_item_id: row.get::<i32, _>(1).unwrap_or(0),
+        lot_number: row.get::<&str, _>(2).map(|s| s.to_string()),
+        expires_on: row.get(3),
+        received_on: row.get(4),
+        quantity_received: row.get::<f64, _>(5).unwrap_or(0.0),
+        quantity_on_hand: row.get::<f64, _>(6).unwrap_or(0.0),
+        supplier: row.get::<&str, _>(7).map(|s| s.to_string()),
+        notes: row.get::<&str, _>(8).map(|s| s.to_string()),
+    }
+}
+
+fn movement_from_row(row: &Row) -> StockMovement {
+    StockMovement {
+        stock_movement_id: row.get::<i32, _>(0).unwrap_or(0),
+        inventory_item_id: row.get::<i32, _>(1).unwrap_or(0),
+        inventory_lot_id: row.get::<i32, _>(2),
+        movement_type: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        quantity: row.get::<f64, _>(4).unwrap_or(0.0),
+        moved_at: row.get(5),
+        source: row.get::<&str, _>(6).map(|s| s.to_string()),
+        source_id: row.get::<i32, _>(7),
+        reason: row.get::<&str, _>(8).map(|s| s.to_string()),
+        recorded_by: row.get::<&str, _>(9).map(|s| s.to_string()),
+    }
+}
+
+/// How many of the smallest unit of its dimension one of `unit` is, with the
+/// dimension. `None` for units stock isn't counted in.
+fn unit_scale(unit: &str) -> Option<(&'static str, f64)> {
+    match unit.trim().to_lowercase().as_str() {
+        "kg" => Some(("mass", 1_000_000.0)),
+        "g" => Some(("mass", 1_000.0)),
+        "mg" => Some(("mass", 1.0)),
+        "l" => Some(("volume", 1_000.0)),
+        "ml" => Some(("volume", 1.0)),
+        "unit" => Some(("count", 1.0)),
+        _ => None,
+    }
+}
+
+/// `quantity` in `from` expressed in `to`, when they measure the same thing.
+pub fn convert_quantity(quantity: f64, from: &str, to: &str) -> Option<f64> {
+    let (from_dimension, from_scale) = unit_scale(from)?;
+    let (to_dimension, to_scale) = unit_scale(to)?;
+    (from_dimension == to_dimension).then(|| quantity * from_scale / to_scale)
+}
+
+async fn find_item(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<InventoryItem>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE i.inventory_item_id = @P1 {}",
+        ITEM_SELECT, ITEM_GROUP_BY
+    );
+    let rows = fetch_rows(client, &query, &[&id]).await?;
+    Ok(rows.first().map(item_from_row))
+}
+
+async fn find_lots(
+    client: &mut DbClient,
+    item_id: i32,
+) -> Result<Vec<InventoryLot>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Inventory_Lot WHERE fk_Inventory_Item_id = @P1 ORDER BY CASE WHEN expires_on IS NULL THEN 1 ELSE 0 END, expires_on, received_on, inventory_lot_id",
+        LOT_COLUMNS
+    );
+    let rows = fetch_rows(client, &query, &[&item_id]).await?;
+    Ok(rows.iter().map(lot_from_row).collect())
+}
+
+/// A stock movement about to be written.
+struct MovementEntry<'a> {
+    inventory_item_id: i32,
+    inventory_lot_id: i32,
+    movement_type: &'a str,
+    quantity: f64,
+    source: Option<&'a str>,
+    source_id: Option<i32>,
+    reason: Option<&'a str>,
+    recorded_by: Option<&'a str>,
+}
+
+/// Change the stock of a lot and log the movement. Returns the movement id,
+/// or `None` when the lot doesn't hold enough for it.
+async fn move_stock(
+    client: &mut DbClient,
+    entry: MovementEntry<'_>,
+) -> Result<Option<i32>, (StatusCode, String)> {
+    let rows_affected = client
+        .execute(
+            "UPDATE Inventory_Lot SET quantity_on_hand = quantity_on_hand + @P2 WHERE inventory_lot_id = @P1 AND quantity_on_hand + @P2 >= 0",
+            &[&entry.inventory_lot_id, &entry.quantity],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Lot update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Lot update error: {}", e),
+            )
+        })?;
+    if rows_affected.total() == 0 {
+        return Ok(None);
+    }
+
+    let new_id = next_id(client, "Stock_Movement", "stock_movement_id").await?;
+    client
+        .execute(
+            "INSERT INTO Stock_Movement (stock_movement_id, fk_Inventory_Item_id, fk_Inventory_Lot_id, movement_type, quantity, moved_at, source, source_id, reason, recorded_by) VALUES (@P1, @P2, @P3, @P4, @P5, SYSUTCDATETIME(), @P6, @P7, @P8, @P9)",
+            &[
+                &new_id,
+                &entry.inventory_item_id,
+                &entry.inventory_lot_id,
+                &entry.movement_type,
+                &entry.quantity,
+                &entry.source,
+                &entry.source_id,
+                &entry.reason,
+                &entry.recorded_by,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Stock movement insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Stock movement insert error: {}", e),
+            )
+        })?;
+
+    Ok(Some(new_id))
+}
+
+/// Take `quantity` of an item out of its unexpired lots, first expiring
+/// first. Returns how much couldn't be taken for lack of stock.
+async fn consume(
+    client: &mut DbClient,
+    item: &InventoryItem,
+    quantity: f64,
+    source: &str,
+    source_id: i32,
+    recorded_by: Option<&str>,
+) -> Result<f64, (StatusCode, String)> {
+    let today = Utc::now().date_naive();
+    let lots: Vec<InventoryLot> = find_lots(client, item.inventory_item_id)
+        .await?
+        .into_iter()
+        .filter(|l| l.quantity_on_hand > EPSILON && l.expires_on.is_none_or(|e| e >= today))
+        .collect();
+
+    let mut remaining = quantity;
+    for lot in lots {
+        if remaining <= EPSILON {
+            break;
+        }
+        let taken = remaining.min(lot.quantity_on_hand);
+        let moved = move_stock(
+            client,
+            MovementEntry {
+                inventory_item_id: item.inventory_item_id,
+                inventory_lot_id: lot.inventory_lot_id,
+                movement_type: "consumed",
+                quantity: -taken,
+                source: Some(source),
+                source_id: Some(source_id),
+                reason: None,
+                recorded_by,
+            },
+        )
+        .await?;
+        if moved.is_some() {
+            remaining -= taken;
+        }
+    }
+
+    Ok(remaining.max(0.0))
+}
+
+fn shortfall_warning(item: &InventoryItem, shortfall: f64) -> Option<String> {
+    (shortfall > EPSILON).then(|| {
+        format!(
+            "Stock of {} ran out: {} {} more than was on hand was used",
+            item.name, shortfall, item.unit
+        )
+    })
+}
+
+/// Take what a feeding record gave out of the stock of its food item, if
+/// one is kept. Returns warnings about the stock.
+pub async fn consume_for_feeding(
+    client: &mut DbClient,
+    food_item_id: i32,
+    food_unit: &str,
+    quantity: f64,
+    feeding_record_id: i32,
+    fed_by: Option<&str>,
+) -> Result<Vec<String>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE i.fk_Food_Item_id = @P1 {}",
+        ITEM_SELECT, ITEM_GROUP_BY
+    );
+    let rows = fetch_rows(client, &query, &[&food_item_id]).await?;
+    let Some(item) = rows.first().map(item_from_row) else {
+        return Ok(Vec::new());
+    };
+    // Linked items are checked to convert when either side is saved
+    let Some(quantity) = convert_quantity(quantity, food_unit, &item.unit) else {
+        return Ok(vec![format!(
+            "Stock of {} not updated: feeds in {} don't convert to {}",
+            item.name, food_unit, item.unit
+        )]);
+    };
+
+    let shortfall = consume(
+        client,
+        &item,
+        quantity,
+        "feeding_record",
+        feeding_record_id,
+        fed_by,
+    )
+    .await?;
+    Ok(shortfall_warning(&item, shortfall).into_iter().collect())
+}
+
+/// Take a dose out of the stock of its drug, if one is kept. Returns
+/// warnings about the stock.
+pub async fn consume_for_dose(
+    client: &mut DbClient,
+    drug: &str,
+    dose_unit: &str,
+    amount: f64,
+    treatment_dose_id: i32,
+    administered_by: &str,
+) -> Result<Vec<String>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE i.category = 'medication' AND LOWER(i.drug_name) = LOWER(@P1) {}",
+        ITEM_SELECT, ITEM_GROUP_BY
+    );
+    let rows = fetch_rows(client, &query, &[&drug.trim()]).await?;
+    let Some(item) = rows.first().map(item_from_row) else {
+        return Ok(Vec::new());
+    };
+    let Some(quantity) = convert_quantity(amount, dose_unit, &item.unit) else {
+        return Ok(vec![format!(
+            "Stock of {} not updated: doses in {} don't convert to {}",
+            item.name, dose_unit, item.unit
+        )]);
+    };
+
+    let shortfall = consume(
+        client,
+        &item,
+        quantity,
+        "treatment_dose",
+        treatment_dose_id,
+        Some(administered_by),
+    )
+    .await?;
+    Ok(shortfall_warning(&item, shortfall).into_iter().collect())
+}
+
+/// Put back what was taken for a record that is being deleted.
+pub async fn return_stock(
+    client: &mut DbClient,
+    source: &str,
+    source_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Stock_Movement WHERE source = @P1 AND source_id = @P2 AND movement_type = 'consumed'",
+        MOVEMENT_COLUMNS
+    );
+    let rows = fetch_rows(client, &query, &[&source, &source_id]).await?;
+    let reason = format!("{} {} deleted", source.replace('_', " "), source_id);
+
+    for movement in rows.iter().map(movement_from_row) {
+        let Some(lot_id) = movement.inventory_lot_id else {
+            continue;
+        };
+        move_stock(
+            client,
+            MovementEntry {
+                inventory_item_id: movement.inventory_item_id,
+                inventory_lot_id: lot_id,
+                movement_type: "returned",
+                quantity: -movement.quantity,
+                source: Some(source),
+                source_id: Some(source_id),
+                reason: Some(&reason),
+                recorded_by: None,
+            },
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+fn validate_item(payload: &SaveInventoryItem) -> Result<(), (StatusCode, String)> {
+    if payload.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "name is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("name", &payload.name, VARCHAR_LENGTH)?;
+    check_length(
+        "drug_name",
+        payload.drug_name.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    if !INVENTORY_CATEGORIES.contains(&payload.category.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown category '{}'; expected one of {}",
+                payload.category,
+                INVENTORY_CATEGORIES.join(", ")
+            ),
+        ));
+    }
+    if !INVENTORY_UNITS.contains(&payload.unit.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown unit '{}'; expected one of {}",
+                payload.unit,
+                INVENTORY_UNITS.join(", ")
+            ),
+        ));
+    }
+    if !(payload.reorder_level.is_finite() && payload.reorder_level >= 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "reorder_level can't be negative".to_string(),
+        ));
+    }
+    if payload.food_item_id.is_some() && payload.category != "food" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Only food items can have a food_item_id".to_string(),
+        ));
+    }
+    if payload.drug_name.is_some() && payload.category != "medication" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Only medication can have a drug_name".to_string(),
+        ));
+    }
+    if payload
+        .drug_name
+        .as_deref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "drug_name cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the name, food item and drug of an item aren't another item's,
+/// and that the food item exists in a unit the item converts to.
+async fn check_item_references(
+    client: &mut DbClient,
+    payload: &SaveInventoryItem,
+    inventory_item_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    if let Some(food_item_id) = payload.food_item_id {
+        let food = find_food_item(client, food_item_id).await?.ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown food_item_id {}", food_item_id),
+        ))?;
+        if convert_quantity(1.0, &food.unit, &payload.unit).is_none() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Food item {} is fed in {}, which doesn't convert to {}",
+                    food.name, food.unit, payload.unit
+                ),
+            ));
+        }
+    }
+
+    let query = "SELECT TOP 1 inventory_item_id, CASE WHEN name = @P1 THEN 'name' WHEN fk_Food_Item_id = @P2 THEN 'food item' ELSE 'drug' END FROM Inventory_Item WHERE inventory_item_id <> @P4 AND (name = @P1 OR fk_Food_Item_id = @P2 OR LOWER(drug_name) = LOWER(@P3))";
+    let rows = fetch_rows(
+        client,
+        query,
+        &[
+            &payload.name.trim(),
+            &payload.food_item_id,
+            &payload.drug_name.as_deref().map(str::trim),
+            &inventory_item_id.unwrap_or(0),
+        ],
+    )
+    .await?;
+    if let Some(row) = rows.first() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Inventory item {} already has this {}",
+                row.get::<i32, _>(0).unwrap_or(0),
+                row.get::<&str, _>(1).unwrap_or("name")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory-items",
+    tag = "inventory",
+    summary = "List inventory items with their stock",
+    params(InventoryItemQuery),
+    responses(
+        (status = 200, description = "Items, by name", body = Vec<InventoryItem>),
+        (status = 400, description = "Unknown category", body = String),
+    )
+)]
+pub async fn get_inventory_items(
+    State(db): State<Database>,
+    Query(params): Query<InventoryItemQuery>,
+) -> Result<Json<Vec<InventoryItem>>, (StatusCode, String)> {
+    if let Some(category) = &params.category
+        && !INVENTORY_CATEGORIES.contains(&category.as_str())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown category '{}'; expected one of {}",
+                category,
+                INVENTORY_CATEGORIES.join(", ")
+            ),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "{} WHERE @P1 IS NULL OR i.category = @P1 {} ORDER BY i.name",
+        ITEM_SELECT, ITEM_GROUP_BY
+    );
+    let rows = fetch_rows(&mut client, &query, &[&params.category]).await?;
+
+    Ok(Json(rows.iter().map(item_from_row).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory-items/{id}",
+    tag = "inventory",
+    summary = "Get inventory item by id",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    responses(
+        (status = 200, description = "Inventory item found", body = InventoryItem),
+        (status = 404, description = "Inventory item not found", body = String),
+    )
+)]
+pub async fn get_inventory_item_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<InventoryItem>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_item(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Inventory item with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/inventory-items",
+    tag = "inventory",
+    summary = "Add an inventory item",
+    description = "Link food to its `food_item_id` and medication to its `drug_name` to have feeding and dose records take from the stock.",
+    request_body = SaveInventoryItem,
+    responses(
+        (status = 201, description = "Inventory item created", body = InventoryItem),
+        (status = 400, description = "Invalid inventory item", body = String),
+        (status = 409, description = "Name, food item or drug already used by another item", body = String),
+    )
+)]
+pub async fn add_inventory_item(
+    State(db): State<Database>,
+    Json(payload): Json<SaveInventoryItem>,
+) -> Result<(StatusCode, Json<InventoryItem>), (StatusCode, String)> {
+    validate_item(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_item_references(&mut client, &payload, None).await?;
+    let new_id = next_id(&mut client, "Inventory_Item", "inventory_item_id").await?;
+
+    client
+        .execute(
+            "INSERT INTO Inventory_Item (inventory_item_id, name, category, unit, reorder_level, fk_Food_Item_id, drug_name, notes) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8)",
+            &[
+                &new_id,
+                &payload.name.trim(),
+                &payload.category.as_str(),
+                &payload.unit.as_str(),
+                &payload.reorder_level,
+                &payload.food_item_id,
+                &payload.drug_name.as_deref().map(str::trim),
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let created = find_item(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created inventory item".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/inventory-items/{id}",
+    tag = "inventory",
+    summary = "Update an inventory item",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    request_body = SaveInventoryItem,
+    responses(
+        (status = 200, description = "Inventory item updated", body = InventoryItem),
+        (status = 400, description = "Invalid inventory item", body = String),
+        (status = 404, description = "Inventory item not found", body = String),
+        (status = 409, description = "Name, food item or drug already used, or the unit changed while lots exist", body = String),
+    )
+)]
+pub async fn update_inventory_item(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveInventoryItem>,
+) -> Result<Json<InventoryItem>, (StatusCode, String)> {
+    validate_item(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let existing = find_item(&mut client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Inventory item with id {} not found", id),
+    ))?;
+    if existing.unit != payload.unit && !find_lots(&mut client, id).await?.is_empty() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Inventory item with id {} already has lots counted in {}",
+                id, existing.unit
+            ),
+        ));
+    }
+    check_item_references(&mut client, &payload, Some(id)).await?;
+
+    client
+        .execute(
+            "UPDATE Inventory_Item SET name = @P2, category = @P3, unit = @P4, reorder_level = @P5, fk_Food_Item_id = @P6, drug_name = @P7, notes = @P8 WHERE inventory_item_id = @P1",
+            &[
+                &id,
+                &payload.name.trim(),
+                &payload.category.as_str(),
+                &payload.unit.as_str(),
+                &payload.reorder_level,
+                &payload.food_item_id,
+                &payload.drug_name.as_deref().map(str::trim),
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    let updated = find_item(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated inventory item".to_string(),
+    ))?;
+
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/inventory-items/{id}",
+    tag = "inventory",
+    summary = "Delete an inventory item that never had stock",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    responses(
+        (status = 204, description = "Inventory item deleted"),
+        (status = 404, description = "Inventory item not found", body = String),
+        (status = 409, description = "The item has lots", body = String),
+    )
+)]
+pub async fn delete_inventory_item(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let lots = find_lots(&mut client, id).await?;
+    if !lots.is_empty() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Inventory item with id {} has {} lot(s) and its history is kept",
+                id,
+                lots.len()
+            ),
+        ));
+    }
+
+    let rows_affected = client
+        .execute(
+            "DELETE FROM Inventory_Item WHERE inventory_item_id = @P1",
+            &[&id],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Inventory item with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory-items/{id}/lots",
+    tag = "inventory",
+    summary = "Lots of an item, first expiring first",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    responses(
+        (status = 200, description = "Every lot received, used up or not", body = Vec<InventoryLot>),
+        (status = 404, description = "Inventory item not found", body = String),
+    )
+)]
+pub async fn get_inventory_lots(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<InventoryLot>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_item(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Inventory item with id {} not found", id),
+        ));
+    }
+
+    Ok(Json(find_lots(&mut client, id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/inventory-items/{id}/lots",
+    tag = "inventory",
+    summary = "Receive a lot of an item",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    request_body = ReceiveLot,
+    responses(
+        (status = 201, description = "Lot received and added to the stock", body = InventoryLot),
+        (status = 400, description = "Invalid lot", body = String),
+        (status = 404, description = "Inventory item not found", body = String),
+    )
+)]
+pub async fn receive_inventory_lot(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ReceiveLot>,
+) -> Result<(StatusCode, Json<InventoryLot>), (StatusCode, String)> {
+    check_length(
+        "lot_number",
+        payload.lot_number.as_deref().unwrap_or(""),
+        LOT_NUMBER_LENGTH,
+    )?;
+    for (field, value) in [
+        ("supplier", &payload.supplier),
+        ("notes", &payload.notes),
+        ("recorded_by", &payload.recorded_by),
+    ] {
+        check_length(field, value.as_deref().unwrap_or(""), VARCHAR_LENGTH)?;
+    }
+    if !(payload.quantity.is_finite() && payload.quantity > 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "quantity must be positive".to_string(),
+        ));
+    }
+    let expires_on = parse_date("expires_on", payload.expires_on.as_deref())?;
+    let received_on = parse_date("received_on", payload.received_on.as_deref())?
+        .unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_item(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Inventory item with id {} not found", id),
+        ));
+    }
+
+    let new_id = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Inventory_Lot", "inventory_lot_id").await?;
+        // Starts empty; the `received` movement puts the quantity in
+        client
+            .execute(
+                "INSERT INTO Inventory_Lot (inventory_lot_id, fk_Inventory_Item_id, lot_number, expires_on, received_on, quantity_received, quantity_on_hand, supplier, notes) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, 0, @P7, @P8)",
+                &[
+                    &new_id,
+                    &id,
+                    &payload.lot_number,
+                    &expires_on,
+                    &received_on,
+                    &payload.quantity,
+                    &payload.supplier,
+                    &payload.notes,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        move_stock(
+            client,
+            MovementEntry {
+                inventory_item_id: id,
+                inventory_lot_id: new_id,
+                movement_type: "received",
+                quantity: payload.quantity,
+                source: None,
+                source_id: None,
+                reason: None,
+                recorded_by: payload.recorded_by.as_deref(),
+            },
+        )
+        .await?;
+
+        Ok(new_id)
+    })
+    .await?;
+
+    let created = find_lots(&mut client, id)
+        .await?
+        .into_iter()
+        .find(|l| l.inventory_lot_id == new_id)
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to fetch received lot".to_string(),
+        ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory-items/{id}/movements",
+    tag = "inventory",
+    summary = "Stock movements of an item, most recent first",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    responses(
+        (status = 200, description = "Every movement of the item's stock", body = Vec<StockMovement>),
+        (status = 404, description = "Inventory item not found", body = String),
+    )
+)]
+pub async fn get_stock_movements(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<StockMovement>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_item(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Inventory item with id {} not found", id),
+        ));
+    }
+
+    let query = format!(
+        "SELECT {} FROM Stock_Movement WHERE fk_Inventory_Item_id = @P1 ORDER BY moved_at DESC, stock_movement_id DESC",
+        MOVEMENT_COLUMNS
+    );
+    let rows = fetch_rows(&mut client, &query, &[&id]).await?;
+
+    Ok(Json(rows.iter().map(movement_from_row).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/inventory-items/{id}/movements",
+    tag = "inventory",
+    summary = "Record a stock movement by hand",
+    description = "For stock used outside feeding and dose records, thrown away, or corrected after a count.",
+    params(
+        ("id" = i32, Path, description = "Inventory item id"),
+    ),
+    request_body = NewStockMovement,
+    responses(
+        (status = 201, description = "Movement recorded", body = StockMovement),
+        (status = 400, description = "Invalid movement, or the lot isn't of this item", body = String),
+        (status = 404, description = "Inventory item not found", body = String),
+        (status = 409, description = "The lot doesn't hold that much", body = String),
+    )
+)]
+pub async fn add_stock_movement(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<NewStockMovement>,
+) -> Result<(StatusCode, Json<StockMovement>), (StatusCode, String)> {
+    if !MANUAL_MOVEMENT_TYPES.contains(&payload.movement_type.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown movement_type '{}'; expected one of {}",
+                payload.movement_type,
+                MANUAL_MOVEMENT_TYPES.join(", ")
+            ),
+        ));
+    }
+    check_length(
+        "reason",
+        payload.reason.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "recorded_by",
+        payload.recorded_by.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    let quantity = match payload.movement_type.as_str() {
+        "adjusted" if payload.quantity.is_finite() && payload.quantity != 0.0 => payload.quantity,
+        "adjusted" => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "quantity of an adjustment can't be 0".to_string(),
+            ));
+        }
+        _ if payload.quantity.is_finite() && payload.quantity > 0.0 => -payload.quantity,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "quantity must be positive".to_string(),
+            ));
+        }
+    };
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_item(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Inventory item with id {} not found", id),
+        ));
+    }
+    if !find_lots(&mut client, id)
+        .await?
+        .iter()
+        .any(|l| l.inventory_lot_id == payload.inventory_lot_id)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Lot {} is not of inventory item {}",
+                payload.inventory_lot_id, id
+            ),
+        ));
+    }
+
+    let movement_id = move_stock(
+        &mut client,
+        MovementEntry {
+            inventory_item_id: id,
+            inventory_lot_id: payload.inventory_lot_id,
+            movement_type: &payload.movement_type,
+            quantity,
+            source: None,
+            source_id: None,
+            reason: payload.reason.as_deref(),
+            recorded_by: payload.recorded_by.as_deref(),
+        },
+    )
+    .await?
+    .ok_or((
+        StatusCode::CONFLICT,
+        format!(
+            "Lot {} doesn't hold {}",
+            payload.inventory_lot_id, -quantity
+        ),
+    ))?;
+
+    let query = format!(
+        "SELECT {} FROM Stock_Movement WHERE stock_movement_id = @P1",
+        MOVEMENT_COLUMNS
+    );
+    let rows = fetch_rows(&mut client, &query, &[&movement_id]).await?;
+    let created = rows.first().map(movement_from_row).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch recorded movement".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory/low-stock",
+    tag = "inventory",
+    summary = "Items at or below their reorder level",
+    description = "Only stock in lots that haven't expired counts.",
+    responses(
+        (status = 200, description = "Items to order, emptiest first", body = Vec<InventoryItem>),
+    )
+)]
+pub async fn get_low_stock(
+    State(db): State<Database>,
+) -> Result<Json<Vec<InventoryItem>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!("{} {}", ITEM_SELECT, ITEM_GROUP_BY);
+    let rows = fetch_rows(&mut client, &query, &[]).await?;
+    let mut items: Vec<InventoryItem> = rows
+        .iter()
+        .map(item_from_row)
+        .filter(|i| i.quantity_on_hand <= i.reorder_level)
+        .collect();
+    // Emptiest relative to what should be kept first
+    items.sort_by(|a, b| {
+        let fill = |i: &InventoryItem| {
+            if i.reorder_level > 0.0 {
+                i.quantity_on_hand / i.reorder_level
+            } else {
+                i.quantity_on_hand
+            }
+        };
+        fill(a).total_cmp(&fill(b))
+    });
+
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    get,
+    path = "/inventory/expiring",
+    tag = "inventory",
+    summary = "Lots with stock left that expire soon or have expired",
+    params(ExpiringQuery),
+    responses(
+        (status = 200, description = "Lots, first expiring first", body = Vec<ExpiringLot>),
+        (status = 400, description = "Invalid within_days", body = String),
+    )
+)]
+pub async fn get_expiring_lots(
+    State(db): State<Database>,
+    Query(params): Query<ExpiringQuery>,
+) -> Result<Json<Vec<ExpiringLot>>, (StatusCode, String)> {
+    let within_days = params.within_days.unwrap_or(DEFAULT_EXPIRING_DAYS);
+    if !(0..=MAX_EXPIRING_DAYS).contains(&within_days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("within_days must be between 0 and {}", MAX_EXPIRING_DAYS),
+        ));
+    }
+    let today = Utc::now().date_naive();
+    let until = today + Duration::days(within_days);
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let columns: Vec<String> = LOT_COLUMNS
+        .split(", ")
+        .map(|c| format!("l.{}", c))
+        .collect();
+    let query = format!(
+        "SELECT {}, i.name, i.category, i.unit FROM Inventory_Lot l JOIN Inventory_Item i ON i.inventory_item_id = l.fk_Inventory_Item_id WHERE l.quantity_on_hand > 0 AND l.expires_on <= @P1 ORDER BY l.expires_on, i.name",
+        columns.join(", ")
+    );
+    let rows = fetch_rows(&mut client, &query, &[&until]).await?;
+
+    Ok(Json(
+        rows.iter()
+            .map(|row| {
+                let lot = lot_from_row(row);
+                ExpiringLot {
+                    item_name: row.get::<&str, _>(9).unwrap_or("").to_string(),
+                    category: row.get::<&str, _>(10).unwrap_or("").to_string(),
+                    unit: row.get::<&str, _>(11).unwrap_or("").to_string(),
+                    days_left: lot
+                        .expires_on
+                        .map(|e| (e - today).num_days())
+                        .unwrap_or_default(),
+                    lot,
+                }
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_quantity_scales_within_a_dimension() {
+        assert_eq!(convert_quantity(2.5, "kg", "g"), Some(2500.0));
+        assert_eq!(convert_quantity(500.0, "mg", "g"), Some(0.5));
+        assert_eq!(convert_quantity(1.5, "l", "ml"), Some(1500.0));
+        assert_eq!(convert_quantity(3.0, "unit", "unit"), Some(3.0));
+    }
+
+    #[test]
+    fn convert_quantity_ignores_case_and_spaces() {
+        assert_eq!(convert_quantity(1.0, " KG ", "g"), Some(1000.0));
+        assert_eq!(convert_quantity(250.0, "ML", "L"), Some(0.25));
+    }
+
+    #[test]
+    fn convert_quantity_refuses_mismatched_dimensions() {
+        assert_eq!(convert_quantity(1.0, "kg", "l"), None);
+        assert_eq!(convert_quantity(1.0, "ml", "mg"), None);
+        assert_eq!(convert_quantity(1.0, "unit", "g"), None);
+    }
+
+    #[test]
+    fn convert_quantity_refuses_unknown_units() {
+        assert_eq!(convert_quantity(1.0, "tablet", "unit"), None);
+        assert_eq!(convert_quantity(1.0, "g", "lb"), None);
+        assert_eq!(convert_quantity(1.0, "", "g"), None);
+    }
+}
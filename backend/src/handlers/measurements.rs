@@ -0,0 +1,669 @@
+use crate::config::Config;
+use crate::db::{Database, DbClient};
+use crate::handlers::animals::find_animal;
+use crate::models::{
+    BODY_CONDITION_SCORE_RANGE, MAX_TREND_WINDOW_DAYS, MEASUREMENT_KINDS, Measurement,
+    MeasurementQuery, MeasurementTrend, NewMeasurement, TrendQuery, in_collection_sql,
+    measurement_unit,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date, parse_timestamp};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
+
+const MEASUREMENT_COLUMNS: &str = "measurement_id, fk_Animal_animal_id, kind, value, unit, measured_at, measured_by, notes, recorded_at";
+
+/// Heaviest and longest values accepted, well above any animal kept
+const MAX_WEIGHT_KG: f64 = 20_000.0;
+const MAX_LENGTH_CM: f64 = 5_000.0;
+
+fn measurement_from_row(row: &Row) -> Measurement {
+    Measurement {
+        measurement_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        kind: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        value: row.get::<f64, _>(3).unwrap_or(0.0),
+        unit: row.get::<&str, _>(4).unwrap_or("").to_string(),
+        measured_at: row.get(5),
+        measured_by: row.get::<&str, _>(6).map(|s| s.to_string()),
+        notes: row.get::<&str, _>(7).map(|s| s.to_string()),
+        recorded_at: row.get(8),
+    }
+}
+
+fn unknown_kind(kind: &str) -> (StatusCode, String) {
+    let kinds: Vec<&str> = MEASUREMENT_KINDS.iter().map(|(k, _)| *k).collect();
+    (
+        StatusCode::BAD_REQUEST,
+        format!(
+            "Unknown kind '{}'; expected one of {}",
+            kind,
+            kinds.join(", ")
+        ),
+    )
+}
+
+fn validate_value(kind: &str, value: f64) -> Result<(), (StatusCode, String)> {
+    let valid = value.is_finite()
+        && match kind {
+            "weight" => value > 0.0 && value <= MAX_WEIGHT_KG,
+            "length" => value > 0.0 && value <= MAX_LENGTH_CM,
+            _ => BODY_CONDITION_SCORE_RANGE.contains(&value),
+        };
+    if valid {
+        return Ok(());
+    }
+    let expected = match kind {
+        "weight" => format!("positive and at most {} kg", MAX_WEIGHT_KG),
+        "length" => format!("positive and at most {} cm", MAX_LENGTH_CM),
+        _ => format!(
+            "between {} and {}",
+            BODY_CONDITION_SCORE_RANGE.start(),
+            BODY_CONDITION_SCORE_RANGE.end()
+        ),
+    };
+    Err((
+        StatusCode::BAD_REQUEST,
+        format!("A {} must be {}", kind, expected),
+    ))
+}
+
+/// The window and alert threshold asked for, or the configured ones.
+fn trend_settings(
+    params: &TrendQuery,
+    config: &Config,
+) -> Result<(i64, f64), (StatusCode, String)> {
+    let window_days = params
+        .window_days
+        .unwrap_or(config.measurements.weight_loss_window_days);
+    if !(1..=MAX_TREND_WINDOW_DAYS).contains(&window_days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "window_days must be between 1 and {}",
+                MAX_TREND_WINDOW_DAYS
+            ),
+        ));
+    }
+    let alert_percent = params
+        .alert_percent
+        .unwrap_or(config.measurements.weight_loss_alert_percent);
+    if !(alert_percent > 0.0 && alert_percent < 100.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "alert_percent must be between 0 and 100".to_string(),
+        ));
+    }
+    Ok((window_days, alert_percent))
+}
+
+/// Work out the trend of measurements of one kind of one animal, oldest
+/// first and all within the window.
+fn trend(
+    animal_name: String,
+    measurements: &[Measurement],
+    window_days: i64,
+    alert_percent: f64,
+) -> Option<MeasurementTrend> {
+    let first = measurements.first()?;
+    let latest = measurements.last()?;
+
+    let peak_value = measurements
+        .iter()
+        .map(|m| m.value)
+        .fold(f64::MIN, f64::max);
+    let drop_from_peak_percent = if peak_value > 0.0 {
+        ((peak_value - latest.value) / peak_value * 100.0).max(0.0)
+    } else {
+        0.0
+    };
+
+    let (change, change_percent) = if measurements.len() > 1 {
+        let change = latest.value - first.value;
+        let percent = (first.value != 0.0).then(|| change / first.value * 100.0);
+        (Some(change), percent)
+    } else {
+        (None, None)
+    };
+
+    // Least squares of value over days since the first measurement
+    let origin = first.measured_at.unwrap_or_default();
+    let points: Vec<(f64, f64)> = measurements
+        .iter()
+        .map(|m| {
+            let days = (m.measured_at.unwrap_or_default() - origin).num_seconds() as f64 / 86_400.0;
+            (days, m.value)
+        })
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let spread: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let change_per_week = (spread > 0.0).then(|| {
+        let covariance: f64 = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        covariance / spread * 7.0
+    });
+
+    Some(MeasurementTrend {
+        animal_id: latest.animal_id,
+        animal_name,
+        kind: latest.kind.clone(),
+        unit: latest.unit.clone(),
+        window_days,
+        alert_percent,
+        measurements: measurements.len(),
+        latest_value: latest.value,
+        latest_measured_at: latest.measured_at,
+        change,
+        change_percent,
+        change_per_week,
+        peak_value,
+        drop_from_peak_percent,
+        weight_loss_alert: latest.kind == "weight" && drop_from_peak_percent >= alert_percent,
+    })
+}
+
+/// Trends of every animal and kind matched by `filter`, over the window
+/// before each one's latest measurement.
+async fn query_trends(
+    client: &mut DbClient,
+    filter: &str,
+    params: &[&dyn tiberius::ToSql],
+    window_days: i64,
+    alert_percent: f64,
+) -> Result<Vec<MeasurementTrend>, (StatusCode, String)> {
+    let columns: Vec<String> = MEASUREMENT_COLUMNS
+        .split(", ")
+        .map(|c| format!("m.{}", c))
+        .collect();
+    // The window is inlined as it was checked to be a small number
+    let query = format!(
+        "SELECT {}, a.name FROM Measurement m JOIN Animal a ON a.animal_id = m.fk_Animal_animal_id WHERE {} AND m.measured_at >= DATEADD(day, -{}, (SELECT MAX(l.measured_at) FROM Measurement l WHERE l.fk_Animal_animal_id = m.fk_Animal_animal_id AND l.kind = m.kind)) ORDER BY m.fk_Animal_animal_id, m.kind, m.measured_at, m.measurement_id",
+        columns.join(", "),
+        filter,
+        window_days
+    );
+
+    let stream = client.query(query, params).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    let mut series: BTreeMap<(i32, String), (String, Vec<Measurement>)> = BTreeMap::new();
+    for row in &rows {
+        let measurement = measurement_from_row(row);
+        let name = row.get::<&str, _>(9).unwrap_or("").to_string();
+        series
+            .entry((measurement.animal_id, measurement.kind.clone()))
+            .or_insert_with(|| (name, Vec::new()))
+            .1
+            .push(measurement);
+    }
+
+    Ok(series
+        .into_values()
+        .filter_map(|(name, measurements)| trend(name, &measurements, window_days, alert_percent))
+        .collect())
+}
+
+async fn check_animal_kept(client: &mut DbClient, id: i32) -> Result<(), (StatusCode, String)> {
+    if find_animal(client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/measurements",
+    tag = "measurements",
+    summary = "Measurements of an animal, oldest first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        MeasurementQuery,
+    ),
+    responses(
+        (status = 200, description = "The measurements matching the filters", body = Vec<Measurement>),
+        (status = 400, description = "Unknown kind or invalid date", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_measurements_of_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Query(params): Query<MeasurementQuery>,
+) -> Result<Json<Vec<Measurement>>, (StatusCode, String)> {
+    if let Some(kind) = &params.kind
+        && measurement_unit(kind).is_none()
+    {
+        return Err(unknown_kind(kind));
+    }
+    let from = parse_date("from", params.from.as_deref())?;
+    let to = parse_date("to", params.to.as_deref())?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_animal_kept(&mut client, id).await?;
+
+    let query = format!(
+        "SELECT {} FROM Measurement WHERE fk_Animal_animal_id = @P1 AND (@P2 IS NULL OR kind = @P2) AND (@P3 IS NULL OR CAST(measured_at AS DATE) >= @P3) AND (@P4 IS NULL OR CAST(measured_at AS DATE) <= @P4) ORDER BY measured_at, measurement_id",
+        MEASUREMENT_COLUMNS
+    );
+
+    let stream = client
+        .query(query, &[&id, &params.kind, &from, &to])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Query error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Query error: {}", e),
+            )
+        })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(Json(rows.iter().map(measurement_from_row).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/measurements",
+    tag = "measurements",
+    summary = "Record a measurement of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = NewMeasurement,
+    responses(
+        (status = 201, description = "Measurement recorded", body = Measurement),
+        (status = 400, description = "Invalid measurement", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn add_measurement(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<NewMeasurement>,
+) -> Result<(StatusCode, Json<Measurement>), (StatusCode, String)> {
+    let kind = payload.kind.trim();
+    let unit = measurement_unit(kind).ok_or_else(|| unknown_kind(kind))?;
+    validate_value(kind, payload.value)?;
+    check_length(
+        "measured_by",
+        payload.measured_by.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    let now = Utc::now().naive_utc();
+    let measured_at: NaiveDateTime =
+        parse_timestamp("measured_at", payload.measured_at.as_deref())?.unwrap_or(now);
+    // A few minutes of leeway for clocks that run ahead
+    if measured_at > now + Duration::minutes(5) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "measured_at can't be in the future".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_animal_kept(&mut client, id).await?;
+
+    let id_query = "SELECT ISNULL(MAX(measurement_id),0)+1 AS next_id FROM Measurement";
+    let id_stream = client.query(id_query, &[]).await.map_err(|e| {
+        error!(error = %e, "ID query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID query error: {}", e),
+        )
+    })?;
+    let id_rows = id_stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "ID result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID result error: {}", e),
+        )
+    })?;
+    let new_id = id_rows.first().and_then(|r| r.get::<i32, _>(0)).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to compute next id".to_string(),
+    ))?;
+
+    let insert_query = "INSERT INTO Measurement (measurement_id, fk_Animal_animal_id, kind, value, unit, measured_at, measured_by, notes, recorded_at) VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, SYSUTCDATETIME())";
+
+    client
+        .execute(
+            insert_query,
+            &[
+                &new_id,
+                &id,
+                &kind,
+                &payload.value,
+                &unit,
+                &measured_at,
+                &payload.measured_by,
+                &payload.notes,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let query = format!(
+        "SELECT {} FROM Measurement WHERE measurement_id = @P1",
+        MEASUREMENT_COLUMNS
+    );
+    let stream = client.query(query, &[&new_id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+    let row = stream.into_row().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+    let created = row.as_ref().map(measurement_from_row).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created measurement".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/measurements/{id}",
+    tag = "measurements",
+    summary = "Delete a measurement taken by mistake",
+    params(
+        ("id" = i32, Path, description = "Measurement id"),
+    ),
+    responses(
+        (status = 204, description = "Measurement deleted"),
+        (status = 404, description = "Measurement not found", body = String),
+    )
+)]
+pub async fn delete_measurement(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let rows_affected = client
+        .execute("DELETE FROM Measurement WHERE measurement_id = @P1", &[&id])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Measurement with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/measurements/trends",
+    tag = "measurements",
+    summary = "Trend of each kind of measurement of an animal",
+    description = "Each trend covers the window before the latest measurement of its kind. Weights that dropped `alert_percent` or more from the highest weight in the window are flagged.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        TrendQuery,
+    ),
+    responses(
+        (status = 200, description = "One trend per kind measured", body = Vec<MeasurementTrend>),
+        (status = 400, description = "Invalid window_days or alert_percent", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_measurement_trends(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    Query(params): Query<TrendQuery>,
+) -> Result<Json<Vec<MeasurementTrend>>, (StatusCode, String)> {
+    let (window_days, alert_percent) = trend_settings(&params, &config)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_animal_kept(&mut client, id).await?;
+
+    let trends = query_trends(
+        &mut client,
+        "m.fk_Animal_animal_id = @P1",
+        &[&id],
+        window_days,
+        alert_percent,
+    )
+    .await?;
+
+    Ok(Json(trends))
+}
+
+#[utoipa::path(
+    get,
+    path = "/measurements/weight-alerts",
+    tag = "measurements",
+    summary = "Animals in the collection losing weight, biggest loss first",
+    params(TrendQuery),
+    responses(
+        (status = 200, description = "Weight trends with an alert", body = Vec<MeasurementTrend>),
+        (status = 400, description = "Invalid window_days or alert_percent", body = String),
+    )
+)]
+pub async fn get_weight_alerts(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Query(params): Query<TrendQuery>,
+) -> Result<Json<Vec<MeasurementTrend>>, (StatusCode, String)> {
+    let (window_days, alert_percent) = trend_settings(&params, &config)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let filter = format!("m.kind = 'weight' AND a.status IN {}", in_collection_sql());
+    let mut alerts: Vec<MeasurementTrend> =
+        query_trends(&mut client, &filter, &[], window_days, alert_percent)
+            .await?
+            .into_iter()
+            .filter(|t| t.weight_loss_alert)
+            .collect();
+    alerts.sort_by(|a, b| {
+        b.drop_from_peak_percent
+            .total_cmp(&a.drop_from_peak_percent)
+    });
+
+    Ok(Json(alerts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// A measurement of `kind` taken `day` days into the window.
+    fn measurement(kind: &str, day: i64, value: f64) -> Measurement {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        Measurement {
+            measurement_id: day as i32 + 1,
+            animal_id: 1,
+            kind: kind.to_string(),
+            value,
+            unit: measurement_unit(kind).unwrap_or_default().to_string(),
+            measured_at: Some(start + Duration::days(day)),
+            measured_by: None,
+            notes: None,
+            recorded_at: None,
+        }
+    }
+
+    fn weights(values: &[(i64, f64)]) -> Vec<Measurement> {
+        values
+            .iter()
+            .map(|&(day, value)| measurement("weight", day, value))
+            .collect()
+    }
+
+    fn trend_of(measurements: &[Measurement], alert_percent: f64) -> MeasurementTrend {
+        trend("Kiara".to_string(), measurements, 30, alert_percent).unwrap()
+    }
+
+    #[test]
+    fn no_measurements_have_no_trend() {
+        assert!(trend("Kiara".to_string(), &[], 30, 10.0).is_none());
+    }
+
+    #[test]
+    fn a_single_measurement_has_no_change() {
+        let trend = trend_of(&weights(&[(0, 120.0)]), 10.0);
+        assert_eq!(trend.measurements, 1);
+        assert_eq!(trend.latest_value, 120.0);
+        assert_eq!(trend.peak_value, 120.0);
+        assert_eq!(trend.change, None);
+        assert_eq!(trend.change_percent, None);
+        assert_eq!(trend.change_per_week, None);
+        assert_eq!(trend.drop_from_peak_percent, 0.0);
+        assert!(!trend.weight_loss_alert);
+    }
+
+    #[test]
+    fn measurements_at_the_same_time_have_no_slope() {
+        let trend = trend_of(&weights(&[(3, 100.0), (3, 110.0)]), 10.0);
+        assert_eq!(trend.change, Some(10.0));
+        assert_eq!(trend.change_percent, Some(10.0));
+        assert_eq!(trend.change_per_week, None);
+    }
+
+    #[test]
+    fn the_slope_is_the_least_squares_fit_per_week() {
+        // Over days 0, 1 and 2 the fit of 1, 3, 2 rises by half a unit a day
+        let trend = trend_of(&weights(&[(0, 1.0), (1, 3.0), (2, 2.0)]), 10.0);
+        assert_eq!(trend.change, Some(1.0));
+        assert_eq!(trend.change_percent, Some(100.0));
+        assert!((trend.change_per_week.unwrap() - 3.5).abs() < 1e-9);
+
+        let trend = trend_of(&weights(&[(0, 100.0), (7, 93.0), (14, 86.0)]), 10.0);
+        assert!((trend.change_per_week.unwrap() + 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_weight_dropping_by_the_threshold_from_the_peak_raises_an_alert() {
+        let measurements = weights(&[(0, 180.0), (10, 200.0), (20, 180.0)]);
+
+        let trend = trend_of(&measurements, 10.0);
+        assert_eq!(trend.peak_value, 200.0);
+        assert!((trend.drop_from_peak_percent - 10.0).abs() < 1e-9);
+        assert!(trend.weight_loss_alert);
+        // Back to the first value, so no overall change
+        assert_eq!(trend.change, Some(0.0));
+
+        assert!(!trend_of(&measurements, 10.5).weight_loss_alert);
+    }
+
+    #[test]
+    fn a_weight_gain_raises_no_alert() {
+        let trend = trend_of(&weights(&[(0, 100.0), (10, 150.0)]), 1.0);
+        assert_eq!(trend.drop_from_peak_percent, 0.0);
+        assert!(!trend.weight_loss_alert);
+    }
+
+    #[test]
+    fn only_weights_raise_an_alert() {
+        for kind in ["length", "body_condition_score"] {
+            let measurements = [measurement(kind, 0, 8.0), measurement(kind, 10, 4.0)];
+            let trend = trend_of(&measurements, 10.0);
+            assert_eq!(trend.drop_from_peak_percent, 50.0);
+            assert!(!trend.weight_loss_alert, "{}", kind);
+        }
+    }
+}
@@ -0,0 +1,782 @@
+use crate::auth::{MEDICAL_READ, MEDICAL_WRITE, require_scope};
+use crate::config::Config;
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::handlers::animals::find_animal;
+use crate::models::{
+    FollowUpQuery, MedicalAttachment, MedicalExamination, NewMedicalAttachment,
+    SaveMedicalExamination, in_collection_sql,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
+
+/// Examinations with the type of care that led to them; callers add the
+/// `WHERE`/`ORDER BY`.
+const EXAMINATION_SELECT: &str = "SELECT m.medical_examination_id, m.fk_Animal_animal_id, m.fk_Cares_cares_id, c.type_of_care, m.examined_on, m.veterinarian, m.reason, m.findings, m.treatment_notes, m.follow_up_on, m.recorded_by, m.recorded_at FROM Medical_Examination m LEFT JOIN Cares c ON c.cares_id = m.fk_Cares_cares_id";
+
+const ATTACHMENT_COLUMNS: &str = "medical_attachment_id, fk_Medical_Examination_id, file_name, content_type, size_bytes, storage_reference, uploaded_at";
+
+const MAX_DIAGNOSIS_CODES: usize = 20;
+const DIAGNOSIS_CODE_LENGTH: usize = 20;
+const CONTENT_TYPE_LENGTH: usize = 100;
+const STORAGE_REFERENCE_LENGTH: usize = 2048;
+
+fn examination_from_row(row: &Row) -> MedicalExamination {
+    MedicalExamination {
+        medical_examination_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        cares_id: row.get::<i32, _>(2),
+        type_of_care: row.get::<&str, _>(3).map(|s| s.to_string()),
+        examined_on: row.get(4),
+        veterinarian: row.get::<&str, _>(5).unwrap_or("").to_string(),
+        reason: row.get::<&str, _>(6).map(|s| s.to_string()),
+        findings: row.get::<&str, _>(7).map(|s| s.to_string()),
+        diagnosis_codes: Vec::new(),
+        treatment_notes: row.get::<&str, _>(8).map(|s| s.to_string()),
+        follow_up_on: row.get(9),
+        attachments: Vec::new(),
+        recorded_by: row.get::<&str, _>(10).map(|s| s.to_string()),
+        recorded_at: row.get(11),
+    }
+}
+
+fn attachment_from_row(row: &Row) -> MedicalAttachment {
+    MedicalAttachment {
+        medical_attachment_id: row.get::<i32, _>(0).unwrap_or(0),
+        medical_examination_id: row.get::<i32, _>(1).unwrap_or(0),
+        file_name: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        content_type: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        size_bytes: row.get::<i64, _>(4).unwrap_or(0),
+        storage_reference: row.get::<&str, _>(5).unwrap_or("").to_string(),
+        uploaded_at: row.get(6),
+    }
+}
+
+/// Run an examination query and fill in the diagnosis codes and attachments
+/// of what it returns.
+async fn query_examinations(
+    client: &mut DbClient,
+    query: &str,
+    params: &[&dyn tiberius::ToSql],
+) -> Result<Vec<MedicalExamination>, (StatusCode, String)> {
+    let rows = fetch_rows(client, query, params).await?;
+
+    let mut examinations: Vec<MedicalExamination> = rows.iter().map(examination_from_row).collect();
+    if examinations.is_empty() {
+        return Ok(examinations);
+    }
+
+    // Ids come from the database, so they can be inlined
+    let ids = examinations
+        .iter()
+        .map(|m| m.medical_examination_id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let diagnosis_query = format!(
+        "SELECT fk_Medical_Examination_id, diagnosis_code FROM Medical_Diagnosis WHERE fk_Medical_Examination_id IN ({}) ORDER BY fk_Medical_Examination_id, position",
+        ids
+    );
+    let rows = fetch_rows(client, &diagnosis_query, &[]).await?;
+    let mut codes: HashMap<i32, Vec<String>> = HashMap::new();
+    for row in &rows {
+        if let (Some(id), Some(code)) = (row.get::<i32, _>(0), row.get::<&str, _>(1)) {
+            codes.entry(id).or_default().push(code.to_string());
+        }
+    }
+
+    let attachment_query = format!(
+        "SELECT {} FROM Medical_Attachment WHERE fk_Medical_Examination_id IN ({}) ORDER BY medical_attachment_id",
+        ATTACHMENT_COLUMNS, ids
+    );
+    let rows = fetch_rows(client, &attachment_query, &[]).await?;
+    let mut attachments: HashMap<i32, Vec<MedicalAttachment>> = HashMap::new();
+    for attachment in rows.iter().map(attachment_from_row) {
+        attachments
+            .entry(attachment.medical_examination_id)
+            .or_default()
+            .push(attachment);
+    }
+
+    for examination in &mut examinations {
+        let id = examination.medical_examination_id;
+        examination.diagnosis_codes = codes.remove(&id).unwrap_or_default();
+        examination.attachments = attachments.remove(&id).unwrap_or_default();
+    }
+
+    Ok(examinations)
+}
+
+async fn find_examination(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<MedicalExamination>, (StatusCode, String)> {
+    let query = format!(
+        "{} WHERE m.medical_examination_id = @P1",
+        EXAMINATION_SELECT
+    );
+    Ok(query_examinations(client, &query, &[&id]).await?.pop())
+}
+
+/// An examination checked and ready to be written.
+struct ValidExamination {
+    examined_on: NaiveDate,
+    follow_up_on: Option<NaiveDate>,
+    diagnosis_codes: Vec<String>,
+}
+
+fn validate_examination(
+    payload: &SaveMedicalExamination,
+    max_text_length: usize,
+) -> Result<ValidExamination, (StatusCode, String)> {
+    if payload.veterinarian.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Veterinarian is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("veterinarian", &payload.veterinarian, VARCHAR_LENGTH)?;
+    check_length(
+        "reason",
+        payload.reason.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    check_length(
+        "findings",
+        payload.findings.as_deref().unwrap_or(""),
+        max_text_length,
+    )?;
+    check_length(
+        "treatment_notes",
+        payload.treatment_notes.as_deref().unwrap_or(""),
+        max_text_length,
+    )?;
+
+    let examined_on = parse_date("examined_on", payload.examined_on.as_deref())?
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let follow_up_on = parse_date("follow_up_on", payload.follow_up_on.as_deref())?;
+    if let Some(follow_up_on) = follow_up_on
+        && follow_up_on < examined_on
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "follow_up_on can't be before examined_on".to_string(),
+        ));
+    }
+
+    let mut diagnosis_codes: Vec<String> = Vec::new();
+    for code in payload.diagnosis_codes.iter().flatten() {
+        let code = code.trim().to_uppercase();
+        if code.is_empty()
+            || code.len() > DIAGNOSIS_CODE_LENGTH
+            || !code
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid diagnosis code '{}'; use up to {} letters, digits, '.' or '-'",
+                    code, DIAGNOSIS_CODE_LENGTH
+                ),
+            ));
+        }
+        if !diagnosis_codes.contains(&code) {
+            diagnosis_codes.push(code);
+        }
+    }
+    if diagnosis_codes.len() > MAX_DIAGNOSIS_CODES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "At most {} diagnosis codes per examination",
+                MAX_DIAGNOSIS_CODES
+            ),
+        ));
+    }
+
+    Ok(ValidExamination {
+        examined_on,
+        follow_up_on,
+        diagnosis_codes,
+    })
+}
+
+/// `type/subtype`, e.g. `application/pdf`; parameters aren't kept.
+fn is_content_type(value: &str) -> bool {
+    let token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    matches!(value.split_once('/'), Some((kind, subtype)) if token(kind) && token(subtype))
+}
+
+async fn check_care_exists(
+    client: &mut DbClient,
+    cares_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let Some(cares_id) = cares_id else {
+        return Ok(());
+    };
+
+    let query = "SELECT COUNT(*) FROM Cares WHERE cares_id = @P1";
+    let rows = fetch_rows(client, query, &[&cares_id]).await?;
+
+    if rows.first().and_then(|r| r.get::<i32, _>(0)).unwrap_or(0) == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown cares_id {}", cares_id),
+        ));
+    }
+    Ok(())
+}
+
+async fn replace_diagnoses(
+    client: &mut DbClient,
+    examination_id: i32,
+    codes: &[String],
+) -> Result<(), (StatusCode, String)> {
+    client
+        .execute(
+            "DELETE FROM Medical_Diagnosis WHERE fk_Medical_Examination_id = @P1",
+            &[&examination_id],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Diagnosis delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Diagnosis delete error: {}", e),
+            )
+        })?;
+
+    for (position, code) in codes.iter().enumerate() {
+        let position = position as i32;
+        client
+            .execute(
+                "INSERT INTO Medical_Diagnosis (fk_Medical_Examination_id, position, diagnosis_code) VALUES (@P1, @P2, @P3)",
+                &[&examination_id, &position, &code.as_str()],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Diagnosis insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Diagnosis insert error: {}", e),
+                )
+            })?;
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/medical-examinations",
+    tag = "medical",
+    summary = "List the medical examinations of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "Examinations, latest first", body = Vec<MedicalExamination>),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_medical_examinations_of_animal(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<MedicalExamination>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_animal(&mut client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+
+    let query = format!(
+        "{} WHERE m.fk_Animal_animal_id = @P1 ORDER BY m.examined_on DESC, m.medical_examination_id DESC",
+        EXAMINATION_SELECT
+    );
+
+    Ok(Json(query_examinations(&mut client, &query, &[&id]).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/medical-examinations",
+    tag = "medical",
+    summary = "Record a medical examination of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = SaveMedicalExamination,
+    responses(
+        (status = 201, description = "Examination recorded", body = MedicalExamination),
+        (status = 400, description = "Invalid examination", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn add_medical_examination(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<SaveMedicalExamination>,
+) -> Result<(StatusCode, Json<MedicalExamination>), (StatusCode, String)> {
+    let api_key = require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+    let valid = validate_examination(&payload, config.limits.max_description_length)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // Deceased animals still get examined, e.g. post mortem
+    if find_animal(&mut client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+    check_care_exists(&mut client, payload.cares_id).await?;
+
+    let new_id = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Medical_Examination", "medical_examination_id").await?;
+
+        let insert_query = r#"
+            INSERT INTO Medical_Examination (medical_examination_id, fk_Animal_animal_id, fk_Cares_cares_id, examined_on, veterinarian, reason, findings, treatment_notes, follow_up_on, recorded_by, recorded_at)
+            VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, @P9, @P10, SYSUTCDATETIME())
+        "#;
+
+        client
+            .execute(
+                insert_query,
+                &[
+                    &new_id,
+                    &id,
+                    &payload.cares_id,
+                    &valid.examined_on,
+                    &payload.veterinarian.trim(),
+                    &payload.reason,
+                    &payload.findings,
+                    &payload.treatment_notes,
+                    &valid.follow_up_on,
+                    &api_key.name.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        replace_diagnoses(client, new_id, &valid.diagnosis_codes).await?;
+
+        Ok(new_id)
+    })
+    .await?;
+
+    let created = find_examination(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created examination".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/medical-examinations/{id}",
+    tag = "medical",
+    summary = "Get medical examination by id",
+    params(
+        ("id" = i32, Path, description = "Medical examination id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "The examination", body = MedicalExamination),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+        (status = 404, description = "Examination not found", body = String),
+    )
+)]
+pub async fn get_medical_examination_by_id(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Json<MedicalExamination>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_examination(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Medical examination with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/medical-examinations/{id}",
+    tag = "medical",
+    summary = "Correct a medical examination",
+    description = "Examinations are part of the animal's medical history, so they can be corrected but not deleted.",
+    params(
+        ("id" = i32, Path, description = "Medical examination id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = SaveMedicalExamination,
+    responses(
+        (status = 200, description = "Updated examination", body = MedicalExamination),
+        (status = 400, description = "Invalid examination", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Examination not found", body = String),
+    )
+)]
+pub async fn update_medical_examination(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<SaveMedicalExamination>,
+) -> Result<Json<MedicalExamination>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+    let valid = validate_examination(&payload, config.limits.max_description_length)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_care_exists(&mut client, payload.cares_id).await?;
+
+    // A correction keeps who recorded the examination and when
+    let update_query = r#"
+        UPDATE Medical_Examination
+        SET fk_Cares_cares_id = @P2,
+            examined_on = @P3,
+            veterinarian = @P4,
+            reason = @P5,
+            findings = @P6,
+            treatment_notes = @P7,
+            follow_up_on = @P8
+        WHERE medical_examination_id = @P1
+    "#;
+
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &payload.cares_id,
+                    &valid.examined_on,
+                    &payload.veterinarian.trim(),
+                    &payload.reason,
+                    &payload.findings,
+                    &payload.treatment_notes,
+                    &valid.follow_up_on,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Update error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Update error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Medical examination with id {} not found", id),
+            ));
+        }
+
+        replace_diagnoses(client, id, &valid.diagnosis_codes).await
+    })
+    .await?;
+
+    let updated = find_examination(&mut client, id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated examination".to_string(),
+    ))?;
+
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    get,
+    path = "/medical-examinations/follow-ups",
+    tag = "medical",
+    summary = "List follow-ups that are due",
+    description = "Examinations whose follow-up date has come, for animals in the collection that haven't been examined again since.",
+    params(
+        FollowUpQuery,
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "Examinations with a follow-up due, soonest first", body = Vec<MedicalExamination>),
+        (status = 400, description = "Invalid due_by", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+    )
+)]
+pub async fn get_medical_follow_ups(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Query(params): Query<FollowUpQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<MedicalExamination>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+    let due_by =
+        parse_date("due_by", params.due_by.as_deref())?.unwrap_or_else(|| Utc::now().date_naive());
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        r#"{}
+        JOIN Animal a ON a.animal_id = m.fk_Animal_animal_id
+        WHERE m.follow_up_on <= @P1
+          AND a.status IN {}
+          AND NOT EXISTS (
+              SELECT 1 FROM Medical_Examination later
+              WHERE later.fk_Animal_animal_id = m.fk_Animal_animal_id
+                AND later.examined_on > m.examined_on
+          )
+        ORDER BY m.follow_up_on, m.medical_examination_id"#,
+        EXAMINATION_SELECT,
+        in_collection_sql()
+    );
+
+    Ok(Json(
+        query_examinations(&mut client, &query, &[&due_by]).await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/medical-examinations/{id}/attachments",
+    tag = "medical",
+    summary = "Add an attachment to a medical examination",
+    params(
+        ("id" = i32, Path, description = "Medical examination id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = NewMedicalAttachment,
+    responses(
+        (status = 201, description = "Attachment recorded", body = MedicalAttachment),
+        (status = 400, description = "Invalid attachment", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Examination not found", body = String),
+    )
+)]
+pub async fn add_medical_attachment(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<NewMedicalAttachment>,
+) -> Result<(StatusCode, Json<MedicalAttachment>), (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+
+    if payload.file_name.trim().is_empty() || payload.storage_reference.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "file_name and storage_reference are required".to_string(),
+        ));
+    }
+    check_length("file_name", &payload.file_name, VARCHAR_LENGTH)?;
+    check_length("content_type", &payload.content_type, CONTENT_TYPE_LENGTH)?;
+    check_length(
+        "storage_reference",
+        &payload.storage_reference,
+        STORAGE_REFERENCE_LENGTH,
+    )?;
+    if !is_content_type(payload.content_type.trim()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("'{}' is not a content type", payload.content_type),
+        ));
+    }
+    if payload.size_bytes < 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "size_bytes can't be negative".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_examination(&mut client, id).await?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Medical examination with id {} not found", id),
+        ));
+    }
+
+    let new_id = next_id(&mut client, "Medical_Attachment", "medical_attachment_id").await?;
+
+    let insert_query = r#"
+        INSERT INTO Medical_Attachment (medical_attachment_id, fk_Medical_Examination_id, file_name, content_type, size_bytes, storage_reference, uploaded_at)
+        VALUES (@P1, @P2, @P3, @P4, @P5, @P6, SYSUTCDATETIME())
+    "#;
+
+    client
+        .execute(
+            insert_query,
+            &[
+                &new_id,
+                &id,
+                &payload.file_name.trim(),
+                &payload.content_type.trim(),
+                &payload.size_bytes,
+                &payload.storage_reference.trim(),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let query = format!(
+        "SELECT {} FROM Medical_Attachment WHERE medical_attachment_id = @P1",
+        ATTACHMENT_COLUMNS
+    );
+    let rows = fetch_rows(&mut client, &query, &[&new_id]).await?;
+
+    let created = rows.first().map(attachment_from_row).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created attachment".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/medical-examinations/{id}/attachments/{attachment_id}",
+    tag = "medical",
+    summary = "Remove an attachment from a medical examination",
+    params(
+        ("id" = i32, Path, description = "Medical examination id"),
+        ("attachment_id" = i32, Path, description = "Medical attachment id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    responses(
+        (status = 204, description = "Attachment removed; the stored file is left alone"),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Attachment not found", body = String),
+    )
+)]
+pub async fn delete_medical_attachment(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path((id, attachment_id)): Path<(i32, i32)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = "DELETE FROM Medical_Attachment WHERE medical_attachment_id = @P2 AND fk_Medical_Examination_id = @P1";
+
+    let rows_affected = client
+        .execute(query, &[&id, &attachment_id])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Delete error: {}", e),
+            )
+        })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!(
+                "Attachment {} of medical examination {} not found",
+                attachment_id, id
+            ),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
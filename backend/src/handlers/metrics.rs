@@ -0,0 +1,86 @@
+use crate::db::Database;
+use crate::metrics::METRICS;
+use crate::models::{frequency_interval_days, in_collection_sql};
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, Utc};
+use tracing::warn;
+
+/// Recompute the domain gauges from the database.
+async fn refresh_domain_gauges(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = db.connect().await?;
+
+    let active_query = format!(
+        "SELECT COUNT(*) FROM Animal WHERE status IN {}",
+        in_collection_sql()
+    );
+    let active = client
+        .query(active_query, &[])
+        .await?
+        .into_row()
+        .await?
+        .and_then(|row| row.get::<i32, _>(0))
+        .unwrap_or(0);
+
+    // Last time each care was given to each animal in the collection
+    let last_cares_query = format!(
+        r#"
+        SELECT MAX(ac.date_of_care), c.frequency
+        FROM Animal_Care_have ac
+        JOIN Animal a ON a.animal_id = ac.fk_Animal_animal_id
+        JOIN Cares c ON c.cares_id = ac.fk_Cares_cares_id
+        WHERE a.status IN {}
+        GROUP BY ac.fk_Animal_animal_id, ac.fk_Cares_cares_id, c.frequency
+    "#,
+        in_collection_sql()
+    );
+    let rows = client
+        .query(last_cares_query, &[])
+        .await?
+        .into_first_result()
+        .await?;
+
+    let today = Utc::now().date_naive();
+    let overdue = rows
+        .iter()
+        .filter(|row| {
+            let last = row.get::<NaiveDate, _>(0);
+            let interval = frequency_interval_days(row.get::<&str, _>(1).unwrap_or(""));
+            match (last, interval) {
+                (Some(last), Some(days)) => (today - last).num_days() > days,
+                _ => false,
+            }
+        })
+        .count();
+
+    METRICS.active_animals.set(i64::from(active));
+    METRICS.overdue_cares.set(overdue as i64);
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "operations",
+    summary = "Prometheus metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", content_type = "text/plain", body = String),
+    )
+)]
+pub async fn get_metrics(State(db): State<Database>) -> impl IntoResponse {
+    // A scrape still succeeds when the database is down; the gauges keep
+    // their last values and the failure shows up in the error counters.
+    if let Err(e) = refresh_domain_gauges(&db).await {
+        METRICS.record_error("metrics_refresh");
+        warn!(error = %e, "Could not refresh domain gauges");
+    }
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        METRICS.render(),
+    )
+}
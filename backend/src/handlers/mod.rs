@@ -1,7 +1,35 @@
 pub mod animal_cares;
+pub mod animal_revisions;
 pub mod animals;
 pub mod cares;
+pub mod enclosures;
+pub mod events;
+pub mod feeding;
+pub mod genealogy;
+pub mod health;
+pub mod inventory;
+pub mod measurements;
+pub mod medical;
+pub mod metrics;
+pub mod movements;
+pub mod species;
+pub mod treatments;
+pub mod webhooks;
 
 pub use animal_cares::*;
+pub use animal_revisions::*;
 pub use animals::*;
 pub use cares::*;
+pub use enclosures::*;
+pub use events::*;
+pub use feeding::*;
+pub use genealogy::*;
+pub use health::*;
+pub use inventory::*;
+pub use measurements::*;
+pub use medical::*;
+pub use metrics::*;
+pub use movements::*;
+pub use species::*;
+pub use treatments::*;
+pub use webhooks::*;
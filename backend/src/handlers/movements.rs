@@ -0,0 +1,483 @@
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::events::{ChangeAction, EntityKind, EventBus};
+use crate::handlers::animals::{apply_status_transition, find_animal};
+use crate::handlers::enclosures::{check_can_enter, enter_enclosure, find_current_assignment};
+use crate::models::{
+    InboundTransfer, MOVEMENT_TYPES, Movement, MovementQuery, OutboundTransfer, TimelineEntry,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_date};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::NaiveDate;
+use tiberius::Row;
+use tracing::error;
+
+const MOVEMENT_COLUMNS: &str = "movement_id, fk_Animal_animal_id, movement_type, fk_From_Enclosure_id, from_location, fk_To_Enclosure_id, to_location, institution, is_loan, moved_on, reason, responsible_staff, recorded_at";
+
+fn movement_from_row(row: &Row) -> Movement {
+    Movement {
+        movement_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        movement_type: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        from_enclosure_id: row.get::<i32, _>(3),
+        from_location: row.get::<&str, _>(4).map(|s| s.to_string()),
+        to_enclosure_id: row.get::<i32, _>(5),
+        to_location: row.get::<&str, _>(6).map(|s| s.to_string()),
+        institution: row.get::<&str, _>(7).map(|s| s.to_string()),
+        is_loan: row.get::<bool, _>(8).unwrap_or(false),
+        moved_on: row.get(9),
+        reason: row.get::<&str, _>(10).map(|s| s.to_string()),
+        responsible_staff: row.get::<&str, _>(11).map(|s| s.to_string()),
+        recorded_at: row.get(12),
+    }
+}
+
+/// A movement about to be logged.
+pub struct NewMovement {
+    pub animal_id: i32,
+    /// One of `MOVEMENT_TYPES`
+    pub movement_type: &'static str,
+    pub from_enclosure_id: Option<i32>,
+    pub from_location: Option<String>,
+    pub to_enclosure_id: Option<i32>,
+    pub to_location: Option<String>,
+    pub institution: Option<String>,
+    pub is_loan: bool,
+    /// Today when `None`
+    pub moved_on: Option<NaiveDate>,
+    pub reason: Option<String>,
+    pub responsible_staff: Option<String>,
+}
+
+/// Append a movement to the log and return it.
+pub async fn record_movement(
+    client: &mut DbClient,
+    movement: NewMovement,
+) -> Result<Movement, (StatusCode, String)> {
+    let new_id = next_id(client, "Movement", "movement_id").await?;
+
+    let insert_query = format!(
+        r#"
+        INSERT INTO Movement ({})
+        VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, @P9,
+                ISNULL(@P10, CAST(SYSUTCDATETIME() AS DATE)), @P11, @P12, SYSUTCDATETIME())
+    "#,
+        MOVEMENT_COLUMNS
+    );
+
+    client
+        .execute(
+            insert_query,
+            &[
+                &new_id,                     // @P1
+                &movement.animal_id,         // @P2
+                &movement.movement_type,     // @P3
+                &movement.from_enclosure_id, // @P4
+                &movement.from_location,     // @P5
+                &movement.to_enclosure_id,   // @P6
+                &movement.to_location,       // @P7
+                &movement.institution,       // @P8
+                &movement.is_loan,           // @P9
+                &movement.moved_on,          // @P10
+                &movement.reason,            // @P11
+                &movement.responsible_staff, // @P12
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Movement insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Movement insert error: {}", e),
+            )
+        })?;
+
+    let query = format!(
+        "SELECT {} FROM Movement WHERE movement_id = @P1",
+        MOVEMENT_COLUMNS
+    );
+    let rows = fetch_rows(client, &query, &[&new_id]).await?;
+
+    rows.first().map(movement_from_row).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch the recorded movement".to_string(),
+    ))
+}
+
+fn validate_transfer(
+    institution: &str,
+    reason: Option<&str>,
+    responsible_staff: Option<&str>,
+) -> Result<(), (StatusCode, String)> {
+    if institution.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Institution is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("institution", institution, VARCHAR_LENGTH)?;
+    check_length("reason", reason.unwrap_or(""), VARCHAR_LENGTH)?;
+    check_length(
+        "responsible_staff",
+        responsible_staff.unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/movements",
+    tag = "movements",
+    summary = "List movements, most recent first",
+    params(MovementQuery),
+    responses(
+        (status = 200, description = "Movements of all animals", body = Vec<Movement>),
+        (status = 400, description = "Unknown movement_type", body = String),
+    )
+)]
+pub async fn get_movements(
+    State(db): State<Database>,
+    Query(params): Query<MovementQuery>,
+) -> Result<Json<Vec<Movement>>, (StatusCode, String)> {
+    if let Some(movement_type) = &params.movement_type
+        && !MOVEMENT_TYPES.contains(&movement_type.as_str())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown movement_type '{}'; expected one of {}",
+                movement_type,
+                MOVEMENT_TYPES.join(", ")
+            ),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "SELECT {} FROM Movement WHERE @P1 IS NULL OR movement_type = @P1 ORDER BY moved_on DESC, movement_id DESC",
+        MOVEMENT_COLUMNS
+    );
+
+    let rows = fetch_rows(&mut client, &query, &[&params.movement_type]).await?;
+
+    Ok(Json(rows.iter().map(movement_from_row).collect()))
+}
+
+async fn find_movements_of_animal(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Vec<Movement>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Movement WHERE fk_Animal_animal_id = @P1 ORDER BY moved_on DESC, movement_id DESC",
+        MOVEMENT_COLUMNS
+    );
+
+    let rows = fetch_rows(client, &query, &[&id]).await?;
+
+    Ok(rows.iter().map(movement_from_row).collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/movements",
+    tag = "movements",
+    summary = "Movements of an animal, most recent first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 200, description = "Where the animal has been moved", body = Vec<Movement>),
+    )
+)]
+pub async fn get_movements_of_animal(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<Movement>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    Ok(Json(find_movements_of_animal(&mut client, id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/transfers/outbound",
+    tag = "movements",
+    summary = "Transfer or loan an animal to another institution",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = OutboundTransfer,
+    responses(
+        (status = 201, description = "Transfer recorded", body = Movement),
+        (status = 400, description = "Invalid transfer", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "The animal's status doesn't allow the transfer", body = String),
+    )
+)]
+pub async fn transfer_animal_out(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path(id): Path<i32>,
+    Json(payload): Json<OutboundTransfer>,
+) -> Result<(StatusCode, Json<Movement>), (StatusCode, String)> {
+    validate_transfer(
+        &payload.institution,
+        payload.reason.as_deref(),
+        payload.responsible_staff.as_deref(),
+    )?;
+    let moved_on = parse_date("moved_on", payload.moved_on.as_deref())?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // A loaned animal stays in the collection; one given away leaves it
+    let is_loan = payload.loan.unwrap_or(false);
+    let status = if is_loan { "on_loan" } else { "transferred" };
+
+    // The status, the enclosure it leaves and the movement change together
+    let movement = in_transaction(&mut client, async |client| {
+        let habitat = find_animal(client, id)
+            .await?
+            .ok_or((
+                StatusCode::NOT_FOUND,
+                format!("Animal with id {} not found", id),
+            ))?
+            .habitat;
+        let enclosure = find_current_assignment(client, id).await?;
+
+        apply_status_transition(client, id, status, moved_on, payload.reason.as_deref()).await?;
+
+        let institution = payload.institution.trim().to_string();
+        record_movement(
+            client,
+            NewMovement {
+                animal_id: id,
+                movement_type: "transfer_out",
+                from_enclosure_id: enclosure.as_ref().map(|e| e.enclosure_id),
+                from_location: enclosure.map(|e| e.enclosure_name).or(habitat),
+                to_enclosure_id: None,
+                to_location: Some(institution.clone()),
+                institution: Some(institution),
+                is_loan,
+                moved_on,
+                reason: payload.reason,
+                responsible_staff: payload.responsible_staff,
+            },
+        )
+        .await
+    })
+    .await?;
+
+    if is_loan {
+        events.publish_without_data(EntityKind::Animal, ChangeAction::Updated, id);
+    } else {
+        events.publish_without_data(EntityKind::Animal, ChangeAction::Deactivated, id);
+    }
+
+    Ok((StatusCode::CREATED, Json(movement)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/transfers/inbound",
+    tag = "movements",
+    summary = "Record an animal arriving from another institution",
+    description = "For an animal coming back from a loan or transfer, or a new arrival added with `POST /animals` first. An animal back from a loan becomes active again; one that was transferred out or released becomes arrived.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    request_body = InboundTransfer,
+    responses(
+        (status = 201, description = "Arrival recorded", body = Movement),
+        (status = 400, description = "Invalid transfer", body = String),
+        (status = 404, description = "Animal not found", body = String),
+        (status = 409, description = "The animal can't arrive, or the enclosure is full or doesn't take the species", body = String),
+    )
+)]
+pub async fn transfer_animal_in(
+    State(db): State<Database>,
+    State(events): State<EventBus>,
+    Path(id): Path<i32>,
+    Json(payload): Json<InboundTransfer>,
+) -> Result<(StatusCode, Json<Movement>), (StatusCode, String)> {
+    validate_transfer(
+        &payload.institution,
+        payload.reason.as_deref(),
+        payload.responsible_staff.as_deref(),
+    )?;
+    let moved_on = parse_date("moved_on", payload.moved_on.as_deref())?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // The status, the enclosure it enters and the movement change together
+    let (movement, status_changed) = in_transaction(&mut client, async |client| {
+        let animal = find_animal(client, id).await?.ok_or((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ))?;
+        // Back from a loan it's simply active again; anything else arrives anew
+        let status = match animal.status.as_str() {
+            "on_loan" => Some("active"),
+            "transferred" | "released" => Some("arrived"),
+            "arrived" | "born" | "active" => None,
+            other => {
+                return Err((
+                    StatusCode::CONFLICT,
+                    format!("Animal with id {} is {} and can't arrive", id, other),
+                ));
+            }
+        };
+
+        // Check the enclosure before changing anything
+        let enclosure = match payload.enclosure_id {
+            Some(enclosure_id) => Some(check_can_enter(client, id, enclosure_id).await?),
+            None => None,
+        };
+
+        if let Some(status) = status {
+            apply_status_transition(client, id, status, moved_on, payload.reason.as_deref())
+                .await?;
+        }
+        if let Some(enclosure) = &enclosure {
+            enter_enclosure(client, id, enclosure, payload.reason.as_deref()).await?;
+        }
+
+        let institution = payload.institution.trim().to_string();
+        let movement = record_movement(
+            client,
+            NewMovement {
+                animal_id: id,
+                movement_type: "transfer_in",
+                from_enclosure_id: None,
+                from_location: Some(institution.clone()),
+                to_enclosure_id: enclosure.as_ref().map(|e| e.enclosure_id),
+                to_location: enclosure.map(|e| e.name).or(animal.habitat),
+                institution: Some(institution),
+                is_loan: false,
+                moved_on,
+                reason: payload.reason,
+                responsible_staff: payload.responsible_staff,
+            },
+        )
+        .await?;
+
+        Ok((movement, status.is_some()))
+    })
+    .await?;
+
+    if status_changed {
+        events.publish_without_data(EntityKind::Animal, ChangeAction::Updated, id);
+    }
+
+    Ok((StatusCode::CREATED, Json(movement)))
+}
+
+fn describe_movement(movement: &Movement) -> String {
+    let place = |location: &Option<String>| location.clone().unwrap_or_else(|| "?".to_string());
+    match movement.movement_type.as_str() {
+        "transfer_out" if movement.is_loan => {
+            format!("Loaned to {}", place(&movement.institution))
+        }
+        "transfer_out" => format!("Transferred to {}", place(&movement.institution)),
+        "transfer_in" => format!("Arrived from {}", place(&movement.institution)),
+        _ => match (&movement.from_location, &movement.to_location) {
+            (Some(from), Some(to)) => format!("Moved from {} to {}", from, to),
+            (None, Some(to)) => format!("Moved to {}", to),
+            (Some(from), None) => format!("Moved out of {}", from),
+            (None, None) => "Moved".to_string(),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/timeline",
+    tag = "movements",
+    summary = "Movements and cares of an animal, most recent first",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+    ),
+    responses(
+        (status = 200, description = "The animal's timeline", body = Vec<TimelineEntry>),
+    )
+)]
+pub async fn get_animal_timeline(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<TimelineEntry>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let mut timeline: Vec<TimelineEntry> = find_movements_of_animal(&mut client, id)
+        .await?
+        .iter()
+        .map(|movement| TimelineEntry {
+            date: movement.moved_on,
+            kind: "movement".to_string(),
+            summary: describe_movement(movement),
+            movement_id: Some(movement.movement_id),
+            animal_care_id: None,
+        })
+        .collect();
+
+    let cares_query = r#"
+        SELECT ach.animal_care_id, ach.date_of_care, c.type_of_care
+        FROM Animal_Care_have ach
+        LEFT JOIN Cares c ON c.cares_id = ach.fk_Cares_cares_id
+        WHERE ach.fk_Animal_animal_id = @P1
+        ORDER BY ach.date_of_care DESC, ach.animal_care_id DESC
+    "#;
+
+    let rows = fetch_rows(&mut client, cares_query, &[&id]).await?;
+
+    timeline.extend(rows.iter().map(|row| TimelineEntry {
+        date: row.get(1),
+        kind: "care".to_string(),
+        summary: row.get::<&str, _>(2).unwrap_or("Care").to_string(),
+        movement_id: None,
+        animal_care_id: row.get::<i32, _>(0),
+    }));
+
+    // Stable, so within a day movements stay ahead of cares; undated last
+    timeline.sort_by(|a, b| match (a.date, b.date) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    Ok(Json(timeline))
+}
@@ -0,0 +1,586 @@
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::models::{CONSERVATION_STATUSES, SaveSpecies, Species};
+use crate::validation::{VARCHAR_LENGTH, check_length};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use std::collections::HashMap;
+use tiberius::Row;
+use tracing::error;
+
+const SPECIES_COLUMNS: &str = "species_id, common_name_pt, common_name_en, scientific_name, taxonomic_class, taxonomic_order, family, conservation_status, typical_lifespan_years";
+
+fn species_from_row(row: &Row) -> Species {
+    Species {
+        species_id: row.get::<i32, _>(0).unwrap_or(0),
+        common_name_pt: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        common_name_en: row.get::<&str, _>(2).map(|s| s.to_string()),
+        scientific_name: row.get::<&str, _>(3).map(|s| s.to_string()),
+        taxonomic_class: row.get::<&str, _>(4).map(|s| s.to_string()),
+        taxonomic_order: row.get::<&str, _>(5).map(|s| s.to_string()),
+        family: row.get::<&str, _>(6).map(|s| s.to_string()),
+        conservation_status: row.get::<&str, _>(7).map(|s| s.to_string()),
+        typical_lifespan_years: row.get::<i32, _>(8),
+        aliases: Vec::new(),
+    }
+}
+
+/// The species a name refers to, as `(species_id, common_name_pt)`.
+///
+/// Matches the Portuguese or English common name, the scientific name or an
+/// alias, so the names animals were registered under before the catalogue
+/// existed keep working.
+pub async fn find_species_by_name(
+    client: &mut DbClient,
+    name: &str,
+) -> Result<Option<(i32, String)>, (StatusCode, String)> {
+    let query = r#"
+        SELECT TOP 1 s.species_id, s.common_name_pt
+        FROM Species s
+        LEFT JOIN Species_Alias sa ON sa.fk_Species_species_id = s.species_id
+        WHERE s.common_name_pt = @P1 OR s.common_name_en = @P1
+           OR s.scientific_name = @P1 OR sa.alias = @P1
+        ORDER BY CASE WHEN s.common_name_pt = @P1 THEN 0 ELSE 1 END, s.species_id
+    "#;
+
+    let rows = fetch_rows(client, query, &[&name.trim()]).await?;
+
+    Ok(rows.first().map(|r| {
+        (
+            r.get::<i32, _>(0).unwrap_or(0),
+            r.get::<&str, _>(1).unwrap_or("").to_string(),
+        )
+    }))
+}
+
+/// Resolve the species of an animal write from `species_id` or `specie`,
+/// preferring the id. `None` when neither is given.
+pub async fn resolve_species(
+    client: &mut DbClient,
+    species_id: Option<i32>,
+    name: Option<&str>,
+) -> Result<Option<(i32, String)>, (StatusCode, String)> {
+    if let Some(id) = species_id {
+        let species = find_species(client, id).await?.ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown species_id {}", id),
+        ))?;
+        return Ok(Some((species.species_id, species.common_name_pt)));
+    }
+
+    match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => find_species_by_name(client, name).await?.map(Some).ok_or((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown species '{}'; add it under /api/v1/species or use one of its names",
+                name
+            ),
+        )),
+        None => Ok(None),
+    }
+}
+
+pub async fn find_species(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<Species>, (StatusCode, String)> {
+    let query = format!(
+        "SELECT {} FROM Species WHERE species_id = @P1",
+        SPECIES_COLUMNS
+    );
+
+    let rows = fetch_rows(client, &query, &[&id]).await?;
+
+    let Some(mut species) = rows.first().map(species_from_row) else {
+        return Ok(None);
+    };
+    species.aliases = find_aliases(client, Some(id))
+        .await?
+        .remove(&id)
+        .unwrap_or_default();
+    Ok(Some(species))
+}
+
+/// Aliases by species id, for one species or all of them.
+async fn find_aliases(
+    client: &mut DbClient,
+    species_id: Option<i32>,
+) -> Result<HashMap<i32, Vec<String>>, (StatusCode, String)> {
+    let query = "SELECT fk_Species_species_id, alias FROM Species_Alias WHERE @P1 IS NULL OR fk_Species_species_id = @P1 ORDER BY alias";
+
+    let rows = fetch_rows(client, query, &[&species_id]).await?;
+
+    let mut aliases: HashMap<i32, Vec<String>> = HashMap::new();
+    for row in &rows {
+        if let (Some(id), Some(alias)) = (row.get::<i32, _>(0), row.get::<&str, _>(1)) {
+            aliases.entry(id).or_default().push(alias.to_string());
+        }
+    }
+    Ok(aliases)
+}
+
+fn validate_species(payload: &SaveSpecies) -> Result<(), (StatusCode, String)> {
+    if payload.common_name_pt.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "common_name_pt is required and cannot be empty".to_string(),
+        ));
+    }
+    for (field, value) in [
+        ("common_name_pt", Some(&payload.common_name_pt)),
+        ("common_name_en", payload.common_name_en.as_ref()),
+        ("scientific_name", payload.scientific_name.as_ref()),
+        ("taxonomic_class", payload.taxonomic_class.as_ref()),
+        ("taxonomic_order", payload.taxonomic_order.as_ref()),
+        ("family", payload.family.as_ref()),
+    ] {
+        check_length(
+            field,
+            value.map(String::as_str).unwrap_or(""),
+            VARCHAR_LENGTH,
+        )?;
+    }
+    for alias in payload.aliases.iter().flatten() {
+        if alias.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Aliases cannot be empty".to_string(),
+            ));
+        }
+        check_length("aliases", alias, VARCHAR_LENGTH)?;
+    }
+    if let Some(status) = &payload.conservation_status
+        && !CONSERVATION_STATUSES.contains(&status.as_str())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown conservation_status '{}'; expected one of {}",
+                status,
+                CONSERVATION_STATUSES.join(", ")
+            ),
+        ));
+    }
+    if let Some(years) = payload.typical_lifespan_years
+        && years <= 0
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "typical_lifespan_years must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Every name the payload claims must be free or already belong to `species_id`,
+/// so a name never resolves to two species.
+async fn check_names_free(
+    client: &mut DbClient,
+    payload: &SaveSpecies,
+    species_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let names = std::iter::once(&payload.common_name_pt).chain(payload.aliases.iter().flatten());
+    for name in names {
+        if let Some((owner, owner_name)) = find_species_by_name(client, name).await?
+            && Some(owner) != species_id
+        {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "'{}' already refers to species {} ({})",
+                    name.trim(),
+                    owner,
+                    owner_name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn replace_aliases(
+    client: &mut DbClient,
+    species_id: i32,
+    aliases: &[String],
+) -> Result<(), (StatusCode, String)> {
+    client
+        .execute(
+            "DELETE FROM Species_Alias WHERE fk_Species_species_id = @P1",
+            &[&species_id],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Alias delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Alias delete error: {}", e),
+            )
+        })?;
+
+    let mut inserted: Vec<&str> = Vec::new();
+    for alias in aliases.iter().map(|a| a.trim()) {
+        if inserted.contains(&alias) {
+            continue;
+        }
+        client
+            .execute(
+                "INSERT INTO Species_Alias (alias, fk_Species_species_id) VALUES (@P1, @P2)",
+                &[&alias, &species_id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Alias insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Alias insert error: {}", e),
+                )
+            })?;
+        inserted.push(alias);
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/species",
+    tag = "species",
+    summary = "List species",
+    responses(
+        (status = 200, description = "Species by Portuguese common name", body = Vec<Species>),
+    )
+)]
+pub async fn get_species(
+    State(db): State<Database>,
+) -> Result<Json<Vec<Species>>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "SELECT {} FROM Species ORDER BY common_name_pt",
+        SPECIES_COLUMNS
+    );
+
+    let rows = fetch_rows(&mut client, &query, &[]).await?;
+
+    let mut aliases = find_aliases(&mut client, None).await?;
+    let species = rows
+        .iter()
+        .map(species_from_row)
+        .map(|mut species| {
+            species.aliases = aliases.remove(&species.species_id).unwrap_or_default();
+            species
+        })
+        .collect();
+
+    Ok(Json(species))
+}
+
+#[utoipa::path(
+    get,
+    path = "/species/{id}",
+    tag = "species",
+    summary = "Get species by id",
+    params(
+        ("id" = i32, Path, description = "Species id"),
+    ),
+    responses(
+        (status = 200, description = "The species", body = Species),
+        (status = 404, description = "Species not found", body = String),
+    )
+)]
+pub async fn get_species_by_id(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<Json<Species>, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_species(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Species with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/species",
+    tag = "species",
+    summary = "Add species",
+    request_body = SaveSpecies,
+    responses(
+        (status = 201, description = "Species created", body = Species),
+        (status = 400, description = "Invalid species", body = String),
+        (status = 409, description = "A name or alias already belongs to another species", body = String),
+    )
+)]
+pub async fn add_species(
+    State(db): State<Database>,
+    Json(payload): Json<SaveSpecies>,
+) -> Result<(StatusCode, Json<Species>), (StatusCode, String)> {
+    validate_species(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_names_free(&mut client, &payload, None).await?;
+
+    // A species is only created with all of its aliases
+    let new_id = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Species", "species_id").await?;
+
+        let insert_query = r#"
+            INSERT INTO Species (species_id, common_name_pt, common_name_en, scientific_name, taxonomic_class, taxonomic_order, family, conservation_status, typical_lifespan_years)
+            VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, @P9)
+        "#;
+
+        client
+            .execute(
+                insert_query,
+                &[
+                    &new_id,
+                    &payload.common_name_pt.trim(),
+                    &payload.common_name_en,
+                    &payload.scientific_name,
+                    &payload.taxonomic_class,
+                    &payload.taxonomic_order,
+                    &payload.family,
+                    &payload.conservation_status,
+                    &payload.typical_lifespan_years,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        replace_aliases(
+            client,
+            new_id,
+            payload.aliases.as_deref().unwrap_or_default(),
+        )
+        .await?;
+
+        Ok(new_id)
+    })
+    .await?;
+
+    let created = find_species(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created species".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/species/{id}",
+    tag = "species",
+    summary = "Update species",
+    params(
+        ("id" = i32, Path, description = "Species id"),
+    ),
+    request_body = SaveSpecies,
+    responses(
+        (status = 200, description = "Updated species", body = Species),
+        (status = 400, description = "Invalid species", body = String),
+        (status = 404, description = "Species not found", body = String),
+        (status = 409, description = "A name or alias already belongs to another species", body = String),
+    )
+)]
+pub async fn update_species(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+    Json(payload): Json<SaveSpecies>,
+) -> Result<Json<Species>, (StatusCode, String)> {
+    validate_species(&payload)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    check_names_free(&mut client, &payload, Some(id)).await?;
+
+    let update_query = r#"
+        UPDATE Species
+        SET common_name_pt = @P2,
+            common_name_en = @P3,
+            scientific_name = @P4,
+            taxonomic_class = @P5,
+            taxonomic_order = @P6,
+            family = @P7,
+            conservation_status = @P8,
+            typical_lifespan_years = @P9
+        WHERE species_id = @P1
+    "#;
+
+    // Aliases are replaced with the rest, or not at all
+    in_transaction(&mut client, async |client| {
+        let rows_affected = client
+            .execute(
+                update_query,
+                &[
+                    &id,
+                    &payload.common_name_pt.trim(),
+                    &payload.common_name_en,
+                    &payload.scientific_name,
+                    &payload.taxonomic_class,
+                    &payload.taxonomic_order,
+                    &payload.family,
+                    &payload.conservation_status,
+                    &payload.typical_lifespan_years,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Update error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Update error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Species with id {} not found", id),
+            ));
+        }
+
+        if let Some(aliases) = &payload.aliases {
+            replace_aliases(client, id, aliases).await?;
+        }
+        Ok(())
+    })
+    .await?;
+
+    find_species(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch updated species".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/species/{id}",
+    tag = "species",
+    summary = "Delete species",
+    params(
+        ("id" = i32, Path, description = "Species id"),
+    ),
+    responses(
+        (status = 204, description = "Species deleted"),
+        (status = 404, description = "Species not found", body = String),
+        (status = 409, description = "Animals or external parents still belong to the species, enclosures still allow it or diet plans are set for it", body = String),
+    )
+)]
+pub async fn delete_species(
+    State(db): State<Database>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // Deactivated animals count too: they keep their species for history,
+    // and so do external parents in the studbook. Enclosure rules and diet
+    // plans are left for whoever manages them to change.
+    let usage_query = "SELECT (SELECT COUNT(*) FROM Animal WHERE fk_Species_species_id = @P1) + (SELECT COUNT(*) FROM External_Animal WHERE fk_Species_species_id = @P1), (SELECT COUNT(*) FROM Enclosure_Species WHERE fk_Species_species_id = @P1), (SELECT COUNT(*) FROM Diet_Plan WHERE fk_Species_species_id = @P1)";
+    let rows = fetch_rows(&mut client, usage_query, &[&id]).await?;
+    let animals = rows.first().and_then(|r| r.get::<i32, _>(0)).unwrap_or(0);
+    if animals > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Species with id {} still has {} animal(s)", id, animals),
+        ));
+    }
+    let enclosures = rows.first().and_then(|r| r.get::<i32, _>(1)).unwrap_or(0);
+    if enclosures > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Species with id {} is still allowed in {} enclosure(s)",
+                id, enclosures
+            ),
+        ));
+    }
+    let diet_plans = rows.first().and_then(|r| r.get::<i32, _>(2)).unwrap_or(0);
+    if diet_plans > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Species with id {} still has {} diet plan(s)",
+                id, diet_plans
+            ),
+        ));
+    }
+
+    // Aliases go with the species, or stay if it can't be deleted
+    in_transaction(&mut client, async |client| {
+        client
+            .execute(
+                "DELETE FROM Species_Alias WHERE fk_Species_species_id = @P1",
+                &[&id],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Delete error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Delete error: {}", e),
+                )
+            })?;
+
+        let rows_affected = client
+            .execute("DELETE FROM Species WHERE species_id = @P1", &[&id])
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Delete error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Delete error: {}", e),
+                )
+            })?;
+
+        if rows_affected.total() == 0 {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Species with id {} not found", id),
+            ));
+        }
+        Ok(())
+    })
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
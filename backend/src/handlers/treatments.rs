@@ -0,0 +1,1035 @@
+use crate::auth::{MEDICAL_READ, MEDICAL_WRITE, require_scope};
+use crate::config::Config;
+use crate::db::{Database, DbClient, fetch_rows, in_transaction, next_id};
+use crate::handlers::animals::find_animal;
+use crate::handlers::inventory::consume_for_dose;
+use crate::models::{
+    ADMINISTRATION_ROUTES, AdministerDose, AdministeredDose, CreateTreatmentPlan, StopTreatment,
+    TreatmentDose, TreatmentPlan, TreatmentPlanQuery,
+};
+use crate::validation::{VARCHAR_LENGTH, check_length, parse_timestamp};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
+
+/// Plans with their animal's name; callers add the `WHERE`/`ORDER BY`.
+const PLAN_SELECT: &str = "SELECT p.treatment_plan_id, p.fk_Animal_animal_id, a.name, p.fk_Medical_Examination_id, p.drug, p.dose_per_kg, p.dose_unit, p.weight_kg, p.dose, p.route, p.interval_hours, p.duration_days, p.starts_at, p.ends_at, p.prescribed_by, p.notes, p.stopped_at, p.stop_reason FROM Treatment_Plan p JOIN Animal a ON a.animal_id = p.fk_Animal_animal_id";
+
+const DOSE_COLUMNS: &str = "treatment_dose_id, fk_Treatment_Plan_id, scheduled_at, administered_at, administered_by, amount, notes";
+
+/// A course can't schedule more doses than this
+const MAX_SCHEDULED_DOSES: i64 = 1000;
+/// Given amounts further than this from the prescribed dose get a warning
+const AMOUNT_TOLERANCE: f64 = 0.1;
+
+fn plan_from_row(row: &Row) -> TreatmentPlan {
+    TreatmentPlan {
+        treatment_plan_id: row.get::<i32, _>(0).unwrap_or(0),
+        animal_id: row.get::<i32, _>(1).unwrap_or(0),
+        animal_name: row.get::<&str, _>(2).unwrap_or("").to_string(),
+        medical_examination_id: row.get::<i32, _>(3),
+        drug: row.get::<&str, _>(4).unwrap_or("").to_string(),
+        dose_per_kg: row.get::<f64, _>(5).unwrap_or(0.0),
+        dose_unit: row.get::<&str, _>(6).unwrap_or("").to_string(),
+        weight_kg: row.get::<f64, _>(7).unwrap_or(0.0),
+        dose: row.get::<f64, _>(8).unwrap_or(0.0),
+        route: row.get::<&str, _>(9).unwrap_or("").to_string(),
+        interval_hours: row.get::<i32, _>(10).unwrap_or(0),
+        duration_days: row.get::<i32, _>(11).unwrap_or(0),
+        starts_at: row.get(12),
+        ends_at: row.get(13),
+        prescribed_by: row.get::<&str, _>(14).unwrap_or("").to_string(),
+        notes: row.get::<&str, _>(15).map(|s| s.to_string()),
+        status: String::new(),
+        stopped_at: row.get(16),
+        stop_reason: row.get::<&str, _>(17).map(|s| s.to_string()),
+        next_dose_at: None,
+        warnings: Vec::new(),
+        doses: None,
+    }
+}
+
+fn dose_from_row(row: &Row) -> TreatmentDose {
+    TreatmentDose {
+        treatment_dose_id: row.get::<i32, _>(0).unwrap_or(0),
+        treatment_plan_id: row.get::<i32, _>(1).unwrap_or(0),
+        scheduled_at: row.get(2),
+        status: String::new(),
+        administered_at: row.get(3),
+        administered_by: row.get::<&str, _>(4).map(|s| s.to_string()),
+        amount: row.get::<f64, _>(5),
+        notes: row.get::<&str, _>(6).map(|s| s.to_string()),
+    }
+}
+
+/// How far from its scheduled time a dose may be given: half the interval,
+/// so it can't be taken for the next one.
+fn tolerance(plan: &TreatmentPlan) -> Duration {
+    Duration::minutes(i64::from(plan.interval_hours) * 30)
+}
+
+fn plan_status(plan: &TreatmentPlan, now: NaiveDateTime) -> &'static str {
+    if plan.stopped_at.is_some() {
+        "stopped"
+    } else if plan.ends_at.is_some_and(|ends_at| ends_at <= now) {
+        "completed"
+    } else {
+        "active"
+    }
+}
+
+fn dose_status(plan: &TreatmentPlan, dose: &TreatmentDose, now: NaiveDateTime) -> &'static str {
+    let Some(scheduled_at) = dose.scheduled_at else {
+        return "extra";
+    };
+    if dose.administered_at.is_some() {
+        "given"
+    } else if plan
+        .stopped_at
+        .is_some_and(|stopped_at| scheduled_at > stopped_at)
+    {
+        "cancelled"
+    } else if now > scheduled_at + tolerance(plan) {
+        "missed"
+    } else if now >= scheduled_at - tolerance(plan) {
+        "due"
+    } else {
+        "scheduled"
+    }
+}
+
+/// Set the computed fields of a plan from its doses: status, statuses of
+/// the doses, next dose, and warnings for missed doses and doses given
+/// closer together than the tolerance.
+fn summarize_plan(plan: &mut TreatmentPlan, mut doses: Vec<TreatmentDose>, now: NaiveDateTime) {
+    plan.status = plan_status(plan, now).to_string();
+
+    for dose in &mut doses {
+        dose.status = dose_status(plan, dose, now).to_string();
+    }
+
+    plan.next_dose_at = doses
+        .iter()
+        .filter(|d| d.status == "scheduled" || d.status == "due")
+        .filter_map(|d| d.scheduled_at)
+        .min();
+
+    let mut warnings: Vec<String> = doses
+        .iter()
+        .filter(|d| d.status == "missed")
+        .map(|d| {
+            format!(
+                "Dose {} scheduled at {} was missed",
+                d.treatment_dose_id,
+                d.scheduled_at.map(|t| t.to_string()).unwrap_or_default()
+            )
+        })
+        .collect();
+
+    let mut given: Vec<&TreatmentDose> = doses
+        .iter()
+        .filter(|d| d.administered_at.is_some())
+        .collect();
+    given.sort_by_key(|d| d.administered_at);
+    for pair in given.windows(2) {
+        let (Some(first), Some(second)) = (pair[0].administered_at, pair[1].administered_at) else {
+            continue;
+        };
+        if second - first < tolerance(plan) {
+            warnings.push(format!(
+                "Doses {} and {} were given {} minutes apart; the interval is {} hours",
+                pair[0].treatment_dose_id,
+                pair[1].treatment_dose_id,
+                (second - first).num_minutes(),
+                plan.interval_hours
+            ));
+        }
+    }
+
+    plan.warnings = warnings;
+    plan.doses = Some(doses);
+}
+
+/// Run a plan query and summarize each plan returned. The doses are kept
+/// only when `with_doses` is set.
+async fn query_plans(
+    client: &mut DbClient,
+    query: &str,
+    params: &[&dyn tiberius::ToSql],
+    with_doses: bool,
+) -> Result<Vec<TreatmentPlan>, (StatusCode, String)> {
+    let rows = fetch_rows(client, query, params).await?;
+
+    let mut plans: Vec<TreatmentPlan> = rows.iter().map(plan_from_row).collect();
+    if plans.is_empty() {
+        return Ok(plans);
+    }
+
+    // Ids come from the database, so they can be inlined
+    let ids = plans
+        .iter()
+        .map(|p| p.treatment_plan_id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let dose_query = format!(
+        "SELECT {} FROM Treatment_Dose WHERE fk_Treatment_Plan_id IN ({}) ORDER BY ISNULL(scheduled_at, administered_at), treatment_dose_id",
+        DOSE_COLUMNS, ids
+    );
+
+    let rows = fetch_rows(client, &dose_query, &[]).await?;
+
+    let mut doses: HashMap<i32, Vec<TreatmentDose>> = HashMap::new();
+    for dose in rows.iter().map(dose_from_row) {
+        doses.entry(dose.treatment_plan_id).or_default().push(dose);
+    }
+
+    let now = Utc::now().naive_utc();
+    for plan in &mut plans {
+        let plan_doses = doses.remove(&plan.treatment_plan_id).unwrap_or_default();
+        summarize_plan(plan, plan_doses, now);
+        if !with_doses {
+            plan.doses = None;
+        }
+    }
+
+    Ok(plans)
+}
+
+async fn find_plan(
+    client: &mut DbClient,
+    id: i32,
+) -> Result<Option<TreatmentPlan>, (StatusCode, String)> {
+    let query = format!("{} WHERE p.treatment_plan_id = @P1", PLAN_SELECT);
+    Ok(query_plans(client, &query, &[&id], true).await?.pop())
+}
+
+fn validate_plan(
+    payload: &CreateTreatmentPlan,
+    max_text_length: usize,
+) -> Result<(), (StatusCode, String)> {
+    for (field, value) in [
+        ("drug", &payload.drug),
+        ("dose_unit", &payload.dose_unit),
+        ("prescribed_by", &payload.prescribed_by),
+    ] {
+        if value.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("{} is required and cannot be empty", field),
+            ));
+        }
+    }
+    check_length("drug", &payload.drug, VARCHAR_LENGTH)?;
+    check_length("dose_unit", &payload.dose_unit, 20)?;
+    check_length("prescribed_by", &payload.prescribed_by, VARCHAR_LENGTH)?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        max_text_length,
+    )?;
+
+    if !ADMINISTRATION_ROUTES.contains(&payload.route.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown route '{}'; expected one of {}",
+                payload.route,
+                ADMINISTRATION_ROUTES.join(", ")
+            ),
+        ));
+    }
+    if !(payload.dose_per_kg.is_finite() && payload.dose_per_kg > 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "dose_per_kg must be positive".to_string(),
+        ));
+    }
+    if !(payload.weight_kg.is_finite() && payload.weight_kg > 0.0 && payload.weight_kg <= 10_000.0)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "weight_kg must be positive and at most 10000".to_string(),
+        ));
+    }
+    if !(1..=24 * 30).contains(&payload.interval_hours) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "interval_hours must be between 1 and 720".to_string(),
+        ));
+    }
+    if !(1..=365).contains(&payload.duration_days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "duration_days must be between 1 and 365".to_string(),
+        ));
+    }
+    let hours = i64::from(payload.duration_days) * 24;
+    let interval = i64::from(payload.interval_hours);
+    let doses = (hours + interval - 1) / interval;
+    if doses > MAX_SCHEDULED_DOSES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "The course would schedule {} doses, at most {}",
+                doses, MAX_SCHEDULED_DOSES
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that an examination exists and is of the animal.
+async fn check_examination_of_animal(
+    client: &mut DbClient,
+    examination_id: Option<i32>,
+    animal_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    let Some(examination_id) = examination_id else {
+        return Ok(());
+    };
+
+    let query =
+        "SELECT fk_Animal_animal_id FROM Medical_Examination WHERE medical_examination_id = @P1";
+    let rows = fetch_rows(client, query, &[&examination_id]).await?;
+
+    match rows.first().and_then(|r| r.get::<i32, _>(0)) {
+        Some(id) if id == animal_id => Ok(()),
+        Some(_) => Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Medical examination {} is of another animal",
+                examination_id
+            ),
+        )),
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown medical_examination_id {}", examination_id),
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/animals/{id}/treatment-plans",
+    tag = "treatments",
+    summary = "Prescribe a treatment for an animal",
+    description = "Schedules a dose every `interval_hours` from `starts_at` for `duration_days`.",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = CreateTreatmentPlan,
+    responses(
+        (status = 201, description = "The plan with its scheduled doses", body = TreatmentPlan),
+        (status = 400, description = "Invalid plan", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn add_treatment_plan(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateTreatmentPlan>,
+) -> Result<(StatusCode, Json<TreatmentPlan>), (StatusCode, String)> {
+    let api_key = require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+    validate_plan(&payload, config.limits.max_description_length)?;
+    let starts_at = parse_timestamp("starts_at", payload.starts_at.as_deref())?
+        .unwrap_or_else(|| Utc::now().naive_utc());
+    let ends_at = starts_at + Duration::days(i64::from(payload.duration_days));
+    let dose = payload.dose_per_kg * payload.weight_kg;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_animal(&mut client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+    check_examination_of_animal(&mut client, payload.medical_examination_id, id).await?;
+
+    let new_id = in_transaction(&mut client, async |client| {
+        let new_id = next_id(client, "Treatment_Plan", "treatment_plan_id").await?;
+
+        let insert_query = r#"
+            INSERT INTO Treatment_Plan (treatment_plan_id, fk_Animal_animal_id, fk_Medical_Examination_id, drug, dose_per_kg, dose_unit, weight_kg, dose, route, interval_hours, duration_days, starts_at, ends_at, prescribed_by, notes, recorded_by, recorded_at)
+            VALUES (@P1, @P2, @P3, @P4, @P5, @P6, @P7, @P8, @P9, @P10, @P11, @P12, @P13, @P14, @P15, @P16, SYSUTCDATETIME())
+        "#;
+
+        client
+            .execute(
+                insert_query,
+                &[
+                    &new_id,
+                    &id,
+                    &payload.medical_examination_id,
+                    &payload.drug.trim(),
+                    &payload.dose_per_kg,
+                    &payload.dose_unit.trim(),
+                    &payload.weight_kg,
+                    &dose,
+                    &payload.route.as_str(),
+                    &payload.interval_hours,
+                    &payload.duration_days,
+                    &starts_at,
+                    &ends_at,
+                    &payload.prescribed_by.trim(),
+                    &payload.notes,
+                    &api_key.name.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Insert error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Insert error: {}", e),
+                )
+            })?;
+
+        let mut dose_id = next_id(client, "Treatment_Dose", "treatment_dose_id").await?;
+        let interval = Duration::hours(i64::from(payload.interval_hours));
+        let mut scheduled_at = starts_at;
+        while scheduled_at < ends_at {
+            client
+                .execute(
+                    "INSERT INTO Treatment_Dose (treatment_dose_id, fk_Treatment_Plan_id, scheduled_at) VALUES (@P1, @P2, @P3)",
+                    &[&dose_id, &new_id, &scheduled_at],
+                )
+                .await
+                .map_err(|e| {
+                    error!(error = %e, "Dose insert error");
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Dose insert error: {}", e),
+                    )
+                })?;
+            dose_id += 1;
+            scheduled_at += interval;
+        }
+
+        Ok(new_id)
+    })
+    .await?;
+
+    let created = find_plan(&mut client, new_id).await?.ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to fetch created treatment plan".to_string(),
+    ))?;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/animals/{id}/treatment-plans",
+    tag = "treatments",
+    summary = "List the treatment plans of an animal",
+    params(
+        ("id" = i32, Path, description = "Animal id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "Plans, latest first", body = Vec<TreatmentPlan>),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+        (status = 404, description = "Animal not found", body = String),
+    )
+)]
+pub async fn get_treatment_plans_of_animal(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TreatmentPlan>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    if find_animal(&mut client, id)
+        .await?
+        .is_none_or(|a| a.status == "removed")
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Animal with id {} not found", id),
+        ));
+    }
+
+    let query = format!(
+        "{} WHERE p.fk_Animal_animal_id = @P1 ORDER BY p.starts_at DESC, p.treatment_plan_id DESC",
+        PLAN_SELECT
+    );
+
+    Ok(Json(query_plans(&mut client, &query, &[&id], false).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/treatment-plans",
+    tag = "treatments",
+    summary = "List treatments across the zoo, by default the active ones",
+    params(
+        TreatmentPlanQuery,
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "Plans, next dose first", body = Vec<TreatmentPlan>),
+        (status = 400, description = "Unknown status", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+    )
+)]
+pub async fn get_treatment_plans(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Query(params): Query<TreatmentPlanQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TreatmentPlan>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+
+    let filter = match params.status.as_deref().map(str::trim) {
+        None | Some("") | Some("active") => "p.stopped_at IS NULL AND p.ends_at > SYSUTCDATETIME()",
+        Some("completed") => "p.stopped_at IS NULL AND p.ends_at <= SYSUTCDATETIME()",
+        Some("stopped") => "p.stopped_at IS NOT NULL",
+        Some("all") => "1 = 1",
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unknown status '{}'; expected active, completed, stopped or all",
+                    other
+                ),
+            ));
+        }
+    };
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = format!(
+        "{} WHERE {} ORDER BY p.starts_at, p.treatment_plan_id",
+        PLAN_SELECT, filter
+    );
+
+    let mut plans = query_plans(&mut client, &query, &[], false).await?;
+    // Plans with a dose coming up first, soonest on top
+    plans.sort_by_key(|p| (p.next_dose_at.is_none(), p.next_dose_at));
+
+    Ok(Json(plans))
+}
+
+#[utoipa::path(
+    get,
+    path = "/treatment-plans/{id}",
+    tag = "treatments",
+    summary = "Get treatment plan by id, with its doses",
+    params(
+        ("id" = i32, Path, description = "Treatment plan id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:read scope"),
+    ),
+    responses(
+        (status = 200, description = "The plan with its doses and warnings", body = TreatmentPlan),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:read scope", body = String),
+        (status = 404, description = "Treatment plan not found", body = String),
+    )
+)]
+pub async fn get_treatment_plan_by_id(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Json<TreatmentPlan>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_READ)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    find_plan(&mut client, id).await?.map(Json).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Treatment plan with id {} not found", id),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/treatment-plans/{id}/stop",
+    tag = "treatments",
+    summary = "Stop a treatment before its end",
+    params(
+        ("id" = i32, Path, description = "Treatment plan id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = StopTreatment,
+    responses(
+        (status = 200, description = "The plan; doses after now are cancelled", body = TreatmentPlan),
+        (status = 400, description = "Invalid reason", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Treatment plan not found", body = String),
+        (status = 409, description = "The treatment already ended or was stopped", body = String),
+    )
+)]
+pub async fn stop_treatment_plan(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<StopTreatment>,
+) -> Result<Json<TreatmentPlan>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+    check_length(
+        "reason",
+        payload.reason.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = "UPDATE Treatment_Plan SET stopped_at = SYSUTCDATETIME(), stop_reason = @P2 WHERE treatment_plan_id = @P1 AND stopped_at IS NULL AND ends_at > SYSUTCDATETIME()";
+
+    let rows_affected = client
+        .execute(query, &[&id, &payload.reason])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Update error: {}", e),
+            )
+        })?;
+
+    let plan = find_plan(&mut client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Treatment plan with id {} not found", id),
+    ))?;
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Treatment plan with id {} is already {}", id, plan.status),
+        ));
+    }
+
+    Ok(Json(plan))
+}
+
+#[utoipa::path(
+    post,
+    path = "/treatment-plans/{id}/doses",
+    tag = "treatments",
+    summary = "Record a dose given",
+    description = "The dose is recorded against the scheduled dose given, or the closest one still open within half an interval; otherwise as an extra dose. Doses given too close to another one, or of another amount than prescribed, are recorded with a warning.",
+    params(
+        ("id" = i32, Path, description = "Treatment plan id"),
+        ("X-API-Key" = String, Header, description = "Key with the medical:write scope"),
+    ),
+    request_body = AdministerDose,
+    responses(
+        (status = 201, description = "The dose recorded, with any warnings", body = AdministeredDose),
+        (status = 400, description = "Invalid dose", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the medical:write scope", body = String),
+        (status = 404, description = "Treatment plan or dose not found", body = String),
+        (status = 409, description = "The treatment was stopped before that time", body = String),
+    )
+)]
+pub async fn administer_dose(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Json(payload): Json<AdministerDose>,
+) -> Result<(StatusCode, Json<AdministeredDose>), (StatusCode, String)> {
+    require_scope(&config.auth, &headers, MEDICAL_WRITE)?;
+
+    if payload.administered_by.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "administered_by is required and cannot be empty".to_string(),
+        ));
+    }
+    check_length("administered_by", &payload.administered_by, VARCHAR_LENGTH)?;
+    check_length(
+        "notes",
+        payload.notes.as_deref().unwrap_or(""),
+        VARCHAR_LENGTH,
+    )?;
+    if payload
+        .amount
+        .is_some_and(|amount| !(amount.is_finite() && amount > 0.0))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "amount must be positive".to_string(),
+        ));
+    }
+    let now = Utc::now().naive_utc();
+    let administered_at =
+        parse_timestamp("administered_at", payload.administered_at.as_deref())?.unwrap_or(now);
+    // A few minutes of leeway for clocks that run ahead
+    if administered_at > now + Duration::minutes(5) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "administered_at can't be in the future".to_string(),
+        ));
+    }
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let plan = find_plan(&mut client, id).await?.ok_or((
+        StatusCode::NOT_FOUND,
+        format!("Treatment plan with id {} not found", id),
+    ))?;
+    if let Some(stopped_at) = plan.stopped_at
+        && administered_at > stopped_at
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Treatment plan with id {} was stopped at {}",
+                id, stopped_at
+            ),
+        ));
+    }
+
+    let doses = plan.doses.clone().unwrap_or_default();
+    let amount = payload.amount.unwrap_or(plan.dose);
+    let mut warnings = Vec::new();
+
+    let target = match payload.treatment_dose_id {
+        Some(dose_id) => {
+            let dose = doses
+                .iter()
+                .find(|d| d.treatment_dose_id == dose_id)
+                .ok_or((
+                    StatusCode::NOT_FOUND,
+                    format!("Dose {} of treatment plan {} not found", dose_id, id),
+                ))?;
+            if let Some(given_at) = dose.administered_at {
+                warnings.push(format!(
+                    "Dose {} was already given at {}; recorded as an extra dose",
+                    dose_id, given_at
+                ));
+                None
+            } else {
+                Some(dose_id)
+            }
+        }
+        None => {
+            let closest = doses
+                .iter()
+                .filter(|d| d.administered_at.is_none() && d.status != "cancelled")
+                .filter_map(|d| d.scheduled_at.map(|t| (d.treatment_dose_id, t)))
+                .filter(|(_, t)| (administered_at - *t).abs() <= tolerance(&plan))
+                .min_by_key(|(_, t)| (administered_at - *t).abs());
+            if closest.is_none() {
+                warnings.push(
+                    "No scheduled dose is open around this time; recorded as an extra dose"
+                        .to_string(),
+                );
+            }
+            closest.map(|(dose_id, _)| dose_id)
+        }
+    };
+
+    for given in doses.iter().filter(|d| d.administered_at.is_some()) {
+        let given_at = given.administered_at.unwrap_or_default();
+        if (administered_at - given_at).abs() < tolerance(&plan) {
+            warnings.push(format!(
+                "Possible double dose: dose {} was given at {}, {} minutes apart",
+                given.treatment_dose_id,
+                given_at,
+                (administered_at - given_at).num_minutes().abs()
+            ));
+        }
+    }
+    if (amount - plan.dose).abs() > plan.dose * AMOUNT_TOLERANCE {
+        warnings.push(format!(
+            "Amount {} {} differs from the prescribed {} {}",
+            amount, plan.dose_unit, plan.dose, plan.dose_unit
+        ));
+    }
+
+    let (dose_id, stock_warnings) = in_transaction(&mut client, async |client| {
+        let dose_id = match target {
+            Some(dose_id) => {
+                let query = "UPDATE Treatment_Dose SET administered_at = @P2, administered_by = @P3, amount = @P4, notes = @P5 WHERE treatment_dose_id = @P1 AND administered_at IS NULL";
+                let rows_affected = client
+                    .execute(
+                        query,
+                        &[
+                            &dose_id,
+                            &administered_at,
+                            &payload.administered_by.trim(),
+                            &amount,
+                            &payload.notes,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        error!(error = %e, "Update error");
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Update error: {}", e),
+                        )
+                    })?;
+                if rows_affected.total() == 0 {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        format!("Dose {} was recorded concurrently", dose_id),
+                    ));
+                }
+                dose_id
+            }
+            None => {
+                let dose_id = next_id(client, "Treatment_Dose", "treatment_dose_id").await?;
+                let query = "INSERT INTO Treatment_Dose (treatment_dose_id, fk_Treatment_Plan_id, scheduled_at, administered_at, administered_by, amount, notes) VALUES (@P1, @P2, NULL, @P3, @P4, @P5, @P6)";
+                client
+                    .execute(
+                        query,
+                        &[
+                            &dose_id,
+                            &id,
+                            &administered_at,
+                            &payload.administered_by.trim(),
+                            &amount,
+                            &payload.notes,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| {
+                        error!(error = %e, "Insert error");
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Insert error: {}", e),
+                        )
+                    })?;
+                dose_id
+            }
+        };
+
+        let stock_warnings = consume_for_dose(
+            client,
+            &plan.drug,
+            &plan.dose_unit,
+            amount,
+            dose_id,
+            payload.administered_by.trim(),
+        )
+        .await?;
+
+        Ok((dose_id, stock_warnings))
+    })
+    .await?;
+    warnings.extend(stock_warnings);
+
+    let dose = find_plan(&mut client, id)
+        .await?
+        .and_then(|plan| plan.doses)
+        .and_then(|doses| doses.into_iter().find(|d| d.treatment_dose_id == dose_id))
+        .ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to fetch recorded dose".to_string(),
+        ))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AdministeredDose { dose, warnings }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+            .and_then(|d| d.and_hms_opt(hour, minute, 0))
+            .unwrap()
+    }
+
+    /// Every 8 hours for a day from midnight.
+    fn plan() -> TreatmentPlan {
+        TreatmentPlan {
+            treatment_plan_id: 1,
+            animal_id: 1,
+            animal_name: "Rex".to_string(),
+            medical_examination_id: None,
+            drug: "Meloxicam".to_string(),
+            dose_per_kg: 0.1,
+            dose_unit: "mg".to_string(),
+            weight_kg: 20.0,
+            dose: 2.0,
+            route: "oral".to_string(),
+            interval_hours: 8,
+            duration_days: 1,
+            starts_at: Some(at(0, 0)),
+            ends_at: Some(at(0, 0) + Duration::days(1)),
+            prescribed_by: "Dr. Vet".to_string(),
+            notes: None,
+            status: String::new(),
+            stopped_at: None,
+            stop_reason: None,
+            next_dose_at: None,
+            warnings: Vec::new(),
+            doses: None,
+        }
+    }
+
+    fn dose(
+        id: i32,
+        scheduled_at: Option<NaiveDateTime>,
+        administered_at: Option<NaiveDateTime>,
+    ) -> TreatmentDose {
+        TreatmentDose {
+            treatment_dose_id: id,
+            treatment_plan_id: 1,
+            scheduled_at,
+            status: String::new(),
+            administered_at,
+            administered_by: administered_at.map(|_| "Keeper".to_string()),
+            amount: administered_at.map(|_| 2.0),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn tolerance_is_half_the_interval() {
+        assert_eq!(tolerance(&plan()), Duration::hours(4));
+        let mut daily = plan();
+        daily.interval_hours = 24;
+        assert_eq!(tolerance(&daily), Duration::hours(12));
+    }
+
+    #[test]
+    fn dose_status_follows_the_clock() {
+        let plan = plan();
+        let open = dose(2, Some(at(8, 0)), None);
+        assert_eq!(dose_status(&plan, &open, at(3, 0)), "scheduled");
+        assert_eq!(dose_status(&plan, &open, at(4, 0)), "due");
+        assert_eq!(dose_status(&plan, &open, at(12, 0)), "due");
+        assert_eq!(dose_status(&plan, &open, at(12, 1)), "missed");
+
+        let given = dose(2, Some(at(8, 0)), Some(at(8, 5)));
+        assert_eq!(dose_status(&plan, &given, at(20, 0)), "given");
+        let extra = dose(4, None, Some(at(9, 0)));
+        assert_eq!(dose_status(&plan, &extra, at(20, 0)), "extra");
+    }
+
+    #[test]
+    fn dose_status_cancels_doses_after_a_stop() {
+        let mut plan = plan();
+        plan.stopped_at = Some(at(6, 0));
+        assert_eq!(
+            dose_status(&plan, &dose(1, Some(at(0, 0)), None), at(20, 0)),
+            "missed"
+        );
+        assert_eq!(
+            dose_status(&plan, &dose(2, Some(at(8, 0)), None), at(20, 0)),
+            "cancelled"
+        );
+    }
+
+    #[test]
+    fn summarize_plan_warns_about_missed_doses() {
+        let mut plan = plan();
+        let doses = vec![
+            dose(1, Some(at(0, 0)), None),
+            dose(2, Some(at(8, 0)), Some(at(8, 10))),
+            dose(3, Some(at(16, 0)), None),
+        ];
+        summarize_plan(&mut plan, doses, at(9, 0));
+
+        assert_eq!(plan.status, "active");
+        assert_eq!(plan.next_dose_at, Some(at(16, 0)));
+        assert_eq!(
+            plan.warnings,
+            vec![format!("Dose 1 scheduled at {} was missed", at(0, 0))]
+        );
+        let statuses: Vec<&str> = plan
+            .doses
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|d| d.status.as_str())
+            .collect();
+        assert_eq!(statuses, ["missed", "given", "scheduled"]);
+    }
+
+    #[test]
+    fn summarize_plan_warns_about_double_doses() {
+        let mut plan = plan();
+        let doses = vec![
+            dose(1, Some(at(0, 0)), Some(at(0, 0))),
+            dose(2, Some(at(8, 0)), Some(at(8, 0))),
+            dose(4, None, Some(at(9, 30))),
+        ];
+        summarize_plan(&mut plan, doses, at(10, 0));
+
+        assert_eq!(
+            plan.warnings,
+            vec!["Doses 2 and 4 were given 90 minutes apart; the interval is 8 hours".to_string()]
+        );
+    }
+
+    #[test]
+    fn summarize_plan_completes_a_plan_after_its_end() {
+        let mut plan = plan();
+        let doses = vec![dose(1, Some(at(0, 0)), Some(at(0, 0)))];
+        summarize_plan(&mut plan, doses, at(0, 0) + Duration::days(2));
+
+        assert_eq!(plan.status, "completed");
+        assert_eq!(plan.next_dose_at, None);
+        assert!(plan.warnings.is_empty());
+    }
+}
@@ -0,0 +1,447 @@
+use crate::auth::{WEBHOOKS_MANAGE, require_scope};
+use crate::config::Config;
+use crate::db::Database;
+use crate::models::{
+    CreateWebhookSubscription, WEBHOOK_EVENT_TYPES, WebhookDelivery, WebhookSubscription,
+};
+use crate::validation::check_length;
+use crate::webhooks;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde_json::json;
+use std::sync::Arc;
+use tiberius::Row;
+use tracing::error;
+
+fn subscription_from_row(row: &Row) -> WebhookSubscription {
+    WebhookSubscription {
+        webhook_subscription_id: row.get::<i32, _>(0).unwrap_or(0),
+        target_url: row.get::<&str, _>(1).unwrap_or("").to_string(),
+        event_types: row
+            .get::<&str, _>(2)
+            .unwrap_or("")
+            .split(',')
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_string())
+            .collect(),
+        is_active: row.get::<bool, _>(3).unwrap_or(false),
+        created_at: row.get(4),
+        secret: None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    summary = "List webhook subscriptions",
+    params(
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    responses(
+        (status = 200, description = "Active subscriptions", body = Vec<WebhookSubscription>),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+    )
+)]
+pub async fn get_webhook_subscriptions(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<WebhookSubscription>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = "SELECT webhook_subscription_id, target_url, event_types, is_active, created_at FROM Webhook_Subscription WHERE is_active = 1 ORDER BY webhook_subscription_id";
+
+    let stream = client.query(query, &[]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    Ok(Json(rows.iter().map(subscription_from_row).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    summary = "Add webhook subscription",
+    params(
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    request_body = CreateWebhookSubscription,
+    responses(
+        (status = 201, description = "Subscription created; the secret is only returned here", body = WebhookSubscription),
+        (status = 400, description = "Invalid URL or event type", body = String),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+    )
+)]
+pub async fn add_webhook_subscription(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateWebhookSubscription>,
+) -> Result<(StatusCode, Json<WebhookSubscription>), (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    // Sizes of the Webhook_Subscription columns
+    check_length("target_url", &payload.target_url, 2048)?;
+    check_length("secret", payload.secret.as_deref().unwrap_or(""), 255)?;
+    let url = reqwest::Url::parse(payload.target_url.trim()).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            "target_url must be an absolute URL".to_string(),
+        )
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "target_url must use http or https".to_string(),
+        ));
+    }
+    if payload.event_types.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "At least one event type is required".to_string(),
+        ));
+    }
+    if let Some(unknown) = payload
+        .event_types
+        .iter()
+        .find(|t| !WEBHOOK_EVENT_TYPES.contains(&t.as_str()))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown event type '{}'; expected one of {}",
+                unknown,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            ),
+        ));
+    }
+
+    let secret = match payload.secret {
+        Some(s) if !s.trim().is_empty() => s,
+        _ => format!("whsec_{}", uuid::Uuid::new_v4().simple()),
+    };
+    let event_types = payload.event_types.join(",");
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let id_query =
+        "SELECT ISNULL(MAX(webhook_subscription_id),0)+1 AS next_id FROM Webhook_Subscription";
+    let id_stream = client.query(id_query, &[]).await.map_err(|e| {
+        error!(error = %e, "ID query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID query error: {}", e),
+        )
+    })?;
+    let id_rows = id_stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "ID result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("ID result error: {}", e),
+        )
+    })?;
+    let new_id = id_rows.first().and_then(|r| r.get::<i32, _>(0)).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Failed to compute next id".to_string(),
+    ))?;
+
+    let insert_query = r#"
+        INSERT INTO Webhook_Subscription (webhook_subscription_id, target_url, secret, event_types, is_active, created_at)
+        VALUES (@P1, @P2, @P3, @P4, 1, SYSUTCDATETIME())
+    "#;
+
+    client
+        .execute(
+            insert_query,
+            &[&new_id, &url.as_str(), &secret, &event_types],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Insert error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Insert error: {}", e),
+            )
+        })?;
+
+    let created = WebhookSubscription {
+        webhook_subscription_id: new_id,
+        target_url: url.to_string(),
+        event_types: payload.event_types,
+        is_active: true,
+        created_at: None,
+        secret: Some(secret),
+    };
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    summary = "Remove webhook subscription",
+    params(
+        ("id" = i32, Path, description = "Subscription id"),
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    responses(
+        (status = 204, description = "Subscription removed"),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+        (status = 404, description = "Subscription not found", body = String),
+    )
+)]
+pub async fn delete_webhook_subscription(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    // Subscriptions are deactivated rather than deleted to keep their
+    // delivery history; pending deliveries are dropped with them.
+    let query = r#"
+        UPDATE Webhook_Subscription SET is_active = 0 WHERE webhook_subscription_id = @P1 AND is_active = 1;
+        UPDATE Webhook_Delivery SET status = 'cancelled', next_attempt_at = NULL
+        WHERE fk_Webhook_Subscription_id = @P1 AND status = 'pending';
+    "#;
+
+    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Delete webhook error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Delete webhook error: {}", e),
+        )
+    })?;
+
+    if rows_affected.rows_affected().first().copied().unwrap_or(0) == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Webhook subscription with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    summary = "Delivery history of a subscription",
+    params(
+        ("id" = i32, Path, description = "Subscription id"),
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    responses(
+        (status = 200, description = "Deliveries, newest first", body = Vec<WebhookDelivery>),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+    )
+)]
+pub async fn get_webhook_deliveries(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<WebhookDelivery>>, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = r#"
+        SELECT webhook_delivery_id, fk_Webhook_Subscription_id, event_type, status, attempt_count,
+               next_attempt_at, last_attempt_at, last_response_status, last_error, created_at, delivered_at
+        FROM Webhook_Delivery
+        WHERE fk_Webhook_Subscription_id = @P1
+        ORDER BY webhook_delivery_id DESC
+    "#;
+
+    let stream = client.query(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Query error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Query error: {}", e),
+        )
+    })?;
+
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Result error: {}", e),
+        )
+    })?;
+
+    let deliveries = rows
+        .iter()
+        .map(|row| WebhookDelivery {
+            webhook_delivery_id: row.get::<i32, _>(0).unwrap_or(0),
+            fk_webhook_subscription_id: row.get::<i32, _>(1).unwrap_or(0),
+            event_type: row.get::<&str, _>(2).unwrap_or("").to_string(),
+            status: row.get::<&str, _>(3).unwrap_or("").to_string(),
+            attempt_count: row.get::<i32, _>(4).unwrap_or(0),
+            next_attempt_at: row.get(5),
+            last_attempt_at: row.get(6),
+            last_response_status: row.get::<i32, _>(7),
+            last_error: row.get::<&str, _>(8).map(|s| s.to_string()),
+            created_at: row.get(9),
+            delivered_at: row.get(10),
+        })
+        .collect();
+
+    Ok(Json(deliveries))
+}
+
+/// Queue a `ping` delivery to one subscription, to check the receiver end to end.
+#[utoipa::path(
+    post,
+    path = "/webhooks/{id}/ping",
+    tag = "webhooks",
+    summary = "Send a test delivery",
+    params(
+        ("id" = i32, Path, description = "Subscription id"),
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    responses(
+        (status = 202, description = "Ping queued"),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+        (status = 404, description = "Subscription not found", body = String),
+    )
+)]
+pub async fn ping_webhook_subscription(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    let queued = webhooks::enqueue(&db, "ping", json!({ "webhook_subscription_id": id }), Some(id))
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Webhook enqueue error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Webhook enqueue error: {}", e),
+            )
+        })?;
+
+    if queued == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Webhook subscription with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Put a dead-lettered delivery back in the queue with a fresh retry budget.
+#[utoipa::path(
+    post,
+    path = "/webhook-deliveries/{id}/redeliver",
+    tag = "webhooks",
+    summary = "Retry a dead-lettered delivery",
+    params(
+        ("id" = i32, Path, description = "Delivery id"),
+        ("X-API-Key" = String, Header, description = "Key with the webhooks:manage scope"),
+    ),
+    responses(
+        (status = 202, description = "Delivery queued again"),
+        (status = 401, description = "No known API key", body = String),
+        (status = 403, description = "The key lacks the webhooks:manage scope", body = String),
+        (status = 404, description = "Dead-lettered delivery not found", body = String),
+    )
+)]
+pub async fn redeliver_webhook(
+    State(db): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_scope(&config.auth, &headers, WEBHOOKS_MANAGE)?;
+
+    let mut client = db.connect().await.map_err(|e| {
+        error!(error = %e, "Database connection error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database connection error: {}", e),
+        )
+    })?;
+
+    let query = r#"
+        UPDATE d
+        SET status = 'pending', attempt_count = 0, next_attempt_at = SYSUTCDATETIME()
+        FROM Webhook_Delivery d
+        JOIN Webhook_Subscription s ON s.webhook_subscription_id = d.fk_Webhook_Subscription_id
+        WHERE d.webhook_delivery_id = @P1 AND d.status = 'dead' AND s.is_active = 1
+    "#;
+
+    let rows_affected = client.execute(query, &[&id]).await.map_err(|e| {
+        error!(error = %e, "Redeliver error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Redeliver error: {}", e),
+        )
+    })?;
+
+    if rows_affected.total() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Dead-lettered delivery with id {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
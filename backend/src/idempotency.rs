@@ -0,0 +1,298 @@
+use crate::db::DbClient;
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// SQL Server error number for a primary key violation
+const PRIMARY_KEY_VIOLATION: u32 = 2627;
+
+/// A key reserved for the request currently being processed.
+pub struct Reservation {
+    key: String,
+    endpoint: &'static str,
+}
+
+pub enum Begin {
+    /// No key was sent, or the key is new: run the handler.
+    Proceed(Option<Reservation>),
+    /// The key was already used for the same request: send this instead.
+    Replay(Response),
+}
+
+fn fingerprint<T: Serialize>(payload: &T) -> Result<String, (StatusCode, String)> {
+    let bytes = serde_json::to_vec(payload).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Fingerprint error: {}", e),
+        )
+    })?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+/// Look up the `Idempotency-Key` of a create request.
+///
+/// A first use reserves the key so concurrent retries are rejected with 409
+/// until the original finishes. A later use with the same body replays the
+/// stored response; with a different body it is rejected with 422. Stored
+/// responses expire after `ttl_seconds`.
+pub async fn begin<T: Serialize>(
+    client: &mut DbClient,
+    headers: &HeaderMap,
+    endpoint: &'static str,
+    payload: &T,
+    ttl_seconds: i32,
+) -> Result<Begin, (StatusCode, String)> {
+    let Some(key) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(Begin::Proceed(None));
+    };
+
+    let key = key
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LENGTH)
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Idempotency-Key must be between 1 and {} visible ASCII characters",
+                MAX_KEY_LENGTH
+            ),
+        ))?
+        .to_string();
+    let request_fingerprint = fingerprint(payload)?;
+
+    client
+        .execute(
+            "DELETE FROM Idempotency_Key WHERE expires_at < SYSUTCDATETIME()",
+            &[],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Idempotency cleanup error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Idempotency cleanup error: {}", e),
+            )
+        })?;
+
+    let lookup_query = "SELECT request_fingerprint, status_code, response_body FROM Idempotency_Key WHERE idempotency_key = @P1 AND endpoint = @P2";
+    let stream = client
+        .query(lookup_query, &[&key, &endpoint])
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Idempotency query error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Idempotency query error: {}", e),
+            )
+        })?;
+    let rows = stream.into_first_result().await.map_err(|e| {
+        error!(error = %e, "Idempotency result error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Idempotency result error: {}", e),
+        )
+    })?;
+
+    if let Some(row) = rows.first() {
+        return existing(
+            &request_fingerprint,
+            row.get::<&str, _>(0).unwrap_or(""),
+            row.get::<i32, _>(1),
+            row.get::<&str, _>(2),
+        );
+    }
+
+    let reserve_query = r#"
+        INSERT INTO Idempotency_Key (idempotency_key, endpoint, request_fingerprint, status_code, response_body, created_at, expires_at)
+        VALUES (@P1, @P2, @P3, NULL, NULL, SYSUTCDATETIME(), DATEADD(SECOND, @P4, SYSUTCDATETIME()))
+    "#;
+    match client
+        .execute(
+            reserve_query,
+            &[&key, &endpoint, &request_fingerprint, &ttl_seconds],
+        )
+        .await
+    {
+        Ok(_) => Ok(Begin::Proceed(Some(Reservation { key, endpoint }))),
+        // Another retry reserved the same key between our lookup and insert
+        Err(e) if e.code() == Some(PRIMARY_KEY_VIOLATION) => Err(in_progress()),
+        Err(e) => {
+            error!(error = %e, "Idempotency reserve error");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Idempotency reserve error: {}", e),
+            ))
+        }
+    }
+}
+
+fn existing(
+    request_fingerprint: &str,
+    stored_fingerprint: &str,
+    status_code: Option<i32>,
+    response_body: Option<&str>,
+) -> Result<Begin, (StatusCode, String)> {
+    if stored_fingerprint != request_fingerprint {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key was already used with a different request body".to_string(),
+        ));
+    }
+
+    let (Some(status_code), Some(body)) = (status_code, response_body) else {
+        return Err(in_progress());
+    };
+
+    let status = u16::try_from(status_code)
+        .ok()
+        .and_then(|s| StatusCode::from_u16(s).ok())
+        .unwrap_or(StatusCode::OK);
+
+    Ok(Begin::Replay(
+        (
+            status,
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                ),
+                (
+                    header::HeaderName::from_static("idempotent-replayed"),
+                    HeaderValue::from_static("true"),
+                ),
+            ],
+            body.to_string(),
+        )
+            .into_response(),
+    ))
+}
+
+fn in_progress() -> (StatusCode, String) {
+    (
+        StatusCode::CONFLICT,
+        "A request with this Idempotency-Key is still being processed".to_string(),
+    )
+}
+
+/// Store a successful response against the reserved key, for replay.
+///
+/// Call it in the same transaction as the write it answers, so a committed
+/// write always leaves a response to replay.
+pub async fn store<T: Serialize>(
+    client: &mut DbClient,
+    reservation: Option<&Reservation>,
+    status: StatusCode,
+    body: &T,
+) -> Result<(), (StatusCode, String)> {
+    let Some(reservation) = reservation else {
+        return Ok(());
+    };
+
+    let body = serde_json::to_string(body).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Idempotency store error: {}", e),
+        )
+    })?;
+    client
+        .execute(
+            "UPDATE Idempotency_Key SET status_code = @P3, response_body = @P4 WHERE idempotency_key = @P1 AND endpoint = @P2",
+            &[
+                &reservation.key,
+                &reservation.endpoint,
+                &(status.as_u16() as i32),
+                &body,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Idempotency store error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Idempotency store error: {}", e),
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Release the reserved key after the request failed, so the client can
+/// retry the same request once the problem is fixed.
+///
+/// A key that can't be released only blocks retries until it expires, so
+/// the error is logged rather than hiding the one the request failed with.
+pub async fn release(client: &mut DbClient, reservation: Option<Reservation>) {
+    let Some(reservation) = reservation else {
+        return;
+    };
+
+    if let Err(e) = client
+        .execute(
+            "DELETE FROM Idempotency_Key WHERE idempotency_key = @P1 AND endpoint = @P2",
+            &[&reservation.key, &reservation.endpoint],
+        )
+        .await
+    {
+        error!(error = %e, "Idempotency release error");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn the_same_body_has_the_same_fingerprint() {
+        let first = fingerprint(&json!({"name": "Kiara", "specie": "Lion"})).unwrap();
+        let again = fingerprint(&json!({"name": "Kiara", "specie": "Lion"})).unwrap();
+        let other = fingerprint(&json!({"name": "Kiara", "specie": "Tiger"})).unwrap();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(first.len(), 64);
+    }
+
+    #[test]
+    fn a_key_reused_with_another_body_is_rejected() {
+        let Err((status, _)) = existing("abc", "def", Some(201), Some("{}")) else {
+            panic!("a different body must not be replayed");
+        };
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn a_key_without_a_stored_response_is_in_progress() {
+        for (status_code, body) in [(None, None), (Some(201), None), (None, Some("{}"))] {
+            let Err((status, _)) = existing("abc", "abc", status_code, body) else {
+                panic!("an unfinished request must not be replayed");
+            };
+            assert_eq!(status, StatusCode::CONFLICT);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_stored_response_is_replayed() {
+        let Ok(Begin::Replay(response)) = existing("abc", "abc", Some(201), Some(r#"{"id":7}"#))
+        else {
+            panic!("a finished request must be replayed");
+        };
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()["idempotent-replayed"], "true");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], br#"{"id":7}"#);
+    }
+}
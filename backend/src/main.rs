@@ -1,80 +1,193 @@
-use axum::routing::{Router, get, post, put, delete};
-use tower_http::cors::CorsLayer;
+use utoipa_swagger_ui::SwaggerUi;
 
+pub mod auth;
+pub mod config;
 pub mod db;
+pub mod etag;
+pub mod events;
 pub mod handlers;
+pub mod idempotency;
+pub mod metrics;
 pub mod models;
+pub mod rate_limit;
+pub mod routes;
+pub mod security;
+pub mod shutdown;
+pub mod state;
+pub mod telemetry;
+pub mod tls;
+pub mod validation;
+pub mod webhooks;
 
+use crate::config::{Cli, Command, Config, ConfigCommand};
 use crate::db::Database;
-use crate::handlers::*;
+use crate::events::EventBus;
+use crate::rate_limit::{ClientAddr, RateLimiter};
+use crate::state::AppState;
+use axum::extract::DefaultBodyLimit;
+use clap::Parser;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() {
-    println!("Starting backend server...");
-
     // Load environment variables from .env file
     dotenv::dotenv().ok();
 
-    // Initialize database configuration
-    let database = Database::new().expect("Failed to create database configuration");
-
-    // Test database connection
-    database
-        .test_connection()
-        .await
-        .expect("Failed to connect to database");
-
-    let cors = CorsLayer::permissive();
-
-    let animals_router = Router::new()
-        .route("/list", get(get_animals))
-        .route("/add", post(add_animal))
-        .route("/animals/{id}", get(get_animal_by_id))
-        .route("/deactivate/{id}", post(deactivate_animal))
-        .route("/update/{id}", put(update_animal))
-        .route("/delete/{id}", delete(delete_animal));
-
-    let cares_router = Router::new()
-        .route("/list", get(get_cares))
-        .route("/by-id/{id}", get(get_care_by_id))
-        .route("/add", post(add_care))
-        .route("/update/{id}", put(update_care))
-        .route("/delete/{id}", delete(delete_care));
-
-    let animal_cares_router = Router::new()
-        .route("/list", get(get_animal_cares))
-        .route("/by-id/{id}", get(get_animal_care_by_id))
-        .route("/by-animal/by-id/{id}", get(get_animal_care_by_animal_id))
-        .route("/add", post(add_animal_care));
-
-    let app = Router::new()
-        .route("/message", get(initial_page))
-        .nest("/animals", animals_router)
-        .nest("/cares", cares_router)
-        .nest("/animal-cares", animal_cares_router)
-        .with_state(database)
-        .layer(cors);
-
-    println!("Binding to 0.0.0.0:3000...");
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    println!("Server listening on port 3000");
-    println!("Available endpoints:");
-    println!("  GET    /message                         - Test endpoint");
-    println!("  GET    /animals/list                    - List all animals (active)");
-    println!("  GET    /animals/animals/id              - Get animal by ID");
-    println!("  POST   /animals/add                     - Add new animal");
-    println!("  PUT    /animals/update/id               - Update animal");
-    println!("  POST   /animals/deactivate/id           - Soft delete animal");
-    println!("  DELETE /animals/delete/id               - Hard delete animal");
-    println!("  GET    /cares/list                      - List all cares");
-    println!("  GET    /cares/by-id/id                  - Get care by ID");
-    println!("  POST   /cares/add                       - Add new care");
-    println!("  PUT    /cares/update/id                 - Update care");
-    println!("  DELETE /cares/delete/id                 - Delete care");
-    println!("  GET    /animal-cares/list               - List all animal-care relations");
-    println!("  GET    /animal-cares/by-id/id           - Get animal-care relation by ID");
-    println!("  GET    /animal-cares/by-animal/by-id/id - Get all cares for animal by animal ID");
-    println!("  POST   /animal-cares/add                - Add animal-care relation");
-
-    axum::serve(listener, app).await.unwrap();
+    let cli = Cli::parse();
+    // Logging isn't set up until the configuration is known, so problems with
+    // it go straight to stderr
+    let config = match Config::load(&cli) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("Invalid configuration:");
+            for e in errors {
+                eprintln!("  - {}", e);
+            }
+            std::process::exit(2);
+        }
+    };
+
+    match cli.command {
+        Some(Command::Config(ConfigCommand::Check)) => {
+            println!("Configuration is valid:\n");
+            print!("{}", config.redacted_toml());
+        }
+        Some(Command::Serve) | None => serve(config).await,
+    }
+}
+
+async fn serve(config: Config) {
+    telemetry::init(&config.logging);
+    info!("Starting backend server...");
+
+    let config = Arc::new(config);
+    let database = Database::new(&config.database);
+
+    let shutdown = CancellationToken::new();
+
+    // Serve right away; readiness reports the database until it comes up
+    tokio::spawn({
+        let database = database.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            tokio::select! {
+                _ = database.wait_until_available() => {}
+                _ = shutdown.cancelled() => {}
+            }
+        }
+    });
+
+    let events = EventBus::new();
+    let webhook_tasks = config
+        .features
+        .webhook_delivery
+        .then(|| webhooks::spawn(database.clone(), events.clone(), shutdown.clone()));
+
+    let (router, api) = routes::router(&config.features).split_for_parts();
+    let endpoints = routes::endpoints(&api);
+
+    let mut app = router;
+    if config.features.legacy_routes {
+        app = app.merge(routes::legacy::router());
+    }
+    app = app.layer(DefaultBodyLimit::max(config.limits.max_body_bytes));
+    if config.rate_limit.enabled {
+        let limiter = Arc::new(RateLimiter::new(&config.rate_limit, &config.auth));
+        app = app.layer(axum::middleware::from_fn_with_state(limiter, rate_limit::limit));
+    }
+    if config.features.metrics {
+        app = app.layer(axum::middleware::from_fn(metrics::track_http));
+    }
+    let mut app = telemetry::layers(app);
+    if config.features.api_docs {
+        let docs = axum::Router::from(SwaggerUi::new("/docs").url("/openapi.json", api));
+        app = app.merge(security::docs_headers(docs));
+    }
+    let app = app
+        .with_state(AppState {
+            config: config.clone(),
+            db: database,
+            events,
+            shutdown: shutdown.clone(),
+        })
+        .layer(security::cors_layer(&config.cors));
+    let app = security::headers(app, &config.security, config.https_only());
+
+    let address = format!("{}:{}", config.server.host, config.server.port);
+    info!("Binding to {}...", address);
+    let listener = match tokio::net::TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(address = %address, error = %e, "Could not bind the server address");
+            std::process::exit(1);
+        }
+    };
+    let scheme = if config.server.tls.enabled { "https" } else { "http" };
+    info!("Server listening on {}://{}, {} endpoints", scheme, address, endpoints.len());
+    for (method, path, summary) in endpoints {
+        debug!(method, path = %path, "{}", summary);
+    }
+    if config.features.api_docs {
+        info!("API docs at /docs, spec at /openapi.json");
+    }
+    if config.features.legacy_routes {
+        info!("The pre-v1 routes (/animals/list, /cares/by-id/id, ...) still answer, marked with a Deprecation header");
+    }
+
+    let mut server = if config.server.tls.enabled {
+        let listener = match tls::TlsListener::new(listener, &config.server.tls, shutdown.clone()) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(error = %e, "Could not set up TLS");
+                std::process::exit(1);
+            }
+        };
+        tokio::spawn(
+            axum::serve(listener, app.into_make_service_with_connect_info::<ClientAddr>())
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                .into_future(),
+        )
+    } else {
+        tokio::spawn(
+            axum::serve(listener, app.into_make_service_with_connect_info::<ClientAddr>())
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                .into_future(),
+        )
+    };
+
+    tokio::select! {
+        result = &mut server => {
+            // The server only stops by itself on an I/O error
+            if let Ok(Err(e)) = result {
+                error!(error = %e, "Server error");
+            }
+            shutdown.cancel();
+        }
+        _ = shutdown::signal() => {
+            let drain = Duration::from_secs(config.server.shutdown_drain_seconds);
+            info!("Shutting down; draining in-flight requests for up to {}s", drain.as_secs());
+            // Stops accepting connections, ends event streams and stops the
+            // webhook sender after its current batch
+            shutdown.cancel();
+
+            let deadline = tokio::time::Instant::now() + drain;
+            let drained = tokio::time::timeout_at(deadline, &mut server).await.is_ok()
+                && tokio::time::timeout_at(deadline, async {
+                    if let Some((dispatcher, sender)) = webhook_tasks {
+                        let _ = sender.await;
+                        let _ = dispatcher.await;
+                    }
+                })
+                .await
+                .is_ok();
+            if drained {
+                info!("Shutdown complete");
+            } else {
+                warn!("Drain timeout reached; abandoning remaining requests and tasks");
+            }
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+/// Every metric the backend exports on `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    db_connect_duration: Histogram,
+    db_query_duration: HistogramVec,
+    errors: IntCounterVec,
+    pub active_animals: IntGauge,
+    pub overdue_cares: IntGauge,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry =
+            Registry::new_custom(Some("zoo".to_string()), None).expect("metric prefix is valid");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .expect("metric is valid");
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time to produce the response head, by route",
+            ),
+            &["method", "route"],
+        )
+        .expect("metric is valid");
+        let db_connect_duration = Histogram::with_opts(HistogramOpts::new(
+            "db_connect_duration_seconds",
+            "Time to open a SQL Server connection",
+        ))
+        .expect("metric is valid");
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Time until SQL Server starts answering a statement",
+            ),
+            &["operation"],
+        )
+        .expect("metric is valid");
+        let errors = IntCounterVec::new(Opts::new("errors_total", "Errors by type"), &["type"])
+            .expect("metric is valid");
+        let active_animals =
+            IntGauge::new("animals_active", "Animals currently active").expect("metric is valid");
+        let overdue_cares = IntGauge::new(
+            "cares_overdue",
+            "Cares of active animals not given within their frequency",
+        )
+        .expect("metric is valid");
+
+        registry
+            .register(Box::new(http_requests.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(http_duration.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(db_connect_duration.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(db_query_duration.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(errors.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(active_animals.clone()))
+            .expect("registered once");
+        registry
+            .register(Box::new(overdue_cares.clone()))
+            .expect("registered once");
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            db_connect_duration,
+            db_query_duration,
+            errors,
+            active_animals,
+            overdue_cares,
+        }
+    }
+
+    pub fn observe_db_connect(&self, started: Instant, failed: bool) {
+        self.db_connect_duration
+            .observe(started.elapsed().as_secs_f64());
+        if failed {
+            self.record_error("db_connect");
+        }
+    }
+
+    /// `operation` is `query` or `execute`.
+    pub fn observe_db_statement(&self, operation: &str, started: Instant, failed: bool) {
+        self.db_query_duration
+            .with_label_values(&[operation])
+            .observe(started.elapsed().as_secs_f64());
+        if failed {
+            self.record_error("db_statement");
+        }
+    }
+
+    pub fn record_error(&self, kind: &str) {
+        self.errors.with_label_values(&[kind]).inc();
+    }
+
+    /// Everything registered, in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!(error = %e, "Metrics encode error");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Counts and times every request under its route template (`/api/v1/animals/{id}`
+/// rather than the concrete path, to keep label cardinality bounded).
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    METRICS
+        .http_requests
+        .with_label_values(&[method.as_str(), route.as_str(), status.as_str()])
+        .inc();
+    METRICS
+        .http_duration
+        .with_label_values(&[method.as_str(), route.as_str()])
+        .observe(started.elapsed().as_secs_f64());
+    if status.is_server_error() {
+        METRICS.record_error("http_5xx");
+    } else if status.is_client_error() {
+        METRICS.record_error("http_4xx");
+    }
+
+    response
+}
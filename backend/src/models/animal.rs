@@ -1,33 +1,196 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lifecycle statuses of an animal. `removed` is for records created by
+/// mistake.
+pub const ANIMAL_STATUSES: &[&str] = &[
+    "arrived",
+    "born",
+    "active",
+    "on_loan",
+    "transferred",
+    "released",
+    "deceased",
+    "removed",
+];
+
+/// Statuses of animals that are still part of the collection and can be
+/// changed; the rest are kept for history only.
+pub const IN_COLLECTION_STATUSES: &[&str] = &["arrived", "born", "active", "on_loan"];
+
+/// Statuses a new animal can be registered with.
+pub const INITIAL_STATUSES: &[&str] = &["arrived", "born", "active"];
+
+/// Whether an animal in status `from` may move to status `to`.
+pub fn status_transition_allowed(from: &str, to: &str) -> bool {
+    let allowed: &[&str] = match from {
+        "arrived" | "born" => &["active", "transferred", "deceased", "removed"],
+        "active" => &["on_loan", "transferred", "released", "deceased", "removed"],
+        "on_loan" => &["active", "transferred", "deceased", "removed"],
+        // Back from another zoo, or recaptured
+        "transferred" | "released" => &["arrived"],
+        _ => &[],
+    };
+    allowed.contains(&to)
+}
+
+/// `(...)` list of `IN_COLLECTION_STATUSES` for a SQL `status IN` filter.
+pub fn in_collection_sql() -> String {
+    let quoted: Vec<String> = IN_COLLECTION_STATUSES
+        .iter()
+        .map(|s| format!("'{}'", s))
+        .collect();
+    format!("({})", quoted.join(", "))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Animal {
     pub animal_id: i32,
     pub name: String,
+    /// Portuguese common name of the species
     pub specie: String,
     pub habitat: Option<String>,
     pub description: Option<String>,
     pub country_of_origin: Option<String>,
     pub date_of_birth: Option<NaiveDate>,
+    /// Row version, also sent as the `ETag` header on single-animal reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+    pub species_id: Option<i32>,
+    /// One of `arrived`, `born`, `active`, `on_loan`, `transferred`,
+    /// `released`, `deceased` or `removed`
+    pub status: String,
+    /// When the animal got its current status
+    pub status_date: Option<NaiveDate>,
+    pub status_reason: Option<String>,
+    /// Father, when it is one of our animals
+    pub sire_id: Option<i32>,
+    /// Father, when it is an external animal
+    pub sire_external_id: Option<i32>,
+    pub dam_id: Option<i32>,
+    pub dam_external_id: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateAnimal {
+    #[schema(max_length = 250)]
     pub name: String,
-    pub specie: String,
+    /// Name of a species: a common or scientific name, or an alias
+    #[schema(max_length = 250)]
+    pub specie: Option<String>,
+    /// Takes precedence over `specie`
+    pub species_id: Option<i32>,
+    #[schema(max_length = 250)]
     pub habitat: Option<String>,
     pub description: Option<String>,
+    #[schema(max_length = 250)]
     pub country_of_origin: Option<String>,
     pub date_of_birth: Option<String>,
+    /// `arrived`, `born` or `active` (the default)
+    pub status: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAnimal {
+    #[schema(max_length = 250)]
     pub name: Option<String>,
+    #[schema(max_length = 250)]
     pub specie: Option<String>,
+    pub species_id: Option<i32>,
+    #[schema(max_length = 250)]
     pub habitat: Option<String>,
     pub description: Option<String>,
+    #[schema(max_length = 250)]
     pub country_of_origin: Option<String>,
     pub date_of_birth: Option<String>,
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangeAnimalStatus {
+    pub status: String,
+    /// `YYYY-MM-DD`, today when omitted
+    pub date: Option<String>,
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnimalListQuery {
+    /// Comma-separated statuses, or `all`; animals in the collection when omitted
+    pub status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every allowed change; any other pair of statuses must be rejected.
+    const ALLOWED: &[(&str, &str)] = &[
+        ("arrived", "active"),
+        ("arrived", "transferred"),
+        ("arrived", "deceased"),
+        ("arrived", "removed"),
+        ("born", "active"),
+        ("born", "transferred"),
+        ("born", "deceased"),
+        ("born", "removed"),
+        ("active", "on_loan"),
+        ("active", "transferred"),
+        ("active", "released"),
+        ("active", "deceased"),
+        ("active", "removed"),
+        ("on_loan", "active"),
+        ("on_loan", "transferred"),
+        ("on_loan", "deceased"),
+        ("on_loan", "removed"),
+        ("transferred", "arrived"),
+        ("released", "arrived"),
+    ];
+
+    #[test]
+    fn status_transitions_follow_the_table() {
+        for from in ANIMAL_STATUSES {
+            for to in ANIMAL_STATUSES {
+                assert_eq!(
+                    status_transition_allowed(from, to),
+                    ALLOWED.contains(&(*from, *to)),
+                    "{} -> {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_statuses_have_no_way_out() {
+        for from in ["deceased", "removed"] {
+            for to in ANIMAL_STATUSES {
+                assert!(!status_transition_allowed(from, to), "{} -> {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn staying_in_the_same_status_is_not_a_transition() {
+        for status in ANIMAL_STATUSES {
+            assert!(!status_transition_allowed(status, status), "{}", status);
+        }
+    }
+
+    #[test]
+    fn unknown_statuses_are_rejected() {
+        assert!(!status_transition_allowed("active", "sleeping"));
+        assert!(!status_transition_allowed("sleeping", "active"));
+        assert!(!status_transition_allowed("", "active"));
+    }
+
+    #[test]
+    fn new_animals_start_in_the_collection() {
+        for status in INITIAL_STATUSES {
+            assert!(IN_COLLECTION_STATUSES.contains(status), "{}", status);
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AnimalCare {
     pub animal_care_id: i32,
     pub date_of_care: Option<NaiveDate>,
@@ -9,7 +10,7 @@ pub struct AnimalCare {
     pub fk_animal_animal_id: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateAnimalCare {
     pub date_of_care: Option<String>,
     pub fk_cares_cares_id: i32,
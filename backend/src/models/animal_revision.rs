@@ -0,0 +1,121 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::Animal;
+
+/// Snapshot of an `Animal` row as it was after a change.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AnimalRevision {
+    pub animal_revision_id: i32,
+    pub fk_animal_animal_id: i32,
+    pub revision_number: i32,
+    pub name: String,
+    /// Species name at the time of the revision
+    pub specie: String,
+    pub species_id: Option<i32>,
+    pub habitat: Option<String>,
+    pub description: Option<String>,
+    pub country_of_origin: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    /// Whether the animal was in the collection
+    pub is_active: bool,
+    pub status: String,
+    pub status_date: Option<NaiveDate>,
+    pub status_reason: Option<String>,
+    pub sire_id: Option<i32>,
+    pub sire_external_id: Option<i32>,
+    pub dam_id: Option<i32>,
+    pub dam_external_id: Option<i32>,
+    pub change_type: String,
+    pub reverted_from_revision: Option<i32>,
+    pub changed_at: Option<NaiveDateTime>,
+}
+
+/// A single field that differs between two consecutive revisions.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A revision together with the changes it introduced over the previous one.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnimalRevisionWithChanges {
+    #[serde(flatten)]
+    pub revision: AnimalRevision,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AsOfQuery {
+    /// Read the animal as it was at this date (`YYYY-MM-DD`) or timestamp
+    pub as_of: Option<String>,
+}
+
+impl AnimalRevision {
+    /// Fields that differ from `previous`. The first revision of an animal is
+    /// diffed against nothing, so every populated field shows up as a change.
+    pub fn diff(&self, previous: Option<&AnimalRevision>) -> Vec<FieldChange> {
+        let before = previous.map(|p| p.fields()).unwrap_or_default();
+        let after = self.fields();
+
+        after
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (field, to))| {
+                let from = before.get(i).and_then(|(_, v)| v.clone());
+                if from == to {
+                    None
+                } else {
+                    Some(FieldChange {
+                        field: field.to_string(),
+                        from,
+                        to,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// The animal as it was at this revision. Historical snapshots carry no
+    /// row version, as they cannot be written back through `If-Match`.
+    pub fn into_animal(self) -> Animal {
+        Animal {
+            animal_id: self.fk_animal_animal_id,
+            name: self.name,
+            specie: self.specie,
+            habitat: self.habitat,
+            description: self.description,
+            country_of_origin: self.country_of_origin,
+            date_of_birth: self.date_of_birth,
+            version: None,
+            species_id: self.species_id,
+            status: self.status,
+            status_date: self.status_date,
+            status_reason: self.status_reason,
+            sire_id: self.sire_id,
+            sire_external_id: self.sire_external_id,
+            dam_id: self.dam_id,
+            dam_external_id: self.dam_external_id,
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("name", Some(self.name.clone())),
+            ("specie", Some(self.specie.clone())),
+            ("habitat", self.habitat.clone()),
+            ("description", self.description.clone()),
+            ("country_of_origin", self.country_of_origin.clone()),
+            ("date_of_birth", self.date_of_birth.map(|d| d.to_string())),
+            ("status", Some(self.status.clone())),
+            ("sire_id", self.sire_id.map(|id| id.to_string())),
+            ("sire_external_id", self.sire_external_id.map(|id| id.to_string())),
+            ("dam_id", self.dam_id.map(|id| id.to_string())),
+            ("dam_external_id", self.dam_external_id.map(|id| id.to_string())),
+        ]
+    }
+}
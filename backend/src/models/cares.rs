@@ -1,23 +1,63 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Care {
     pub cares_id: i32,
     pub type_of_care: String,
     pub frequency: String,
     pub description: Option<String>,
+    /// Row version, also sent as the `ETag` header on single-care reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateCare {
+    #[schema(max_length = 250)]
     pub type_of_care: String,
+    #[schema(max_length = 250)]
     pub frequency: String,
     pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCare {
+    #[schema(max_length = 250)]
     pub type_of_care: String,
+    #[schema(max_length = 250)]
     pub frequency: String,
     pub description: Option<String>,
 }
+
+/// Days between two cares of the given frequency, as written in `Cares`
+/// (`Diaria`, `Semanal`, `Mensal`, `Semestral`, `Anual`; case and accents
+/// don't matter). `None` for frequencies we can't schedule.
+pub fn frequency_interval_days(frequency: &str) -> Option<i64> {
+    let normalized: String = frequency
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' => 'a',
+            'é' | 'ê' => 'e',
+            'í' => 'i',
+            'ó' | 'ô' | 'õ' => 'o',
+            'ú' => 'u',
+            'ç' => 'c',
+            other => other,
+        })
+        .collect();
+
+    match normalized.as_str() {
+        "diaria" | "diario" => Some(1),
+        "semanal" => Some(7),
+        "quinzenal" => Some(15),
+        "mensal" => Some(30),
+        "bimestral" => Some(60),
+        "trimestral" => Some(90),
+        "semestral" => Some(182),
+        "anual" => Some(365),
+        _ => None,
+    }
+}
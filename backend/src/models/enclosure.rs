@@ -0,0 +1,78 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Enclosure {
+    pub enclosure_id: i32,
+    pub name: String,
+    /// Part of the zoo the enclosure is in, e.g. `Setor Norte`
+    pub zone: Option<String>,
+    /// Environment it recreates, e.g. `Savana`
+    pub biome: Option<String>,
+    /// Most animals it may hold at once
+    pub capacity: i32,
+    /// Whether animals of different species may share it
+    pub mixed_species: bool,
+    /// Species it may hold; any species when empty
+    pub allowed_species_ids: Vec<i32>,
+}
+
+/// Body of both create and full update.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveEnclosure {
+    #[schema(max_length = 250)]
+    pub name: String,
+    #[schema(max_length = 250)]
+    pub zone: Option<String>,
+    #[schema(max_length = 250)]
+    pub biome: Option<String>,
+    pub capacity: i32,
+    /// Defaults to `false`: one species at a time
+    pub mixed_species: Option<bool>,
+    /// Defaults to any species
+    pub allowed_species_ids: Option<Vec<i32>>,
+}
+
+/// A stay of an animal in an enclosure. The current one has no `removed_at`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EnclosureAssignment {
+    pub enclosure_assignment_id: i32,
+    pub animal_id: i32,
+    pub enclosure_id: i32,
+    pub enclosure_name: String,
+    pub assigned_at: Option<NaiveDateTime>,
+    pub removed_at: Option<NaiveDateTime>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MoveAnimal {
+    pub enclosure_id: i32,
+    /// Why the animal is moved, kept in its move history
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+    #[schema(max_length = 250)]
+    pub responsible_staff: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SpeciesCount {
+    pub species_id: Option<i32>,
+    pub specie: String,
+    pub animals: i32,
+}
+
+/// Active animals currently in an enclosure.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EnclosureOccupancy {
+    pub enclosure_id: i32,
+    pub name: String,
+    pub zone: Option<String>,
+    pub biome: Option<String>,
+    pub capacity: i32,
+    pub occupants: i32,
+    /// Places left; negative if the capacity was lowered below the occupants
+    pub available: i32,
+    pub species: Vec<SpeciesCount>,
+}
@@ -0,0 +1,211 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Units food is planned, given and bought in.
+pub const FOOD_UNITS: &[&str] = &["kg", "g", "l", "ml", "unit"];
+
+/// How a feed is offered.
+pub const FEEDING_METHODS: &[&str] = &[
+    "bowl",
+    "scatter",
+    "hand_fed",
+    "enrichment",
+    "whole_prey",
+    "browse",
+    "hay_net",
+    "tube",
+];
+
+/// Something animals are fed, e.g. hay, a kind of fish or pellets.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodItem {
+    pub food_item_id: i32,
+    pub name: String,
+    /// `kg`, `g`, `l`, `ml` or `unit`
+    pub unit: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveFoodItem {
+    #[schema(max_length = 250)]
+    pub name: String,
+    pub unit: String,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+/// What an animal, or every animal of a species, is fed each day. A plan for
+/// the animal itself takes the place of its species' plan.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DietPlan {
+    pub diet_plan_id: i32,
+    /// Set for a plan of one animal
+    pub animal_id: Option<i32>,
+    pub animal_name: Option<String>,
+    /// Set for a plan of a whole species
+    pub species_id: Option<i32>,
+    pub species_name: Option<String>,
+    pub name: String,
+    pub valid_from: Option<NaiveDate>,
+    /// Last day of the plan; open-ended when `None`
+    pub valid_to: Option<NaiveDate>,
+    pub notes: Option<String>,
+    /// Feeds of a day, by time
+    pub items: Vec<DietPlanItem>,
+}
+
+/// One feed of a diet plan.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DietPlanItem {
+    pub diet_plan_item_id: i32,
+    pub food_item_id: i32,
+    pub food_item_name: String,
+    pub unit: String,
+    pub quantity: f64,
+    #[schema(value_type = String, example = "08:30:00")]
+    pub feeding_time: Option<NaiveTime>,
+    pub feeding_method: String,
+    pub notes: Option<String>,
+}
+
+/// Body of both create and full update.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveDietPlan {
+    /// Either an animal or a species
+    pub animal_id: Option<i32>,
+    pub species_id: Option<i32>,
+    #[schema(max_length = 250)]
+    pub name: String,
+    /// `YYYY-MM-DD`, today when omitted
+    pub valid_from: Option<String>,
+    /// `YYYY-MM-DD`
+    pub valid_to: Option<String>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+    pub items: Vec<SaveDietPlanItem>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveDietPlanItem {
+    /// On update, the item being kept; items left out are removed
+    pub diet_plan_item_id: Option<i32>,
+    pub food_item_id: i32,
+    /// In the unit of the food item
+    pub quantity: f64,
+    /// `HH:MM`
+    pub feeding_time: String,
+    /// One of `bowl`, `scatter`, `hand_fed`, `enrichment`, `whole_prey`,
+    /// `browse`, `hay_net` or `tube`
+    pub feeding_method: String,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DietPlanQuery {
+    pub animal_id: Option<i32>,
+    pub species_id: Option<i32>,
+    /// Only plans in effect on this date (`YYYY-MM-DD`)
+    pub active_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeedingDateQuery {
+    /// `YYYY-MM-DD`, today when omitted
+    pub date: Option<String>,
+}
+
+/// A feed due on the feeding sheet of a day, with what was recorded for it.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FeedingSheetEntry {
+    pub animal_id: i32,
+    pub animal_name: String,
+    pub species_name: Option<String>,
+    /// Where the animal is now
+    pub enclosure_name: Option<String>,
+    pub diet_plan_id: i32,
+    pub diet_plan_item_id: i32,
+    #[schema(value_type = String, example = "08:30:00")]
+    pub feeding_time: Option<NaiveTime>,
+    pub food_item_id: i32,
+    pub food_item_name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub feeding_method: String,
+    pub notes: Option<String>,
+    /// `None` until the feed is recorded
+    pub record: Option<FeedingRecord>,
+}
+
+/// What was actually given to an animal, and how much of it was left.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedingRecord {
+    pub feeding_record_id: i32,
+    pub animal_id: i32,
+    /// The planned feed, `None` for feeds outside the plan
+    pub diet_plan_item_id: Option<i32>,
+    pub food_item_id: i32,
+    pub food_item_name: String,
+    pub unit: String,
+    pub fed_on: Option<NaiveDate>,
+    pub quantity_given: f64,
+    pub quantity_refused: f64,
+    pub fed_by: Option<String>,
+    pub notes: Option<String>,
+    pub recorded_at: Option<NaiveDateTime>,
+}
+
+/// A recorded feed and anything about the stock it was taken from that
+/// needs a second look.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RecordedFeeding {
+    #[serde(flatten)]
+    pub record: FeedingRecord,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewFeedingRecord {
+    /// The planned feed given; its food item is used
+    pub diet_plan_item_id: Option<i32>,
+    /// Required for feeds outside the plan
+    pub food_item_id: Option<i32>,
+    /// `YYYY-MM-DD`, today when omitted
+    pub fed_on: Option<String>,
+    /// The planned quantity when omitted
+    pub quantity_given: Option<f64>,
+    /// Left uneaten, 0 when omitted
+    pub quantity_refused: Option<f64>,
+    #[schema(max_length = 250)]
+    pub fed_by: Option<String>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeedingPeriodQuery {
+    /// First day (`YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Last day (`YYYY-MM-DD`)
+    pub to: Option<String>,
+}
+
+/// How much of a food item the plans call for over a period, and how much
+/// was given, refused and eaten.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FoodTotal {
+    pub food_item_id: i32,
+    pub food_item_name: String,
+    pub unit: String,
+    /// Sum of the feeding sheets of the period, for the animals kept now
+    pub planned: f64,
+    pub given: f64,
+    pub refused: f64,
+    /// Given minus refused
+    pub eaten: f64,
+}
@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// An animal of another institution, kept only as the parent of one of ours.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExternalAnimal {
+    pub external_animal_id: i32,
+    pub name: String,
+    pub species_id: i32,
+    pub specie: String,
+    /// Zoo or institution that holds, or held, the animal
+    pub institution: Option<String>,
+    /// The animal's number in its own studbook
+    pub studbook_number: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+/// Body of both create and full update.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveExternalAnimal {
+    #[schema(max_length = 250)]
+    pub name: String,
+    /// Name of a species: a common or scientific name, or an alias
+    #[schema(max_length = 250)]
+    pub specie: Option<String>,
+    /// Takes precedence over `specie`
+    pub species_id: Option<i32>,
+    #[schema(max_length = 250)]
+    pub institution: Option<String>,
+    #[schema(max_length = 50)]
+    pub studbook_number: Option<String>,
+    /// `YYYY-MM-DD`
+    pub date_of_birth: Option<String>,
+}
+
+/// Both parents of an animal. Each one is either one of our animals or an
+/// external one, and is left unknown when both ids are omitted.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetParents {
+    pub sire_id: Option<i32>,
+    pub sire_external_id: Option<i32>,
+    pub dam_id: Option<i32>,
+    pub dam_external_id: Option<i32>,
+}
+
+/// An animal as it appears in a pedigree: one of ours (`animal_id`) or an
+/// external parent (`external_animal_id`).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PedigreeAnimal {
+    pub animal_id: Option<i32>,
+    pub external_animal_id: Option<i32>,
+    pub name: String,
+    pub species_id: Option<i32>,
+    pub specie: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    /// Lifecycle status of our animals
+    pub status: Option<String>,
+    /// Institution of external animals
+    pub institution: Option<String>,
+    pub studbook_number: Option<String>,
+    pub sire_id: Option<i32>,
+    pub sire_external_id: Option<i32>,
+    pub dam_id: Option<i32>,
+    pub dam_external_id: Option<i32>,
+}
+
+/// An ancestor or descendant of an animal.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Relative {
+    /// 1 for parents and offspring, 2 for grandparents and grandchildren...
+    pub generation: i32,
+    /// How it is related, e.g. `dam's sire` or `grandchild`
+    pub relation: String,
+    #[serde(flatten)]
+    pub animal: PedigreeAnimal,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PedigreeQuery {
+    /// Generations to follow, 1 to 10; 3 when omitted
+    pub depth: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InbreedingQuery {
+    /// The other candidate of the pairing
+    pub mate_id: i32,
+}
+
+/// What breeding two animals would mean for their offspring.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InbreedingResult {
+    pub animal: PedigreeAnimal,
+    pub mate: PedigreeAnimal,
+    /// Inbreeding coefficient of the offspring, i.e. the kinship of the pair:
+    /// 0 for unrelated animals, 0.25 for full siblings
+    pub coefficient: f64,
+    /// Ancestors both candidates share
+    pub common_ancestors: Vec<PedigreeAnimal>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StudbookQuery {
+    /// `json` (the default) or `csv`
+    pub format: Option<String>,
+}
+
+/// A line of a species' studbook. Ids are `A<animal_id>` for our animals and
+/// `E<external_animal_id>` for external ones.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StudbookEntry {
+    pub studbook_id: String,
+    pub name: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub sire: Option<String>,
+    pub dam: Option<String>,
+    pub status: Option<String>,
+    pub institution: Option<String>,
+    pub studbook_number: Option<String>,
+}
@@ -0,0 +1,140 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+pub const INVENTORY_CATEGORIES: &[&str] = &["food", "medication", "enrichment", "other"];
+
+/// Units stock is counted in.
+pub const INVENTORY_UNITS: &[&str] = &["kg", "g", "mg", "l", "ml", "unit"];
+
+/// Movements that can be entered by hand; `received` comes with a new lot,
+/// and `consumed` from feeding and dose records too.
+pub const MANUAL_MOVEMENT_TYPES: &[&str] = &["consumed", "adjusted", "discarded"];
+
+/// A food, medication or supply kept in stock.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InventoryItem {
+    pub inventory_item_id: i32,
+    pub name: String,
+    /// `food`, `medication`, `enrichment` or `other`
+    pub category: String,
+    /// `kg`, `g`, `mg`, `l`, `ml` or `unit`
+    pub unit: String,
+    /// Usable stock at or below this shows in the low-stock report
+    pub reorder_level: f64,
+    /// Feeding records of this food item take from this stock
+    pub food_item_id: Option<i32>,
+    /// Doses of treatments with this drug take from this stock
+    pub drug_name: Option<String>,
+    pub notes: Option<String>,
+    /// In lots that haven't expired
+    pub quantity_on_hand: f64,
+    /// Still in lots past their expiry date
+    pub quantity_expired: f64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveInventoryItem {
+    #[schema(max_length = 250)]
+    pub name: String,
+    pub category: String,
+    pub unit: String,
+    pub reorder_level: f64,
+    /// Only for food; its unit has to convert to the item's
+    pub food_item_id: Option<i32>,
+    /// Only for medication, as written on treatment plans
+    #[schema(max_length = 250)]
+    pub drug_name: Option<String>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InventoryItemQuery {
+    /// Only items of this category
+    pub category: Option<String>,
+}
+
+/// A delivery of an item, used up first-expiring-first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InventoryLot {
+    pub inventory_lot_id: i32,
+    pub inventory_item_id: i32,
+    pub lot_number: Option<String>,
+    pub expires_on: Option<NaiveDate>,
+    pub received_on: Option<NaiveDate>,
+    pub quantity_received: f64,
+    pub quantity_on_hand: f64,
+    pub supplier: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReceiveLot {
+    #[schema(max_length = 100)]
+    pub lot_number: Option<String>,
+    /// `YYYY-MM-DD`
+    pub expires_on: Option<String>,
+    /// `YYYY-MM-DD`, today when omitted
+    pub received_on: Option<String>,
+    pub quantity: f64,
+    #[schema(max_length = 250)]
+    pub supplier: Option<String>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+    #[schema(max_length = 250)]
+    pub recorded_by: Option<String>,
+}
+
+/// A change to the stock of a lot. Quantities are negative when stock goes
+/// out.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StockMovement {
+    pub stock_movement_id: i32,
+    pub inventory_item_id: i32,
+    pub inventory_lot_id: Option<i32>,
+    /// `received`, `consumed`, `adjusted`, `discarded` or `returned`
+    pub movement_type: String,
+    pub quantity: f64,
+    pub moved_at: Option<NaiveDateTime>,
+    /// `feeding_record` or `treatment_dose` for stock taken automatically
+    pub source: Option<String>,
+    pub source_id: Option<i32>,
+    pub reason: Option<String>,
+    pub recorded_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewStockMovement {
+    pub inventory_lot_id: i32,
+    /// `consumed`, `adjusted` or `discarded`
+    pub movement_type: String,
+    /// Taken out for `consumed` and `discarded`; added, or taken out when
+    /// negative, for `adjusted`
+    pub quantity: f64,
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+    #[schema(max_length = 250)]
+    pub recorded_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExpiringQuery {
+    /// Lots expiring within this many days, or already expired; 30 when
+    /// omitted
+    pub within_days: Option<i64>,
+}
+
+/// A lot with stock left that expires soon or has expired.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExpiringLot {
+    pub item_name: String,
+    pub category: String,
+    pub unit: String,
+    #[serde(flatten)]
+    pub lot: InventoryLot,
+    /// Negative once expired
+    pub days_left: i64,
+}
@@ -0,0 +1,103 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// What can be measured, with the unit values are kept in.
+pub const MEASUREMENT_KINDS: &[(&str, &str)] = &[
+    ("weight", "kg"),
+    ("length", "cm"),
+    ("body_condition_score", "score"),
+];
+
+/// Body condition is scored from 1 (emaciated) to 9 (obese).
+pub const BODY_CONDITION_SCORE_RANGE: std::ops::RangeInclusive<f64> = 1.0..=9.0;
+
+/// Longest window a trend can look back over.
+pub const MAX_TREND_WINDOW_DAYS: i64 = 365;
+
+/// The unit of a measurement kind, `None` for unknown kinds.
+pub fn measurement_unit(kind: &str) -> Option<&'static str> {
+    MEASUREMENT_KINDS
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, unit)| *unit)
+}
+
+/// A weight, length or body condition score taken of an animal. Times are UTC.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Measurement {
+    pub measurement_id: i32,
+    pub animal_id: i32,
+    /// `weight`, `length` or `body_condition_score`
+    pub kind: String,
+    pub value: f64,
+    /// `kg`, `cm` or `score`
+    pub unit: String,
+    pub measured_at: Option<NaiveDateTime>,
+    pub measured_by: Option<String>,
+    pub notes: Option<String>,
+    pub recorded_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewMeasurement {
+    /// `weight` (kg), `length` (cm) or `body_condition_score` (1 to 9)
+    pub kind: String,
+    pub value: f64,
+    /// `YYYY-MM-DDTHH:MM[:SS]` in UTC; now when omitted
+    pub measured_at: Option<String>,
+    #[schema(max_length = 250)]
+    pub measured_by: Option<String>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MeasurementQuery {
+    /// Only measurements of this kind
+    pub kind: Option<String>,
+    /// Taken on or after this date (`YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Taken on or before this date (`YYYY-MM-DD`)
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrendQuery {
+    /// Days before the latest measurement to look back over; the configured
+    /// `weight_loss_window_days` when omitted
+    pub window_days: Option<i64>,
+    /// Weight loss that raises an alert, in percent; the configured
+    /// `weight_loss_alert_percent` when omitted
+    pub alert_percent: Option<f64>,
+}
+
+/// How one kind of measurement of an animal moved over the window ending at
+/// its latest measurement.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MeasurementTrend {
+    pub animal_id: i32,
+    pub animal_name: String,
+    pub kind: String,
+    pub unit: String,
+    pub window_days: i64,
+    pub alert_percent: f64,
+    /// Measurements taken in the window
+    pub measurements: usize,
+    pub latest_value: f64,
+    pub latest_measured_at: Option<NaiveDateTime>,
+    /// Latest value minus the first one in the window; `None` with a single
+    /// measurement
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    /// Least-squares slope over the window
+    pub change_per_week: Option<f64>,
+    /// Highest value in the window
+    pub peak_value: f64,
+    /// How far the latest value is below the peak, in percent
+    pub drop_from_peak_percent: f64,
+    /// Set for weights that dropped at least `alert_percent` from the peak
+    pub weight_loss_alert: bool,
+}
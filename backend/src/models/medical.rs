@@ -0,0 +1,76 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// A veterinary examination of an animal.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MedicalExamination {
+    pub medical_examination_id: i32,
+    pub animal_id: i32,
+    /// Care that led to the examination, e.g. a scheduled check-up
+    pub cares_id: Option<i32>,
+    pub type_of_care: Option<String>,
+    pub examined_on: Option<NaiveDate>,
+    pub veterinarian: String,
+    pub reason: Option<String>,
+    pub findings: Option<String>,
+    /// Diagnosis codes, e.g. ICD-10 `B05.9` or a SNOMED CT id
+    pub diagnosis_codes: Vec<String>,
+    pub treatment_notes: Option<String>,
+    /// When the animal should be seen again
+    pub follow_up_on: Option<NaiveDate>,
+    pub attachments: Vec<MedicalAttachment>,
+    /// Name of the API key the examination was recorded with; corrections keep it
+    pub recorded_by: Option<String>,
+    pub recorded_at: Option<NaiveDateTime>,
+}
+
+/// Body of both create and full update.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveMedicalExamination {
+    pub cares_id: Option<i32>,
+    /// `YYYY-MM-DD`, today when omitted
+    pub examined_on: Option<String>,
+    #[schema(max_length = 250)]
+    pub veterinarian: String,
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+    pub findings: Option<String>,
+    pub diagnosis_codes: Option<Vec<String>>,
+    pub treatment_notes: Option<String>,
+    /// `YYYY-MM-DD`
+    pub follow_up_on: Option<String>,
+}
+
+/// A file that belongs to an examination, such as an X-ray or a lab report.
+/// Only its metadata is kept here; the file itself lives in `storage_reference`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MedicalAttachment {
+    pub medical_attachment_id: i32,
+    pub medical_examination_id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Where the file is stored, e.g. a URL or object storage key
+    pub storage_reference: String,
+    pub uploaded_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewMedicalAttachment {
+    #[schema(max_length = 250)]
+    pub file_name: String,
+    /// e.g. `image/jpeg` or `application/pdf`
+    #[schema(max_length = 100)]
+    pub content_type: String,
+    pub size_bytes: i64,
+    #[schema(max_length = 2048)]
+    pub storage_reference: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FollowUpQuery {
+    /// Follow-ups due on or before this date (`YYYY-MM-DD`); today when omitted
+    pub due_by: Option<String>,
+}
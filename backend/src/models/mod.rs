@@ -1,7 +1,31 @@
 pub mod animal;
 pub mod animal_care;
+pub mod animal_revision;
 pub mod cares;
+pub mod enclosure;
+pub mod feeding;
+pub mod genealogy;
+pub mod health;
+pub mod inventory;
+pub mod measurement;
+pub mod medical;
+pub mod movement;
+pub mod species;
+pub mod treatment;
+pub mod webhook;
 
 pub use animal::*;
 pub use animal_care::*;
+pub use animal_revision::*;
 pub use cares::*;
+pub use enclosure::*;
+pub use feeding::*;
+pub use genealogy::*;
+pub use health::*;
+pub use inventory::*;
+pub use measurement::*;
+pub use medical::*;
+pub use movement::*;
+pub use species::*;
+pub use treatment::*;
+pub use webhook::*;
@@ -0,0 +1,75 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Kinds of movement, as stored in `Movement.movement_type`.
+pub const MOVEMENT_TYPES: &[&str] = &["internal", "transfer_in", "transfer_out"];
+
+/// A change of where an animal is: between enclosures or habitats of the zoo
+/// (`internal`), or to or from another institution.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Movement {
+    pub movement_id: i32,
+    pub animal_id: i32,
+    /// `internal`, `transfer_in` or `transfer_out`
+    pub movement_type: String,
+    pub from_enclosure_id: Option<i32>,
+    /// Enclosure, habitat or institution the animal left
+    pub from_location: Option<String>,
+    pub to_enclosure_id: Option<i32>,
+    /// Enclosure, habitat or institution the animal went to
+    pub to_location: Option<String>,
+    /// Other zoo or institution of a transfer
+    pub institution: Option<String>,
+    /// Whether an outbound transfer is a loan the animal returns from
+    pub is_loan: bool,
+    pub moved_on: Option<NaiveDate>,
+    pub reason: Option<String>,
+    pub responsible_staff: Option<String>,
+    pub recorded_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OutboundTransfer {
+    #[schema(max_length = 250)]
+    pub institution: String,
+    /// Loans keep the animal on the zoo's books; other transfers deactivate it
+    pub loan: Option<bool>,
+    /// `YYYY-MM-DD`, today when omitted
+    pub moved_on: Option<String>,
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+    #[schema(max_length = 250)]
+    pub responsible_staff: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InboundTransfer {
+    #[schema(max_length = 250)]
+    pub institution: String,
+    /// Enclosure the animal is placed in on arrival
+    pub enclosure_id: Option<i32>,
+    /// `YYYY-MM-DD`, today when omitted
+    pub moved_on: Option<String>,
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+    #[schema(max_length = 250)]
+    pub responsible_staff: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct MovementQuery {
+    /// Only movements of this type
+    pub movement_type: Option<String>,
+}
+
+/// A movement or a care in an animal's timeline.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TimelineEntry {
+    pub date: Option<NaiveDate>,
+    /// `movement` or `care`
+    pub kind: String,
+    pub summary: String,
+    pub movement_id: Option<i32>,
+    pub animal_care_id: Option<i32>,
+}
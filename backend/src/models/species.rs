@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// IUCN Red List categories, from not evaluated to extinct.
+pub const CONSERVATION_STATUSES: &[&str] = &["NE", "DD", "LC", "NT", "VU", "EN", "CR", "EW", "EX"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Species {
+    pub species_id: i32,
+    /// Name animals are listed under, e.g. `Leão`
+    pub common_name_pt: String,
+    pub common_name_en: Option<String>,
+    pub scientific_name: Option<String>,
+    pub taxonomic_class: Option<String>,
+    pub taxonomic_order: Option<String>,
+    pub family: Option<String>,
+    /// IUCN category code, e.g. `VU`
+    pub conservation_status: Option<String>,
+    pub typical_lifespan_years: Option<i32>,
+    /// Other names animals were given this species under, e.g. `Leoa`
+    pub aliases: Vec<String>,
+}
+
+/// Body of both create and full update.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveSpecies {
+    #[schema(max_length = 250)]
+    pub common_name_pt: String,
+    #[schema(max_length = 250)]
+    pub common_name_en: Option<String>,
+    #[schema(max_length = 250)]
+    pub scientific_name: Option<String>,
+    #[schema(max_length = 250)]
+    pub taxonomic_class: Option<String>,
+    #[schema(max_length = 250)]
+    pub taxonomic_order: Option<String>,
+    #[schema(max_length = 250)]
+    pub family: Option<String>,
+    pub conservation_status: Option<String>,
+    pub typical_lifespan_years: Option<i32>,
+    /// Replaces the species' aliases; left as they are when omitted
+    pub aliases: Option<Vec<String>>,
+}
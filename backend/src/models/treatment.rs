@@ -0,0 +1,122 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Ways a drug can be given.
+pub const ADMINISTRATION_ROUTES: &[&str] = &[
+    "oral",
+    "intramuscular",
+    "intravenous",
+    "subcutaneous",
+    "topical",
+    "ophthalmic",
+    "inhaled",
+    "rectal",
+];
+
+/// A course of medication prescribed for an animal. Times are UTC.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TreatmentPlan {
+    pub treatment_plan_id: i32,
+    pub animal_id: i32,
+    pub animal_name: String,
+    /// Examination the treatment was prescribed at
+    pub medical_examination_id: Option<i32>,
+    pub drug: String,
+    pub dose_per_kg: f64,
+    /// Unit of the dose, e.g. `mg` or `ml`
+    pub dose_unit: String,
+    /// Weight the dose was worked out for
+    pub weight_kg: f64,
+    /// Dose per administration: `dose_per_kg` × `weight_kg`
+    pub dose: f64,
+    pub route: String,
+    pub interval_hours: i32,
+    pub duration_days: i32,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub prescribed_by: String,
+    pub notes: Option<String>,
+    /// `active`, `completed` or `stopped`
+    pub status: String,
+    pub stopped_at: Option<NaiveDateTime>,
+    pub stop_reason: Option<String>,
+    pub next_dose_at: Option<NaiveDateTime>,
+    /// Missed doses and doses given too close together
+    pub warnings: Vec<String>,
+    /// Scheduled and given doses; left out of lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doses: Option<Vec<TreatmentDose>>,
+}
+
+/// A dose of a treatment: one of the scheduled ones, or an extra one given
+/// outside the schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TreatmentDose {
+    pub treatment_dose_id: i32,
+    pub treatment_plan_id: i32,
+    /// `None` for extra doses
+    pub scheduled_at: Option<NaiveDateTime>,
+    /// `scheduled`, `due`, `given`, `missed`, `cancelled` or `extra`
+    pub status: String,
+    pub administered_at: Option<NaiveDateTime>,
+    pub administered_by: Option<String>,
+    pub amount: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTreatmentPlan {
+    pub medical_examination_id: Option<i32>,
+    #[schema(max_length = 250)]
+    pub drug: String,
+    pub dose_per_kg: f64,
+    #[schema(max_length = 20)]
+    pub dose_unit: String,
+    pub weight_kg: f64,
+    /// One of `oral`, `intramuscular`, `intravenous`, `subcutaneous`,
+    /// `topical`, `ophthalmic`, `inhaled` or `rectal`
+    pub route: String,
+    pub interval_hours: i32,
+    pub duration_days: i32,
+    /// First dose, `YYYY-MM-DDTHH:MM[:SS]` in UTC; now when omitted
+    pub starts_at: Option<String>,
+    #[schema(max_length = 250)]
+    pub prescribed_by: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StopTreatment {
+    #[schema(max_length = 250)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdministerDose {
+    /// Scheduled dose being given; the closest one still open when omitted
+    pub treatment_dose_id: Option<i32>,
+    /// `YYYY-MM-DDTHH:MM[:SS]` in UTC; now when omitted
+    pub administered_at: Option<String>,
+    #[schema(max_length = 250)]
+    pub administered_by: String,
+    /// The prescribed dose when omitted
+    pub amount: Option<f64>,
+    #[schema(max_length = 250)]
+    pub notes: Option<String>,
+}
+
+/// A recorded dose and anything about it that needs a second look. The dose
+/// is recorded either way, as it has already been given.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AdministeredDose {
+    pub dose: TreatmentDose,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TreatmentPlanQuery {
+    /// `active` (the default), `completed`, `stopped` or `all`
+    pub status: Option<String>,
+}
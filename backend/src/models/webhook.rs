@@ -0,0 +1,46 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Event types a webhook subscription can ask for.
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    "animal.created",
+    "animal.updated",
+    "animal.deactivated",
+    "care.logged",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookSubscription {
+    pub webhook_subscription_id: i32,
+    pub target_url: String,
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_at: Option<NaiveDateTime>,
+    /// Only returned when the subscription is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookSubscription {
+    pub target_url: String,
+    pub event_types: Vec<String>,
+    /// Shared secret for the HMAC signature; generated if not given
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub webhook_delivery_id: i32,
+    pub fk_webhook_subscription_id: i32,
+    pub event_type: String,
+    pub status: String,
+    pub attempt_count: i32,
+    pub next_attempt_at: Option<NaiveDateTime>,
+    pub last_attempt_at: Option<NaiveDateTime>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub delivered_at: Option<NaiveDateTime>,
+}
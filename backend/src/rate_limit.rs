@@ -0,0 +1,362 @@
+use crate::auth;
+use crate::config::{AuthConfig, RateLimitConfig};
+use crate::tls::TlsListener;
+use axum::{
+    extract::{ConnectInfo, Request, State, connect_info::Connected},
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    serve::IncomingStream,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tracing::debug;
+
+/// Buckets that have refilled completely are dropped this often
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The peer address of a connection, whether plain TCP or TLS.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, TcpListener>> for ClientAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        ClientAddr(*stream.remote_addr())
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for ClientAddr {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        ClientAddr(*stream.remote_addr())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Budget {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Buckets {
+    buckets: HashMap<(ClientKey, Budget), Bucket>,
+    last_sweep: Instant,
+}
+
+/// Token buckets per client and budget, kept in memory.
+///
+/// Each bucket holds up to `burst` requests and refills at the per-minute
+/// rate, so a client can make a short burst and then keeps to the rate.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    auth: AuthConfig,
+    state: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig, auth: &AuthConfig) -> Self {
+        Self {
+            config: config.clone(),
+            auth: auth.clone(),
+            state: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                last_sweep: Instant::now(),
+            }),
+        }
+    }
+
+    fn limits(&self, budget: Budget) -> (f64, f64) {
+        let (per_minute, burst) = match budget {
+            Budget::Read => (self.config.reads_per_minute, self.config.read_burst),
+            Budget::Write => (self.config.writes_per_minute, self.config.write_burst),
+        };
+        (f64::from(per_minute) / 60.0, f64::from(burst))
+    }
+
+    /// Take a token, or say how long until the next one.
+    fn take(&self, key: ClientKey, budget: Budget, now: Instant) -> Result<(), Duration> {
+        let (rate, capacity) = self.limits(budget);
+        let mut state = self.state.lock().expect("rate limiter lock poisoned");
+
+        if now.duration_since(state.last_sweep) >= SWEEP_INTERVAL {
+            state.last_sweep = now;
+            state.buckets.retain(|(_, budget), bucket| {
+                let (rate, capacity) = self.limits(*budget);
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate < capacity
+            });
+        }
+
+        let bucket = state.buckets.entry((key, budget)).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// The configured API key when one is sent, otherwise the client's IP
+    /// address. Unknown keys count as no key, so inventing a new one per
+    /// request doesn't get a fresh bucket.
+    fn client_key(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<ClientKey> {
+        if let Some(api_key) = auth::api_key(&self.auth, headers) {
+            return Some(ClientKey::ApiKey(api_key.name.clone()));
+        }
+        if self.config.trust_forwarded_for {
+            // The proxy appends the address it saw, so the last entry is the
+            // only one the client can't forge
+            let forwarded = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .next_back()
+                .and_then(|ip| ip.trim().parse().ok());
+            if let Some(ip) = forwarded {
+                return Some(ClientKey::Ip(ip));
+            }
+        }
+        peer.map(ClientKey::Ip)
+    }
+}
+
+/// Answers `429 Too Many Requests` with `Retry-After` once a client's
+/// bucket for the request's budget is empty. Health checks and metrics
+/// scrapes are never limited.
+pub async fn limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if path.starts_with("/health/") || path == "/metrics" {
+        return next.run(request).await;
+    }
+
+    let budget = match *request.method() {
+        Method::GET | Method::HEAD | Method::OPTIONS => Budget::Read,
+        _ => Budget::Write,
+    };
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<ClientAddr>>()
+        .map(|ConnectInfo(ClientAddr(addr))| addr.ip());
+    let Some(key) = limiter.client_key(request.headers(), peer) else {
+        return next.run(request).await;
+    };
+
+    match limiter.take(key, budget, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let seconds = wait.as_secs_f64().ceil().max(1.0) as u64;
+            debug!(?budget, retry_after = seconds, "Rate limit reached");
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, seconds.to_string())],
+                format!("Too many requests; retry in {}s", seconds),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiKeyConfig;
+    use axum::http::HeaderValue;
+
+    const PEER: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(192, 0, 2, 7));
+
+    fn limiter(config: RateLimitConfig) -> RateLimiter {
+        let auth = AuthConfig {
+            api_keys: vec![ApiKeyConfig {
+                name: "clinic".to_string(),
+                key: "0123456789abcdef".to_string(),
+                scopes: Vec::new(),
+            }],
+        };
+        RateLimiter::new(&config, &auth)
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn configured_keys_get_their_own_bucket() {
+        let limiter = limiter(RateLimitConfig::default());
+        let key = limiter.client_key(&headers(&[("x-api-key", "0123456789abcdef")]), Some(PEER));
+        assert_eq!(key, Some(ClientKey::ApiKey("clinic".to_string())));
+    }
+
+    #[test]
+    fn unknown_keys_are_keyed_by_address() {
+        let limiter = limiter(RateLimitConfig::default());
+        for sent in ["not-a-configured-key", "0123456789abcdeF", ""] {
+            let key = limiter.client_key(&headers(&[("x-api-key", sent)]), Some(PEER));
+            assert_eq!(key, Some(ClientKey::Ip(PEER)), "X-API-Key: {}", sent);
+        }
+    }
+
+    #[test]
+    fn inventing_keys_does_not_get_fresh_buckets() {
+        let limiter = limiter(RateLimitConfig {
+            write_burst: 3,
+            ..RateLimitConfig::default()
+        });
+        let now = Instant::now();
+        let results: Vec<bool> = (0..4)
+            .map(|i| {
+                let sent = format!("invented-key-{}", i);
+                let key = limiter
+                    .client_key(&headers(&[("x-api-key", &sent)]), Some(PEER))
+                    .unwrap();
+                limiter.take(key, Budget::Write, now).is_ok()
+            })
+            .collect();
+        assert_eq!(results, [true, true, true, false]);
+    }
+
+    #[test]
+    fn forwarded_for_is_only_used_when_trusted() {
+        let forwarded = headers(&[("x-forwarded-for", "203.0.113.1, 198.51.100.2")]);
+        let proxied: IpAddr = "198.51.100.2".parse().unwrap();
+
+        let limiter_direct = limiter(RateLimitConfig::default());
+        assert_eq!(
+            limiter_direct.client_key(&forwarded, Some(PEER)),
+            Some(ClientKey::Ip(PEER))
+        );
+
+        let limiter_proxied = limiter(RateLimitConfig {
+            trust_forwarded_for: true,
+            ..RateLimitConfig::default()
+        });
+        assert_eq!(
+            limiter_proxied.client_key(&forwarded, Some(PEER)),
+            Some(ClientKey::Ip(proxied))
+        );
+        assert_eq!(limiter_proxied.client_key(&HeaderMap::new(), None), None);
+    }
+
+    fn bucket_limiter() -> RateLimiter {
+        limiter(RateLimitConfig {
+            reads_per_minute: 60,
+            read_burst: 3,
+            writes_per_minute: 6,
+            write_burst: 2,
+            ..RateLimitConfig::default()
+        })
+    }
+
+    fn ip() -> ClientKey {
+        ClientKey::Ip(PEER)
+    }
+
+    #[test]
+    fn a_full_bucket_allows_a_burst_then_rejects() {
+        let limiter = bucket_limiter();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.take(ip(), Budget::Read, now).is_ok());
+        }
+        // One read per second, and the bucket is empty
+        assert_eq!(
+            limiter.take(ip(), Budget::Read, now),
+            Err(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn tokens_refill_at_the_rate() {
+        let limiter = bucket_limiter();
+        let start = Instant::now();
+        for _ in 0..2 {
+            assert!(limiter.take(ip(), Budget::Write, start).is_ok());
+        }
+        // Six writes per minute is one every ten seconds
+        let wait = limiter
+            .take(ip(), Budget::Write, start + Duration::from_secs(4))
+            .unwrap_err();
+        assert!((wait.as_secs_f64() - 6.0).abs() < 1e-6, "{:?}", wait);
+        assert!(
+            limiter
+                .take(ip(), Budget::Write, start + Duration::from_secs(10))
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .take(ip(), Budget::Write, start + Duration::from_secs(10))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn refill_stops_at_the_burst() {
+        let limiter = bucket_limiter();
+        let start = Instant::now();
+        assert!(limiter.take(ip(), Budget::Read, start).is_ok());
+        // Idle far longer than it takes to refill
+        let later = start + Duration::from_secs(30);
+        for _ in 0..3 {
+            assert!(limiter.take(ip(), Budget::Read, later).is_ok());
+        }
+        assert!(limiter.take(ip(), Budget::Read, later).is_err());
+    }
+
+    #[test]
+    fn budgets_and_clients_have_separate_buckets() {
+        let limiter = bucket_limiter();
+        let now = Instant::now();
+        for _ in 0..2 {
+            assert!(limiter.take(ip(), Budget::Write, now).is_ok());
+        }
+        assert!(limiter.take(ip(), Budget::Write, now).is_err());
+        assert!(limiter.take(ip(), Budget::Read, now).is_ok());
+        let other = ClientKey::Ip("192.0.2.8".parse().unwrap());
+        assert!(limiter.take(other, Budget::Write, now).is_ok());
+        let key = ClientKey::ApiKey("clinic".to_string());
+        assert!(limiter.take(key, Budget::Write, now).is_ok());
+    }
+
+    #[test]
+    fn refilled_buckets_are_swept() {
+        let limiter = bucket_limiter();
+        let start = Instant::now();
+        assert!(limiter.take(ip(), Budget::Read, start).is_ok());
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 1);
+
+        let other = ClientKey::Ip("192.0.2.8".parse().unwrap());
+        assert!(
+            limiter
+                .take(other, Budget::Read, start + SWEEP_INTERVAL)
+                .is_ok()
+        );
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 1);
+    }
+}
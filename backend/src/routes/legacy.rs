@@ -0,0 +1,71 @@
+use crate::handlers::*;
+use crate::state::AppState;
+use axum::{
+    Router,
+    http::{HeaderName, HeaderValue},
+    routing::{delete, get, post, put},
+};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// `Deprecation` header (RFC 9745): the date the verb-style routes were
+/// superseded by `/api/v1`, as a Unix timestamp.
+const DEPRECATED_SINCE: &str = "@1792281600";
+
+/// Points clients at the documentation of the replacement routes.
+const DEPRECATION_LINK: &str = r#"</docs>; rel="deprecation"; type="text/html""#;
+
+/// The original verb-style routes, kept until the frontend moves to `/api/v1`.
+///
+/// These are the only routes registered without `routes!`, so they are left
+/// out of the OpenAPI document on purpose. Every response carries a
+/// `Deprecation` header.
+pub fn router() -> Router<AppState> {
+    let animals_router = Router::new()
+        .route("/list", get(get_animals))
+        .route("/add", post(add_animal))
+        .route("/animals/{id}", get(get_animal_by_id))
+        .route("/deactivate/{id}", post(deactivate_animal))
+        .route("/update/{id}", put(update_animal))
+        .route("/delete/{id}", delete(delete_animal))
+        .route("/revisions/{id}", get(get_animal_revisions))
+        .route("/revert/{id}/{revision}", post(revert_animal));
+
+    let cares_router = Router::new()
+        .route("/list", get(get_cares))
+        .route("/by-id/{id}", get(get_care_by_id))
+        .route("/add", post(add_care))
+        .route("/update/{id}", put(update_care))
+        .route("/delete/{id}", delete(delete_care));
+
+    let animal_cares_router = Router::new()
+        .route("/list", get(get_animal_cares))
+        .route("/by-id/{id}", get(get_animal_care_by_id))
+        .route("/by-animal/by-id/{id}", get(get_animal_care_by_animal_id))
+        .route("/add", post(add_animal_care));
+
+    let events_router = Router::new().route("/stream", get(stream_events));
+
+    let webhooks_router = Router::new()
+        .route("/list", get(get_webhook_subscriptions))
+        .route("/add", post(add_webhook_subscription))
+        .route("/delete/{id}", delete(delete_webhook_subscription))
+        .route("/deliveries/{id}", get(get_webhook_deliveries))
+        .route("/ping/{id}", post(ping_webhook_subscription))
+        .route("/redeliver/{id}", post(redeliver_webhook));
+
+    Router::new()
+        .route("/message", get(initial_page))
+        .nest("/animals", animals_router)
+        .nest("/cares", cares_router)
+        .nest("/animal-cares", animal_cares_router)
+        .nest("/events", events_router)
+        .nest("/webhooks", webhooks_router)
+        .layer(SetResponseHeaderLayer::overriding(
+            HeaderName::from_static("deprecation"),
+            HeaderValue::from_static(DEPRECATED_SINCE),
+        ))
+        .layer(SetResponseHeaderLayer::appending(
+            HeaderName::from_static("link"),
+            HeaderValue::from_static(DEPRECATION_LINK),
+        ))
+}
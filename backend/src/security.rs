@@ -0,0 +1,105 @@
+use crate::config::{CorsConfig, SecurityConfig};
+use axum::{
+    Router,
+    http::{HeaderName, HeaderValue, Method, header},
+};
+use std::time::Duration;
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    set_header::SetResponseHeaderLayer,
+};
+
+/// Swagger UI loads its own scripts and styles and sets inline styles and
+/// `data:` images, none of which the API responses need.
+const DOCS_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; img-src 'self' data:; \
+     style-src 'self' 'unsafe-inline'; frame-ancestors 'none'";
+
+/// CORS built from the configured allow-list. Everything in it was validated
+/// when the configuration was loaded.
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins = if config.allowed_origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|o| HeaderValue::from_str(o).ok()),
+        )
+    };
+    let methods: Vec<Method> = config
+        .allowed_methods
+        .iter()
+        .filter_map(|m| m.parse().ok())
+        .collect();
+    let allowed_headers: Vec<HeaderName> = config
+        .allowed_headers
+        .iter()
+        .filter_map(|h| h.parse().ok())
+        .collect();
+    let exposed_headers: Vec<HeaderName> = config
+        .exposed_headers
+        .iter()
+        .filter_map(|h| h.parse().ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(allowed_headers)
+        .expose_headers(exposed_headers)
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age_seconds))
+}
+
+/// Adds the browser hardening headers to every response that doesn't set
+/// them itself. HSTS is only sent when every client connects over HTTPS.
+pub fn headers<S>(router: Router<S>, config: &SecurityConfig, https_only: bool) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let mut router = router;
+    if https_only {
+        let hsts = format!("max-age={}; includeSubDomains", config.hsts_max_age_seconds);
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_str(&hsts).expect("a number is a valid header value"),
+        ));
+    }
+    if !config.headers {
+        return router;
+    }
+    if let Ok(policy) = HeaderValue::from_str(&config.content_security_policy)
+        && !policy.is_empty()
+    {
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            header::CONTENT_SECURITY_POLICY,
+            policy,
+        ));
+    }
+    router
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::X_FRAME_OPTIONS,
+            HeaderValue::from_static("DENY"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("no-referrer"),
+        ))
+}
+
+/// The policy Swagger UI needs, set on the docs routes before the general
+/// one is applied.
+pub fn docs_headers<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(SetResponseHeaderLayer::overriding(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(DOCS_CONTENT_SECURITY_POLICY),
+    ))
+}
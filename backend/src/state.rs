@@ -0,0 +1,46 @@
+use axum::extract::FromRef;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::events::EventBus;
+
+/// Shared state handed to every router.
+///
+/// Handlers extract only the parts they need (`State<Database>`,
+/// `State<EventBus>`, `State<Arc<Config>>`, `State<CancellationToken>`)
+/// through the `FromRef` impls below.
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub db: Database,
+    pub events: EventBus,
+    /// Cancelled when the server starts shutting down, to end long-lived
+    /// responses such as event streams.
+    pub shutdown: CancellationToken,
+}
+
+impl FromRef<AppState> for Database {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for EventBus {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
+
+impl FromRef<AppState> for CancellationToken {
+    fn from_ref(state: &AppState) -> Self {
+        state.shutdown.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
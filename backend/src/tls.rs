@@ -0,0 +1,179 @@
+use crate::config::TlsConfig;
+use axum::serve::Listener;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::server::TlsStream;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+/// A client that hasn't finished the handshake by then is dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshaken connections waiting for the server to pick them up
+const ACCEPT_QUEUE: usize = 64;
+
+/// Read a PEM certificate chain and private key into a server config.
+pub fn load(cert_path: &Path, key_path: &Path) -> Result<Arc<ServerConfig>, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("{}: {}", cert_path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("{}: no certificates found", cert_path.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("{}: {}", key_path.display(), e))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("{}: {}", cert_path.display(), e))?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// Serves TLS connections to `axum::serve`.
+///
+/// Handshakes run in their own tasks so a slow client can't hold up the
+/// others, and the certificate is re-read when its files change, so a
+/// rotated certificate is picked up without a restart.
+pub struct TlsListener {
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub fn new(
+        tcp: TcpListener,
+        config: &TlsConfig,
+        shutdown: CancellationToken,
+    ) -> Result<Self, String> {
+        let (cert_path, key_path) = match (&config.cert_path, &config.key_path) {
+            (Some(cert), Some(key)) => (cert.clone(), key.clone()),
+            _ => return Err("TLS needs both a certificate and a key".to_string()),
+        };
+        let current = Arc::new(RwLock::new(load(&cert_path, &key_path)?));
+        let local_addr = tcp.local_addr().map_err(|e| e.to_string())?;
+
+        tokio::spawn(watch_certificate(
+            cert_path,
+            key_path,
+            Duration::from_secs(config.reload_interval_seconds),
+            current.clone(),
+            shutdown,
+        ));
+
+        let (sender, connections) = mpsc::channel(ACCEPT_QUEUE);
+        tokio::spawn(accept_loop(tcp, current, sender));
+
+        Ok(Self {
+            connections,
+            local_addr,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept loop only ends once the server has stopped listening
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+async fn accept_loop(
+    tcp: TcpListener,
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+    sender: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = sender.closed() => return,
+            accepted = tcp.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!(error = %e, "Accept error");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = TlsAcceptor::from(current.read().expect("TLS config lock poisoned").clone());
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let _ = sender.send((stream, peer)).await;
+                }
+                Ok(Err(e)) => debug!(peer = %peer, error = %e, "TLS handshake failed"),
+                Err(_) => debug!(peer = %peer, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+async fn watch_certificate(
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    interval: Duration,
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+    shutdown: CancellationToken,
+) {
+    let mut seen = modified(&cert_path, &key_path).await;
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        let now = modified(&cert_path, &key_path).await;
+        if now == seen {
+            continue;
+        }
+        // A half-written pair fails to load and is retried on the next tick
+        match load(&cert_path, &key_path) {
+            Ok(config) => {
+                *current.write().expect("TLS config lock poisoned") = config;
+                seen = now;
+                info!(cert = %cert_path.display(), "TLS certificate reloaded");
+            }
+            Err(e) => {
+                warn!(error = %e, "Could not reload the TLS certificate; keeping the current one")
+            }
+        }
+    }
+}
+
+async fn modified(cert_path: &Path, key_path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    let time = |path: &Path| {
+        let path = path.to_path_buf();
+        async move {
+            tokio::fs::metadata(path)
+                .await
+                .and_then(|m| m.modified())
+                .ok()
+        }
+    };
+    (time(cert_path).await, time(key_path).await)
+}
@@ -0,0 +1,58 @@
+use axum::http::StatusCode;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Longest value the `VARCHAR(250)` columns hold
+pub const VARCHAR_LENGTH: usize = 250;
+
+/// Reject a text field longer than `max` characters before it reaches the
+/// database, which would otherwise truncate it or fail the statement.
+pub fn check_length(field: &str, value: &str, max: usize) -> Result<(), (StatusCode, String)> {
+    let length = value.chars().count();
+    if length > max {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "{} is too long ({} characters, at most {})",
+                field, length, max
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// `YYYY-MM-DD`.
+pub fn parse_date(
+    field: &str,
+    value: Option<&str>,
+) -> Result<Option<NaiveDate>, (StatusCode, String)> {
+    value
+        .map(|d| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("{} must be YYYY-MM-DD", field),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// `YYYY-MM-DDTHH:MM:SS` or `YYYY-MM-DDTHH:MM`, in UTC.
+pub fn parse_timestamp(
+    field: &str,
+    value: Option<&str>,
+) -> Result<Option<NaiveDateTime>, (StatusCode, String)> {
+    value
+        .map(|v| {
+            let v = v.trim();
+            NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M"))
+                .map_err(|_| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        format!("{} must be YYYY-MM-DDTHH:MM[:SS]", field),
+                    )
+                })
+        })
+        .transpose()
+}
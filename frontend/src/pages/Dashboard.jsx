@@ -12,9 +12,20 @@ export default function Dashboard() {
     getAnimals();
   }, []);
 
-  async function getAnimals() {
+  // Refresh whenever the backend reports an animal change, instead of relying
+  // on the manual refresh. "reset" means events were missed while disconnected.
+  useEffect(() => {
+    const source = new EventSource('http://localhost:3000/events/stream?entity=animal');
+    const refresh = () => getAnimals(false);
+    ['animal.created', 'animal.updated', 'animal.deactivated', 'reset'].forEach(type =>
+      source.addEventListener(type, refresh)
+    );
+    return () => source.close();
+  }, []);
+
+  async function getAnimals(showLoading = true) {
     try {
-      setLoading(true);
+      if (showLoading) setLoading(true);
       setError(null);
       
       const response = await fetch('http://localhost:3000/animals/list');
@@ -54,7 +65,7 @@ export default function Dashboard() {
       ) : error ? (
         <div style={styles.errorBox}>
           <p style={styles.errorText}>❌ Error: {error}</p>
-          <button style={styles.button} onClick={fetchAnimals}>
+          <button style={styles.button} onClick={() => getAnimals()}>
             Retry
           </button>
         </div>
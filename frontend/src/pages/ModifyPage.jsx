@@ -119,9 +119,14 @@ export default function ModifyPage() {
         try {
             setError(null);
             
+            const animalHeaders = { 'Content-Type': 'application/json' };
+            if (modifiedAnimal.version !== undefined) {
+                animalHeaders['If-Match'] = `"${modifiedAnimal.version}"`;
+            }
+
             const animalResponse = await fetch(`http://localhost:3000/animals/update/${modifiedAnimal.animal_id}`, {
                 method: 'PUT',
-                headers: { 'Content-Type': 'application/json' },
+                headers: animalHeaders,
                 body: JSON.stringify({
                     name: modifiedAnimal.name,
                     specie: modifiedAnimal.specie,
@@ -134,6 +139,9 @@ export default function ModifyPage() {
                 })
             });
 
+            if (animalResponse.status === 412) {
+                throw new Error('Este animal foi alterado por outra pessoa. Recarregue a página e tente novamente.');
+            }
             if (!animalResponse.ok) {
                 throw new Error('Falha ao atualizar animal');
             }
@@ -72,6 +72,7 @@ export default function ModifyAnimalPopup({ animal, onSave, onCancel, animalInde
             // Prepare modified animal with updated data
             const modifiedAnimal = {
                 animal_id: animal.animal_id,
+                version: animal.version,
                 name: formData.name.trim(),
                 specie: formData.specie.trim(),
                 habitat: formData.habitat?.trim() || null,
@@ -7,21 +7,89 @@ GO
 USE zoo_db;
 GO
 
+CREATE TABLE Species (
+    species_id INT PRIMARY KEY,
+    common_name_pt VARCHAR(250) NOT NULL,
+    common_name_en VARCHAR(250),
+    scientific_name VARCHAR(250),
+    taxonomic_class VARCHAR(250),
+    taxonomic_order VARCHAR(250),
+    family VARCHAR(250),
+    conservation_status VARCHAR(2),
+    typical_lifespan_years INT
+)
+CREATE TABLE Species_Alias (
+    alias VARCHAR(250) PRIMARY KEY,
+    fk_Species_species_id INT
+)
 CREATE TABLE Animal (
     name VARCHAR(250),
     description TEXT,
     date_of_birth DATE,
-    specie VARCHAR(250),
+    fk_Species_species_id INT,
     habitat VARCHAR(250),
     country_of_origin VARCHAR(250),
-    is_active BIT,
-    animal_id INT PRIMARY KEY
+    status VARCHAR(20) NOT NULL,
+    status_date DATE,
+    status_reason VARCHAR(250),
+    fk_Sire_Animal_id INT,
+    fk_Sire_External_Animal_id INT,
+    fk_Dam_Animal_id INT,
+    fk_Dam_External_Animal_id INT,
+    animal_id INT PRIMARY KEY,
+    row_version ROWVERSION
+)
+CREATE TABLE External_Animal (
+    external_animal_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    fk_Species_species_id INT NOT NULL,
+    institution VARCHAR(250),
+    studbook_number VARCHAR(50),
+    date_of_birth DATE
+)
+CREATE TABLE Enclosure (
+    enclosure_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    zone VARCHAR(250),
+    biome VARCHAR(250),
+    capacity INT NOT NULL,
+    mixed_species BIT NOT NULL,
+    is_active BIT NOT NULL
+)
+CREATE TABLE Enclosure_Species (
+    fk_Enclosure_enclosure_id INT,
+    fk_Species_species_id INT,
+    PRIMARY KEY (fk_Enclosure_enclosure_id, fk_Species_species_id)
+)
+CREATE TABLE Enclosure_Assignment (
+    enclosure_assignment_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    fk_Enclosure_enclosure_id INT,
+    assigned_at DATETIME2,
+    removed_at DATETIME2,
+    reason VARCHAR(250)
+)
+CREATE TABLE Movement (
+    movement_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    movement_type VARCHAR(20),
+    fk_From_Enclosure_id INT,
+    from_location VARCHAR(250),
+    fk_To_Enclosure_id INT,
+    to_location VARCHAR(250),
+    institution VARCHAR(250),
+    is_loan BIT NOT NULL,
+    moved_on DATE,
+    reason VARCHAR(250),
+    responsible_staff VARCHAR(250),
+    recorded_at DATETIME2
 )
 CREATE TABLE Cares (
     type_of_care VARCHAR(250),
     description TEXT,
     frequency VARCHAR(250),
-    cares_id INT PRIMARY KEY
+    cares_id INT PRIMARY KEY,
+    row_version ROWVERSION
 )
 CREATE TABLE Animal_Care_have (
     date_of_care DATE,
@@ -29,6 +97,275 @@ CREATE TABLE Animal_Care_have (
     fk_Animal_animal_id INT,
     animal_care_id INT PRIMARY KEY
 )
+CREATE TABLE Medical_Examination (
+    medical_examination_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Cares_cares_id INT,
+    examined_on DATE NOT NULL,
+    veterinarian VARCHAR(250) NOT NULL,
+    reason VARCHAR(250),
+    findings TEXT,
+    treatment_notes TEXT,
+    follow_up_on DATE,
+    recorded_by VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Medical_Diagnosis (
+    fk_Medical_Examination_id INT,
+    position INT,
+    diagnosis_code VARCHAR(20) NOT NULL,
+    PRIMARY KEY (fk_Medical_Examination_id, position)
+)
+CREATE TABLE Medical_Attachment (
+    medical_attachment_id INT PRIMARY KEY,
+    fk_Medical_Examination_id INT NOT NULL,
+    file_name VARCHAR(250) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_reference VARCHAR(2048) NOT NULL,
+    uploaded_at DATETIME2
+)
+CREATE TABLE Treatment_Plan (
+    treatment_plan_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Medical_Examination_id INT,
+    drug VARCHAR(250) NOT NULL,
+    dose_per_kg FLOAT NOT NULL,
+    dose_unit VARCHAR(20) NOT NULL,
+    weight_kg FLOAT NOT NULL,
+    dose FLOAT NOT NULL,
+    route VARCHAR(20) NOT NULL,
+    interval_hours INT NOT NULL,
+    duration_days INT NOT NULL,
+    starts_at DATETIME2 NOT NULL,
+    ends_at DATETIME2 NOT NULL,
+    prescribed_by VARCHAR(250) NOT NULL,
+    notes TEXT,
+    stopped_at DATETIME2,
+    stop_reason VARCHAR(250),
+    recorded_by VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Treatment_Dose (
+    treatment_dose_id INT PRIMARY KEY,
+    fk_Treatment_Plan_id INT NOT NULL,
+    scheduled_at DATETIME2,
+    administered_at DATETIME2,
+    administered_by VARCHAR(250),
+    amount FLOAT,
+    notes VARCHAR(250)
+)
+CREATE TABLE Measurement (
+    measurement_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    kind VARCHAR(30) NOT NULL,
+    value FLOAT NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    measured_at DATETIME2 NOT NULL,
+    measured_by VARCHAR(250),
+    notes VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Food_Item (
+    food_item_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    notes VARCHAR(250)
+)
+CREATE TABLE Diet_Plan (
+    diet_plan_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    fk_Species_species_id INT,
+    name VARCHAR(250) NOT NULL,
+    valid_from DATE NOT NULL,
+    valid_to DATE,
+    notes VARCHAR(250)
+)
+CREATE TABLE Diet_Plan_Item (
+    diet_plan_item_id INT PRIMARY KEY,
+    fk_Diet_Plan_id INT NOT NULL,
+    fk_Food_Item_id INT NOT NULL,
+    quantity FLOAT NOT NULL,
+    feeding_time TIME NOT NULL,
+    feeding_method VARCHAR(30) NOT NULL,
+    notes VARCHAR(250)
+)
+CREATE TABLE Feeding_Record (
+    feeding_record_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Diet_Plan_Item_id INT,
+    fk_Food_Item_id INT NOT NULL,
+    fed_on DATE NOT NULL,
+    quantity_given FLOAT NOT NULL,
+    quantity_refused FLOAT NOT NULL,
+    fed_by VARCHAR(250),
+    notes VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Inventory_Item (
+    inventory_item_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    category VARCHAR(20) NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    reorder_level FLOAT NOT NULL,
+    fk_Food_Item_id INT,
+    drug_name VARCHAR(250),
+    notes VARCHAR(250)
+)
+CREATE TABLE Inventory_Lot (
+    inventory_lot_id INT PRIMARY KEY,
+    fk_Inventory_Item_id INT NOT NULL,
+    lot_number VARCHAR(100),
+    expires_on DATE,
+    received_on DATE NOT NULL,
+    quantity_received FLOAT NOT NULL,
+    quantity_on_hand FLOAT NOT NULL,
+    supplier VARCHAR(250),
+    notes VARCHAR(250)
+)
+CREATE TABLE Stock_Movement (
+    stock_movement_id INT PRIMARY KEY,
+    fk_Inventory_Item_id INT NOT NULL,
+    fk_Inventory_Lot_id INT,
+    movement_type VARCHAR(20) NOT NULL,
+    quantity FLOAT NOT NULL,
+    moved_at DATETIME2 NOT NULL,
+    source VARCHAR(30),
+    source_id INT,
+    reason VARCHAR(250),
+    recorded_by VARCHAR(250)
+)
+CREATE TABLE Animal_Revision (
+    animal_revision_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    revision_number INT,
+    name VARCHAR(250),
+    description TEXT,
+    date_of_birth DATE,
+    specie VARCHAR(250),
+    fk_Species_species_id INT,
+    habitat VARCHAR(250),
+    country_of_origin VARCHAR(250),
+    is_active BIT,
+    status VARCHAR(20),
+    status_date DATE,
+    status_reason VARCHAR(250),
+    fk_Sire_Animal_id INT,
+    fk_Sire_External_Animal_id INT,
+    fk_Dam_Animal_id INT,
+    fk_Dam_External_Animal_id INT,
+    change_type VARCHAR(50),
+    reverted_from_revision INT,
+    changed_at DATETIME2
+)
+CREATE TABLE Idempotency_Key (
+    idempotency_key VARCHAR(255),
+    endpoint VARCHAR(100),
+    request_fingerprint CHAR(64),
+    status_code INT,
+    response_body NVARCHAR(MAX),
+    created_at DATETIME2,
+    expires_at DATETIME2,
+    PRIMARY KEY (idempotency_key, endpoint)
+)
+CREATE TABLE Webhook_Subscription (
+    webhook_subscription_id INT PRIMARY KEY,
+    target_url VARCHAR(2048),
+    secret VARCHAR(255),
+    event_types VARCHAR(500),
+    is_active BIT,
+    created_at DATETIME2
+)
+CREATE TABLE Webhook_Delivery (
+    webhook_delivery_id INT PRIMARY KEY,
+    fk_Webhook_Subscription_id INT,
+    event_type VARCHAR(100),
+    payload NVARCHAR(MAX),
+    status VARCHAR(20),
+    attempt_count INT,
+    next_attempt_at DATETIME2,
+    last_attempt_at DATETIME2,
+    last_response_status INT,
+    last_error NVARCHAR(1000),
+    created_at DATETIME2,
+    delivered_at DATETIME2
+)
+CREATE UNIQUE INDEX UX_Species_common_name_pt
+    ON Species (common_name_pt)
+
+ALTER TABLE Species_Alias ADD CONSTRAINT FK_Species_Alias_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Species_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Animal ADD CONSTRAINT CK_Animal_status
+    CHECK (status IN ('arrived', 'born', 'active', 'on_loan', 'transferred', 'released', 'deceased', 'removed'))
+
+ALTER TABLE External_Animal ADD CONSTRAINT FK_External_Animal_Species_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Sire_1
+    FOREIGN KEY (fk_Sire_Animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Sire_2
+    FOREIGN KEY (fk_Sire_External_Animal_id)
+    REFERENCES External_Animal (external_animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Dam_1
+    FOREIGN KEY (fk_Dam_Animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Dam_2
+    FOREIGN KEY (fk_Dam_External_Animal_id)
+    REFERENCES External_Animal (external_animal_id)
+
+-- A parent is either one of ours or an external one
+ALTER TABLE Animal ADD CONSTRAINT CK_Animal_parents
+    CHECK ((fk_Sire_Animal_id IS NULL OR fk_Sire_External_Animal_id IS NULL)
+       AND (fk_Dam_Animal_id IS NULL OR fk_Dam_External_Animal_id IS NULL))
+
+CREATE UNIQUE INDEX UX_Enclosure_name
+    ON Enclosure (name) WHERE is_active = 1
+
+ALTER TABLE Enclosure_Species ADD CONSTRAINT FK_Enclosure_Species_1
+    FOREIGN KEY (fk_Enclosure_enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+ALTER TABLE Enclosure_Species ADD CONSTRAINT FK_Enclosure_Species_2
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Enclosure_Assignment ADD CONSTRAINT FK_Enclosure_Assignment_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Enclosure_Assignment ADD CONSTRAINT FK_Enclosure_Assignment_2
+    FOREIGN KEY (fk_Enclosure_enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+CREATE INDEX IX_Enclosure_Assignment_current
+    ON Enclosure_Assignment (fk_Enclosure_enclosure_id, removed_at)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_2
+    FOREIGN KEY (fk_From_Enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_3
+    FOREIGN KEY (fk_To_Enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+CREATE INDEX IX_Movement_animal
+    ON Movement (fk_Animal_animal_id, moved_on)
+
 ALTER TABLE Animal_Care_have ADD CONSTRAINT FK_Animal_Care_have_1
     FOREIGN KEY (fk_Cares_cares_id)
     REFERENCES Cares (cares_id)
@@ -36,4 +373,164 @@ ALTER TABLE Animal_Care_have ADD CONSTRAINT FK_Animal_Care_have_1
 ALTER TABLE Animal_Care_have ADD CONSTRAINT FK_Animal_Care_have_2
     FOREIGN KEY (fk_Animal_animal_id)
     REFERENCES Animal (animal_id)
+
+ALTER TABLE Medical_Examination ADD CONSTRAINT FK_Medical_Examination_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Medical_Examination ADD CONSTRAINT FK_Medical_Examination_2
+    FOREIGN KEY (fk_Cares_cares_id)
+    REFERENCES Cares (cares_id)
+
+ALTER TABLE Medical_Diagnosis ADD CONSTRAINT FK_Medical_Diagnosis_1
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+ALTER TABLE Medical_Attachment ADD CONSTRAINT FK_Medical_Attachment_1
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+CREATE INDEX IX_Medical_Examination_animal
+    ON Medical_Examination (fk_Animal_animal_id, examined_on)
+
+CREATE INDEX IX_Medical_Examination_follow_up
+    ON Medical_Examination (follow_up_on)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT FK_Treatment_Plan_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT FK_Treatment_Plan_2
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT CK_Treatment_Plan_route
+    CHECK (route IN ('oral', 'intramuscular', 'intravenous', 'subcutaneous', 'topical', 'ophthalmic', 'inhaled', 'rectal'))
+
+ALTER TABLE Treatment_Dose ADD CONSTRAINT FK_Treatment_Dose_1
+    FOREIGN KEY (fk_Treatment_Plan_id)
+    REFERENCES Treatment_Plan (treatment_plan_id)
+
+ALTER TABLE Treatment_Dose ADD CONSTRAINT CK_Treatment_Dose_given
+    CHECK (scheduled_at IS NOT NULL OR administered_at IS NOT NULL)
+
+CREATE INDEX IX_Treatment_Plan_animal
+    ON Treatment_Plan (fk_Animal_animal_id, starts_at)
+
+CREATE INDEX IX_Treatment_Dose_plan
+    ON Treatment_Dose (fk_Treatment_Plan_id, scheduled_at)
+
+ALTER TABLE Measurement ADD CONSTRAINT FK_Measurement_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Measurement ADD CONSTRAINT CK_Measurement_kind
+    CHECK (kind IN ('weight', 'length', 'body_condition_score'))
+
+CREATE INDEX IX_Measurement_animal_kind
+    ON Measurement (fk_Animal_animal_id, kind, measured_at)
+
+CREATE UNIQUE INDEX UX_Food_Item_name
+    ON Food_Item (name)
+
+ALTER TABLE Food_Item ADD CONSTRAINT CK_Food_Item_unit
+    CHECK (unit IN ('kg', 'g', 'l', 'ml', 'unit'))
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT FK_Diet_Plan_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT FK_Diet_Plan_2
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT CK_Diet_Plan_target
+    CHECK ((fk_Animal_animal_id IS NULL AND fk_Species_species_id IS NOT NULL)
+        OR (fk_Animal_animal_id IS NOT NULL AND fk_Species_species_id IS NULL))
+
+ALTER TABLE Diet_Plan_Item ADD CONSTRAINT FK_Diet_Plan_Item_1
+    FOREIGN KEY (fk_Diet_Plan_id)
+    REFERENCES Diet_Plan (diet_plan_id)
+
+ALTER TABLE Diet_Plan_Item ADD CONSTRAINT FK_Diet_Plan_Item_2
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_2
+    FOREIGN KEY (fk_Diet_Plan_Item_id)
+    REFERENCES Diet_Plan_Item (diet_plan_item_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_3
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+CREATE UNIQUE INDEX UX_Feeding_Record_planned
+    ON Feeding_Record (fk_Animal_animal_id, fk_Diet_Plan_Item_id, fed_on)
+    WHERE fk_Diet_Plan_Item_id IS NOT NULL
+
+CREATE INDEX IX_Feeding_Record_fed_on
+    ON Feeding_Record (fed_on)
+
+CREATE UNIQUE INDEX UX_Inventory_Item_name
+    ON Inventory_Item (name)
+
+CREATE UNIQUE INDEX UX_Inventory_Item_food_item
+    ON Inventory_Item (fk_Food_Item_id)
+    WHERE fk_Food_Item_id IS NOT NULL
+
+CREATE UNIQUE INDEX UX_Inventory_Item_drug_name
+    ON Inventory_Item (drug_name)
+    WHERE drug_name IS NOT NULL
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT CK_Inventory_Item_category
+    CHECK (category IN ('food', 'medication', 'enrichment', 'other'))
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT CK_Inventory_Item_unit
+    CHECK (unit IN ('kg', 'g', 'mg', 'l', 'ml', 'unit'))
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT FK_Inventory_Item_1
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+ALTER TABLE Inventory_Lot ADD CONSTRAINT FK_Inventory_Lot_1
+    FOREIGN KEY (fk_Inventory_Item_id)
+    REFERENCES Inventory_Item (inventory_item_id)
+
+ALTER TABLE Inventory_Lot ADD CONSTRAINT CK_Inventory_Lot_quantity
+    CHECK (quantity_on_hand >= 0)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT FK_Stock_Movement_1
+    FOREIGN KEY (fk_Inventory_Item_id)
+    REFERENCES Inventory_Item (inventory_item_id)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT FK_Stock_Movement_2
+    FOREIGN KEY (fk_Inventory_Lot_id)
+    REFERENCES Inventory_Lot (inventory_lot_id)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT CK_Stock_Movement_type
+    CHECK (movement_type IN ('received', 'consumed', 'adjusted', 'discarded', 'returned'))
+
+CREATE INDEX IX_Inventory_Lot_expires
+    ON Inventory_Lot (expires_on)
+
+CREATE INDEX IX_Stock_Movement_source
+    ON Stock_Movement (source, source_id)
+
+ALTER TABLE Animal_Revision ADD CONSTRAINT FK_Animal_Revision_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+CREATE UNIQUE INDEX UX_Animal_Revision_animal_revision
+    ON Animal_Revision (fk_Animal_animal_id, revision_number)
+
+ALTER TABLE Webhook_Delivery ADD CONSTRAINT FK_Webhook_Delivery_1
+    FOREIGN KEY (fk_Webhook_Subscription_id)
+    REFERENCES Webhook_Subscription (webhook_subscription_id)
+
+CREATE INDEX IX_Webhook_Delivery_due
+    ON Webhook_Delivery (status, next_attempt_at)
 GO
\ No newline at end of file
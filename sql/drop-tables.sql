@@ -1,7 +1,31 @@
 USE zoo_db;
 GO
 
+DROP TABLE Webhook_Delivery;
+DROP TABLE Webhook_Subscription;
+DROP TABLE Idempotency_Key;
+DROP TABLE Animal_Revision;
+DROP TABLE Stock_Movement;
+DROP TABLE Inventory_Lot;
+DROP TABLE Inventory_Item;
+DROP TABLE Feeding_Record;
+DROP TABLE Diet_Plan_Item;
+DROP TABLE Diet_Plan;
+DROP TABLE Food_Item;
+DROP TABLE Measurement;
+DROP TABLE Treatment_Dose;
+DROP TABLE Treatment_Plan;
+DROP TABLE Medical_Attachment;
+DROP TABLE Medical_Diagnosis;
+DROP TABLE Medical_Examination;
+DROP TABLE Movement;
+DROP TABLE Enclosure_Assignment;
+DROP TABLE Enclosure_Species;
+DROP TABLE Enclosure;
 DROP TABLE Animal_Care_have;
 DROP TABLE Cares;
 DROP TABLE Animal;
+DROP TABLE External_Animal;
+DROP TABLE Species_Alias;
+DROP TABLE Species;
 GO
\ No newline at end of file
@@ -1,119 +1,156 @@
 USE zoo_db;
 GO
 
-INSERT INTO Animal (name, description, date_of_birth, specie, habitat, country_of_origin, is_active, animal_id) VALUES
-('Pedro', 'Animal saudável e ativo.', '2017-03-12', 'Leão', 'Savana', 'África do Sul', 1, 1),
-('Luna', 'Animal dócil e calmo.', '2019-07-22', 'Pantera', 'Floresta', 'Brasil', 1, 2),
-('Max', 'Corre bastante.', '2018-11-03', 'Tigre', 'Floresta Tropical', 'Índia', 1, 3),
-('Bella', 'Muito brincalhona.', '2020-01-14', 'Onça', 'Floresta Amazônica', 'Brasil', 1, 4),
-('Thor', 'Animal forte e dominante.', '2016-05-30', 'Urso', 'Montanhas', 'Canadá', 1, 5),
-('Nina', 'Gosta de áreas abertas.', '2015-09-18', 'Leoa', 'Savana', 'Quênia', 1, 6),
-('Rex', 'Animal rápido.', '2017-02-21', 'Guepardo', 'Savana', 'Namíbia', 1, 7),
-('Milo', 'Curioso e atento.', '2021-04-28', 'Lince', 'Floresta Boreal', 'Rússia', 1, 8),
-('Jade', 'Calma e silenciosa.', '2014-10-10', 'Pantera Negra', 'Floresta', 'Índia', 1, 9),
-('Simba', 'Muito sociável.', '2018-08-19', 'Leão', 'Savana', 'Tanzânia', 1, 10),
-('Zara', 'Animal observador.', '2017-06-05', 'Tigre', 'Floresta', 'Bangladesh', 1, 11),
-('Leo', 'Caçador nato.', '2019-12-25', 'Leopardo', 'Savana', 'Ruanda', 1, 12),
-('Maya', 'Tranquila e dócil.', '2016-04-01', 'Onça', 'Pantanal', 'Brasil', 1, 13),
-('Apollo', 'Muito forte.', '2015-02-17', 'Urso Pardo', 'Montanhas', 'EUA', 1, 14),
-('Kiara', 'Rápida e esperta.', '2020-03-03', 'Guepardo', 'Savana', 'África do Sul', 1, 15),
-('Zeus', 'Dorminhoco.', '2013-12-12', 'Urso Polar', 'Ártico', 'Canadá', 1, 16),
-('Sasha', 'Animal atento.', '2016-07-07', 'Lobo', 'Floresta', 'Rússia', 1, 17),
-('Rocky', 'Muito ativo.', '2021-11-11', 'Raposa', 'Floresta', 'EUA', 1, 18),
-('Koda', 'Animal robusto.', '2014-09-09', 'Urso', 'Montanhas', 'Alasca', 1, 19),
-('Aria', 'Muito observadora.', '2019-10-20', 'Lince', 'Floresta', 'Noruega', 1, 20),
-('Toby', 'Gosta de correr.', '2017-01-13', 'Chacal', 'Deserto', 'Egito', 1, 21),
-('Lola', 'Extremamente dócil.', '2020-02-22', 'Leoa', 'Savana', 'Botsuana', 1, 22),
-('Hunter', 'Veloz.', '2018-06-16', 'Guepardo', 'Savana', 'Quênia', 1, 23),
-('Ghost', 'Silencioso.', '2014-03-30', 'Lobo Ártico', 'Tundra', 'Canadá', 1, 24),
-('Freya', 'Muito calma.', '2015-08-08', 'Raposa do Ártico', 'Tundra', 'Islândia', 1, 25),
-('Zion', 'Dominante.', '2016-10-02', 'Leão', 'Savana', 'África do Sul', 1, 26),
-('Nova', 'Solitária.', '2019-03-14', 'Pantera', 'Floresta', 'Índia', 1, 27),
-('Atlas', 'Forte.', '2017-11-18', 'Urso', 'Montanhas', 'Romênia', 1, 28),
-('Ruby', 'Muito ativa.', '2020-04-09', 'Onça', 'Floresta', 'Brasil', 1, 29),
-('Bruno', 'Pesado e grande.', '2013-07-21', 'Urso Pardo', 'Montanhas', 'EUA', 1, 30),
-('Loki', 'Enganador.', '2016-12-01', 'Lobo', 'Floresta', 'Suécia', 1, 31),
-('Ivy', 'Muito ágil.', '2018-05-08', 'Leopardo', 'Savana', 'Etiópia', 1, 32),
-('Ares', 'Corajoso.', '2015-06-17', 'Tigre', 'Floresta', 'Índia', 1, 33),
-('Nova', 'Sempre alerta.', '2021-03-03', 'Raposa', 'Floresta', 'EUA', 1, 34),
-('Zeke', 'Muito rápido.', '2019-09-09', 'Guepardo', 'Savana', 'Tanzânia', 1, 35),
-('Mira', 'Calma.', '2020-10-29', 'Pantera', 'Floresta', 'Brasil', 1, 36),
-('Odin', 'Grande e forte.', '2014-01-06', 'Urso', 'Montanha', 'Canadá', 1, 37),
-('Suki', 'Gentil.', '2017-04-04', 'Lince', 'Floresta', 'Suécia', 1, 38),
-('Flint', 'Sempre observando.', '2016-02-12', 'Lobo', 'Floresta', 'Alemanha', 1, 39),
-('Amber', 'Muito rápida.', '2018-07-27', 'Onça', 'Pantanal', 'Brasil', 1, 40),
-('Rory', 'Vocaliza muito.', '2021-02-15', 'Lobo', 'Floresta', 'EUA', 1, 41),
-('Mika', 'Tímida.', '2015-11-19', 'Raposa', 'Floresta', 'Canadá', 1, 42),
-('Zuri', 'Extremamente veloz.', '2019-08-23', 'Guepardo', 'Savana', 'Namíbia', 1, 43),
-('Nero', 'Grande caçador.', '2016-06-29', 'Tigre', 'Floresta', 'Índia', 1, 44),
-('Kira', 'Muito gentil.', '2020-09-10', 'Leoa', 'Savana', 'Botsuana', 1, 45),
-('Shadow', 'Silencioso.', '2017-03-02', 'Pantera Negra', 'Floresta', 'Malásia', 1, 46),
-('Bolt', 'Corre muito rápido.', '2018-05-05', 'Guepardo', 'Savana', 'Quênia', 1, 47),
-('Fang', 'Agressivo.', '2013-10-14', 'Lobo Cinzento', 'Floresta', 'Rússia', 1, 48),
-('Nala', 'Carinhosa.', '2021-01-21', 'Leoa', 'Savana', 'África do Sul', 1, 49),
-('Khan', 'Caçador poderoso.', '2014-04-04', 'Tigre', 'Floresta', 'Bangladesh', 1, 50),
-('Tara', 'Calma.', '2015-08-30', 'Onça', 'Pantanal', 'Brasil', 1, 51),
-('Blaze', 'Impulsivo.', '2018-12-12', 'Lobo', 'Floresta', 'EUA', 1, 52),
-('Echo', 'Não faz barulho.', '2017-07-07', 'Pantera', 'Floresta', 'Índia', 1, 53),
-('Zane', 'Ágil.', '2019-10-11', 'Leopardo', 'Floresta', 'Etiópia', 1, 54),
-('Orion', 'Grande.', '2013-03-03', 'Urso Pardo', 'Montanhas', 'EUA', 1, 55),
-('Fiona', 'Muito ativa.', '2021-05-20', 'Raposa', 'Floresta', 'Alemanha', 1, 56),
-('Arlo', 'Forte.', '2016-09-14', 'Lobo', 'Floresta', 'Polônia', 1, 57),
-('Yara', 'Sempre alerta.', '2018-06-06', 'Onça', 'Floresta', 'Brasil', 1, 58),
-('Zen', 'Sábio.', '2015-02-18', 'Lince', 'Floresta', 'Canadá', 1, 59),
-('Groot', 'Grande porte.', '2014-11-11', 'Urso', 'Montanhas', 'Rússia', 1, 60),
-('Raven', 'Silenciosa.', '2019-04-04', 'Pantera Negra', 'Floresta', 'Índia', 1, 61),
-('Tyson', 'Forte.', '2016-07-27', 'Leão', 'Savana', 'Tanzânia', 1, 62),
-('Skye', 'Muito rápida.', '2020-10-10', 'Guepardo', 'Savana', 'Namíbia', 1, 63),
-('Cleo', 'Calma.', '2017-01-01', 'Onça', 'Floresta', 'Brasil', 1, 64),
-('Juno', 'Atenta.', '2018-08-08', 'Lince', 'Floresta', 'Rússia', 1, 65),
-('Storm', 'Agressivo.', '2014-09-22', 'Lobo', 'Floresta', 'Canadá', 1, 66),
-('Lara', 'Muito dócil.', '2021-03-19', 'Raposa', 'Floresta', 'França', 1, 67),
-('Oso', 'Grande.', '2015-12-05', 'Urso Pardo', 'Montanhas', 'Chile', 1, 68),
-('Blade', 'Frio e calculista.', '2016-05-14', 'Pantera', 'Floresta', 'Índia', 1, 69),
-('Mango', 'Curioso.', '2018-02-28', 'Leopardo', 'Floresta', 'Congo', 1, 70),
-('Claw', 'Bravo.', '2013-11-17', 'Tigre', 'Floresta', 'Índia', 1, 71),
-('Yumi', 'Pequena e rápida.', '2020-06-06', 'Raposa', 'Floresta', 'Japão', 1, 72),
-('Kai', 'Grande caçador.', '2017-03-30', 'Lobo', 'Floresta', 'Noruega', 1, 73),
-('Sapphire', 'Elegante.', '2016-10-01', 'Pantera Negra', 'Floresta', 'Malásia', 1, 74),
-('Rocco', 'Forte.', '2018-12-29', 'Urso', 'Montanhas', 'Canadá', 1, 75),
-('Gale', 'Ágil.', '2019-04-16', 'Onça', 'Pantanal', 'Brasil', 1, 76),
-('Flora', 'Calma.', '2021-07-11', 'Lince', 'Floresta', 'Finlândia', 1, 77),
-('Axel', 'Muito rápido.', '2016-01-24', 'Guepardo', 'Savana', 'Quênia', 1, 78),
-('Vera', 'Carinhosa.', '2018-10-07', 'Leoa', 'Savana', 'Ruanda', 1, 79),
-('Czar', 'Dominante.', '2015-03-08', 'Tigre', 'Floresta', 'Índia', 1, 80),
-('Sombra', 'Silenciosa.', '2014-12-20', 'Pantera', 'Floresta', 'Brasil', 1, 81),
-('Falcon', 'Veloz.', '2017-08-08', 'Leopardo', 'Floresta', 'Etiópia', 1, 82),
-('Grizzly', 'Grande.', '2013-04-14', 'Urso Pardo', 'Montanhas', 'EUA', 1, 83),
-('Runa', 'Atenta.', '2019-05-18', 'Raposa', 'Floresta', 'Alemanha', 1, 84),
-('Bora', 'Inteligente.', '2020-11-03', 'Lince', 'Floresta', 'Rússia', 1, 85),
-('Jasper', 'Bravo.', '2016-02-11', 'Lobo', 'Floresta', 'EUA', 1, 86),
-('Igor', 'Grande porte.', '2015-09-30', 'Urso', 'Montanhas', 'Rússia', 1, 87),
-('Mira', 'Elegante.', '2018-06-14', 'Pantera', 'Floresta', 'Índia', 1, 88),
-('Floki', 'Ativo.', '2021-01-09', 'Raposa', 'Floresta', 'Noruega', 1, 89),
-('Enzo', 'Rápido.', '2017-04-25', 'Guepardo', 'Savana', 'Namíbia', 1, 90),
-('Loki Jr', 'Curioso.', '2019-09-22', 'Lobo', 'Floresta', 'Canadá', 1, 91),
-('Salem', 'Sombrio.', '2014-11-09', 'Pantera Negra', 'Floresta', 'Índia', 1, 92),
-('Kodiak', 'Gigante.', '2013-02-18', 'Urso Pardo', 'Montanhas', 'Rússia', 1, 93),
-('Pearl', 'Brilhante.', '2020-03-12', 'Onça', 'Pantanal', 'Brasil', 1, 94),
-('Dante', 'Explorador.', '2018-07-07', 'Lobo', 'Floresta', 'Alemanha', 1, 95),
-('Lena', 'Muito calma.', '2017-05-29', 'Raposa', 'Floresta', 'Canadá', 1, 96),
-('Odin Jr', 'Forte.', '2016-01-05', 'Urso', 'Montanhas', 'EUA', 1, 97),
-('Night', 'Silenciosa.', '2019-04-21', 'Pantera', 'Floresta', 'Malásia', 1, 98),
-('Snow', 'Ágil.', '2021-06-06', 'Raposa do Ártico', 'Tundra', 'Canadá', 1, 99),
-('Tiger Jr', 'Pequeno mas feroz.', '2020-08-28', 'Tigre', 'Floresta', 'Índia', 1, 100);
-
-
-INSERT INTO Cares VALUES ('Alimentacao','Fornecimento de comida adequada','Diaria',1);
-INSERT INTO Cares VALUES ('Exame Veterinario','Avaliação de saúde completa','Mensal',2);
-INSERT INTO Cares VALUES ('Vacinacao','Aplicação de vacinas','Anual',3);
-INSERT INTO Cares VALUES ('Treinamento','Treino comportamental','Semanal',4);
-INSERT INTO Cares VALUES ('Higiene','Limpeza e banho','Semanal',5);
-INSERT INTO Cares VALUES ('Enriquecimento','Atividades mentais','Diaria',6);
-INSERT INTO Cares VALUES ('Pesagem','Controle de peso','Mensal',7);
-INSERT INTO Cares VALUES ('Avaliacao Dentaria','Limpeza dental','Semestral',8);
-INSERT INTO Cares VALUES ('Hidratacao','Monitoramento de água','Diaria',9);
-INSERT INTO Cares VALUES ('Observacao','Monitoramento geral','Diaria',10);
+INSERT INTO Species (species_id, common_name_pt, common_name_en, scientific_name, taxonomic_class, taxonomic_order, family, conservation_status, typical_lifespan_years) VALUES
+(1, 'Leão', 'Lion', 'Panthera leo', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 15),
+(2, 'Tigre', 'Tiger', 'Panthera tigris', 'Mammalia', 'Carnivora', 'Felidae', 'EN', 15),
+(3, 'Onça-pintada', 'Jaguar', 'Panthera onca', 'Mammalia', 'Carnivora', 'Felidae', 'NT', 15),
+(4, 'Leopardo', 'Leopard', 'Panthera pardus', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 15),
+-- A melanistic leopard or jaguar; move each animal to the right species once known
+(5, 'Pantera-negra', 'Black panther', NULL, 'Mammalia', 'Carnivora', 'Felidae', NULL, 15),
+(6, 'Urso-pardo', 'Brown bear', 'Ursus arctos', 'Mammalia', 'Carnivora', 'Ursidae', 'LC', 25),
+(7, 'Urso-polar', 'Polar bear', 'Ursus maritimus', 'Mammalia', 'Carnivora', 'Ursidae', 'VU', 25),
+(8, 'Guepardo', 'Cheetah', 'Acinonyx jubatus', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 12),
+(9, 'Lince-euroasiático', 'Eurasian lynx', 'Lynx lynx', 'Mammalia', 'Carnivora', 'Felidae', 'LC', 17),
+(10, 'Lobo-cinzento', 'Grey wolf', 'Canis lupus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 13),
+(11, 'Lobo-ártico', 'Arctic wolf', 'Canis lupus arctos', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 12),
+(12, 'Raposa-vermelha', 'Red fox', 'Vulpes vulpes', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 8),
+(13, 'Raposa-do-ártico', 'Arctic fox', 'Vulpes lagopus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 8),
+(14, 'Chacal-dourado', 'Golden jackal', 'Canis aureus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 12);
+
+INSERT INTO Species_Alias (alias, fk_Species_species_id) VALUES
+('Leão', 1),
+('Leoa', 1),
+('Tigre', 2),
+('Onça', 3),
+('Leopardo', 4),
+('Pantera', 5),
+('Pantera Negra', 5),
+('Urso', 6),
+('Urso Pardo', 6),
+('Urso Polar', 7),
+('Guepardo', 8),
+('Lince', 9),
+('Lobo', 10),
+('Lobo Cinzento', 10),
+('Lobo Ártico', 11),
+('Raposa', 12),
+('Raposa do Ártico', 13),
+('Chacal', 14);
+
+INSERT INTO Animal (name, description, date_of_birth, fk_Species_species_id, habitat, country_of_origin, status, status_date, animal_id) VALUES
+('Pedro', 'Animal saudável e ativo.', '2017-03-12', 1, 'Savana', 'África do Sul', 'active', NULL, 1),
+('Luna', 'Animal dócil e calmo.', '2019-07-22', 5, 'Floresta', 'Brasil', 'active', NULL, 2),
+('Max', 'Corre bastante.', '2018-11-03', 2, 'Floresta Tropical', 'Índia', 'active', NULL, 3),
+('Bella', 'Muito brincalhona.', '2020-01-14', 3, 'Floresta Amazônica', 'Brasil', 'active', NULL, 4),
+('Thor', 'Animal forte e dominante.', '2016-05-30', 6, 'Montanhas', 'Canadá', 'active', NULL, 5),
+('Nina', 'Gosta de áreas abertas.', '2015-09-18', 1, 'Savana', 'Quênia', 'active', NULL, 6),
+('Rex', 'Animal rápido.', '2017-02-21', 8, 'Savana', 'Namíbia', 'active', NULL, 7),
+('Milo', 'Curioso e atento.', '2021-04-28', 9, 'Floresta Boreal', 'Rússia', 'active', NULL, 8),
+('Jade', 'Calma e silenciosa.', '2014-10-10', 5, 'Floresta', 'Índia', 'active', NULL, 9),
+('Simba', 'Muito sociável.', '2018-08-19', 1, 'Savana', 'Tanzânia', 'active', NULL, 10),
+('Zara', 'Animal observador.', '2017-06-05', 2, 'Floresta', 'Bangladesh', 'active', NULL, 11),
+('Leo', 'Caçador nato.', '2019-12-25', 4, 'Savana', 'Ruanda', 'active', NULL, 12),
+('Maya', 'Tranquila e dócil.', '2016-04-01', 3, 'Pantanal', 'Brasil', 'active', NULL, 13),
+('Apollo', 'Muito forte.', '2015-02-17', 6, 'Montanhas', 'EUA', 'active', NULL, 14),
+('Kiara', 'Rápida e esperta.', '2020-03-03', 8, 'Savana', 'África do Sul', 'active', NULL, 15),
+('Zeus', 'Dorminhoco.', '2013-12-12', 7, 'Ártico', 'Canadá', 'active', NULL, 16),
+('Sasha', 'Animal atento.', '2016-07-07', 10, 'Floresta', 'Rússia', 'active', NULL, 17),
+('Rocky', 'Muito ativo.', '2021-11-11', 12, 'Floresta', 'EUA', 'active', NULL, 18),
+('Koda', 'Animal robusto.', '2014-09-09', 6, 'Montanhas', 'Alasca', 'active', NULL, 19),
+('Aria', 'Muito observadora.', '2019-10-20', 9, 'Floresta', 'Noruega', 'active', NULL, 20),
+('Toby', 'Gosta de correr.', '2017-01-13', 14, 'Deserto', 'Egito', 'active', NULL, 21),
+('Lola', 'Extremamente dócil.', '2020-02-22', 1, 'Savana', 'Botsuana', 'active', NULL, 22),
+('Hunter', 'Veloz.', '2018-06-16', 8, 'Savana', 'Quênia', 'active', NULL, 23),
+('Ghost', 'Silencioso.', '2014-03-30', 11, 'Tundra', 'Canadá', 'active', NULL, 24),
+('Freya', 'Muito calma.', '2015-08-08', 13, 'Tundra', 'Islândia', 'active', NULL, 25),
+('Zion', 'Dominante.', '2016-10-02', 1, 'Savana', 'África do Sul', 'active', NULL, 26),
+('Nova', 'Solitária.', '2019-03-14', 5, 'Floresta', 'Índia', 'active', NULL, 27),
+('Atlas', 'Forte.', '2017-11-18', 6, 'Montanhas', 'Romênia', 'active', NULL, 28),
+('Ruby', 'Muito ativa.', '2020-04-09', 3, 'Floresta', 'Brasil', 'active', NULL, 29),
+('Bruno', 'Pesado e grande.', '2013-07-21', 6, 'Montanhas', 'EUA', 'active', NULL, 30),
+('Loki', 'Enganador.', '2016-12-01', 10, 'Floresta', 'Suécia', 'active', NULL, 31),
+('Ivy', 'Muito ágil.', '2018-05-08', 4, 'Savana', 'Etiópia', 'active', NULL, 32),
+('Ares', 'Corajoso.', '2015-06-17', 2, 'Floresta', 'Índia', 'active', NULL, 33),
+('Nova', 'Sempre alerta.', '2021-03-03', 12, 'Floresta', 'EUA', 'active', NULL, 34),
+('Zeke', 'Muito rápido.', '2019-09-09', 8, 'Savana', 'Tanzânia', 'active', NULL, 35),
+('Mira', 'Calma.', '2020-10-29', 5, 'Floresta', 'Brasil', 'active', NULL, 36),
+('Odin', 'Grande e forte.', '2014-01-06', 6, 'Montanha', 'Canadá', 'active', NULL, 37),
+('Suki', 'Gentil.', '2017-04-04', 9, 'Floresta', 'Suécia', 'active', NULL, 38),
+('Flint', 'Sempre observando.', '2016-02-12', 10, 'Floresta', 'Alemanha', 'active', NULL, 39),
+('Amber', 'Muito rápida.', '2018-07-27', 3, 'Pantanal', 'Brasil', 'active', NULL, 40),
+('Rory', 'Vocaliza muito.', '2021-02-15', 10, 'Floresta', 'EUA', 'active', NULL, 41),
+('Mika', 'Tímida.', '2015-11-19', 12, 'Floresta', 'Canadá', 'active', NULL, 42),
+('Zuri', 'Extremamente veloz.', '2019-08-23', 8, 'Savana', 'Namíbia', 'active', NULL, 43),
+('Nero', 'Grande caçador.', '2016-06-29', 2, 'Floresta', 'Índia', 'active', NULL, 44),
+('Kira', 'Muito gentil.', '2020-09-10', 1, 'Savana', 'Botsuana', 'active', NULL, 45),
+('Shadow', 'Silencioso.', '2017-03-02', 5, 'Floresta', 'Malásia', 'active', NULL, 46),
+('Bolt', 'Corre muito rápido.', '2018-05-05', 8, 'Savana', 'Quênia', 'active', NULL, 47),
+('Fang', 'Agressivo.', '2013-10-14', 10, 'Floresta', 'Rússia', 'active', NULL, 48),
+('Nala', 'Carinhosa.', '2021-01-21', 1, 'Savana', 'África do Sul', 'active', NULL, 49),
+('Khan', 'Caçador poderoso.', '2014-04-04', 2, 'Floresta', 'Bangladesh', 'active', NULL, 50),
+('Tara', 'Calma.', '2015-08-30', 3, 'Pantanal', 'Brasil', 'active', NULL, 51),
+('Blaze', 'Impulsivo.', '2018-12-12', 10, 'Floresta', 'EUA', 'active', NULL, 52),
+('Echo', 'Não faz barulho.', '2017-07-07', 5, 'Floresta', 'Índia', 'active', NULL, 53),
+('Zane', 'Ágil.', '2019-10-11', 4, 'Floresta', 'Etiópia', 'active', NULL, 54),
+('Orion', 'Grande.', '2013-03-03', 6, 'Montanhas', 'EUA', 'active', NULL, 55),
+('Fiona', 'Muito ativa.', '2021-05-20', 12, 'Floresta', 'Alemanha', 'active', NULL, 56),
+('Arlo', 'Forte.', '2016-09-14', 10, 'Floresta', 'Polônia', 'active', NULL, 57),
+('Yara', 'Sempre alerta.', '2018-06-06', 3, 'Floresta', 'Brasil', 'active', NULL, 58),
+('Zen', 'Sábio.', '2015-02-18', 9, 'Floresta', 'Canadá', 'active', NULL, 59),
+('Groot', 'Grande porte.', '2014-11-11', 6, 'Montanhas', 'Rússia', 'active', NULL, 60),
+('Raven', 'Silenciosa.', '2019-04-04', 5, 'Floresta', 'Índia', 'active', NULL, 61),
+('Tyson', 'Forte.', '2016-07-27', 1, 'Savana', 'Tanzânia', 'active', NULL, 62),
+('Skye', 'Muito rápida.', '2020-10-10', 8, 'Savana', 'Namíbia', 'active', NULL, 63),
+('Cleo', 'Calma.', '2017-01-01', 3, 'Floresta', 'Brasil', 'active', NULL, 64),
+('Juno', 'Atenta.', '2018-08-08', 9, 'Floresta', 'Rússia', 'active', NULL, 65),
+('Storm', 'Agressivo.', '2014-09-22', 10, 'Floresta', 'Canadá', 'active', NULL, 66),
+('Lara', 'Muito dócil.', '2021-03-19', 12, 'Floresta', 'França', 'active', NULL, 67),
+('Oso', 'Grande.', '2015-12-05', 6, 'Montanhas', 'Chile', 'active', NULL, 68),
+('Blade', 'Frio e calculista.', '2016-05-14', 5, 'Floresta', 'Índia', 'active', NULL, 69),
+('Mango', 'Curioso.', '2018-02-28', 4, 'Floresta', 'Congo', 'active', NULL, 70),
+('Claw', 'Bravo.', '2013-11-17', 2, 'Floresta', 'Índia', 'active', NULL, 71),
+('Yumi', 'Pequena e rápida.', '2020-06-06', 12, 'Floresta', 'Japão', 'active', NULL, 72),
+('Kai', 'Grande caçador.', '2017-03-30', 10, 'Floresta', 'Noruega', 'active', NULL, 73),
+('Sapphire', 'Elegante.', '2016-10-01', 5, 'Floresta', 'Malásia', 'active', NULL, 74),
+('Rocco', 'Forte.', '2018-12-29', 6, 'Montanhas', 'Canadá', 'active', NULL, 75),
+('Gale', 'Ágil.', '2019-04-16', 3, 'Pantanal', 'Brasil', 'active', NULL, 76),
+('Flora', 'Calma.', '2021-07-11', 9, 'Floresta', 'Finlândia', 'active', NULL, 77),
+('Axel', 'Muito rápido.', '2016-01-24', 8, 'Savana', 'Quênia', 'active', NULL, 78),
+('Vera', 'Carinhosa.', '2018-10-07', 1, 'Savana', 'Ruanda', 'active', NULL, 79),
+('Czar', 'Dominante.', '2015-03-08', 2, 'Floresta', 'Índia', 'active', NULL, 80),
+('Sombra', 'Silenciosa.', '2014-12-20', 5, 'Floresta', 'Brasil', 'active', NULL, 81),
+('Falcon', 'Veloz.', '2017-08-08', 4, 'Floresta', 'Etiópia', 'active', NULL, 82),
+('Grizzly', 'Grande.', '2013-04-14', 6, 'Montanhas', 'EUA', 'active', NULL, 83),
+('Runa', 'Atenta.', '2019-05-18', 12, 'Floresta', 'Alemanha', 'active', NULL, 84),
+('Bora', 'Inteligente.', '2020-11-03', 9, 'Floresta', 'Rússia', 'active', NULL, 85),
+('Jasper', 'Bravo.', '2016-02-11', 10, 'Floresta', 'EUA', 'active', NULL, 86),
+('Igor', 'Grande porte.', '2015-09-30', 6, 'Montanhas', 'Rússia', 'active', NULL, 87),
+('Mira', 'Elegante.', '2018-06-14', 5, 'Floresta', 'Índia', 'active', NULL, 88),
+('Floki', 'Ativo.', '2021-01-09', 12, 'Floresta', 'Noruega', 'active', NULL, 89),
+('Enzo', 'Rápido.', '2017-04-25', 8, 'Savana', 'Namíbia', 'active', NULL, 90),
+('Loki Jr', 'Curioso.', '2019-09-22', 10, 'Floresta', 'Canadá', 'active', NULL, 91),
+('Salem', 'Sombrio.', '2014-11-09', 5, 'Floresta', 'Índia', 'active', NULL, 92),
+('Kodiak', 'Gigante.', '2013-02-18', 6, 'Montanhas', 'Rússia', 'active', NULL, 93),
+('Pearl', 'Brilhante.', '2020-03-12', 3, 'Pantanal', 'Brasil', 'active', NULL, 94),
+('Dante', 'Explorador.', '2018-07-07', 10, 'Floresta', 'Alemanha', 'active', NULL, 95),
+('Lena', 'Muito calma.', '2017-05-29', 12, 'Floresta', 'Canadá', 'active', NULL, 96),
+('Odin Jr', 'Forte.', '2016-01-05', 6, 'Montanhas', 'EUA', 'active', NULL, 97),
+('Night', 'Silenciosa.', '2019-04-21', 5, 'Floresta', 'Malásia', 'active', NULL, 98),
+('Snow', 'Ágil.', '2021-06-06', 13, 'Tundra', 'Canadá', 'active', NULL, 99),
+('Tiger Jr', 'Pequeno mas feroz.', '2020-08-28', 2, 'Floresta', 'Índia', 'active', NULL, 100);
+
+
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Alimentacao','Fornecimento de comida adequada','Diaria',1);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Exame Veterinario','Avaliação de saúde completa','Mensal',2);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Vacinacao','Aplicação de vacinas','Anual',3);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Treinamento','Treino comportamental','Semanal',4);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Higiene','Limpeza e banho','Semanal',5);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Enriquecimento','Atividades mentais','Diaria',6);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Pesagem','Controle de peso','Mensal',7);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Avaliacao Dentaria','Limpeza dental','Semestral',8);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Hidratacao','Monitoramento de água','Diaria',9);
+INSERT INTO Cares (type_of_care, description, frequency, cares_id) VALUES ('Observacao','Monitoramento geral','Diaria',10);
 
 INSERT INTO Animal_Care_have VALUES ('2024-01-01',1,1,1);
 INSERT INTO Animal_Care_have VALUES ('2024-01-02',2,2,2);
@@ -224,3 +261,35 @@ INSERT INTO Animal_Care_have VALUES ('2024-04-06',7,97,97);
 INSERT INTO Animal_Care_have VALUES ('2024-04-07',8,98,98);
 INSERT INTO Animal_Care_have VALUES ('2024-04-08',9,99,99);
 INSERT INTO Animal_Care_have VALUES ('2024-04-09',10,100,100);
+
+-- Baseline revision for every seeded animal, so point-in-time reads have a starting point
+INSERT INTO Animal_Revision (animal_revision_id, fk_Animal_animal_id, revision_number, name, description, date_of_birth, specie, fk_Species_species_id, habitat, country_of_origin, is_active, status, status_date, status_reason, change_type, reverted_from_revision, changed_at)
+SELECT ROW_NUMBER() OVER (ORDER BY a.animal_id), a.animal_id, 1, a.name, a.description, a.date_of_birth, s.common_name_pt, a.fk_Species_species_id, a.habitat, a.country_of_origin, 1, a.status, a.status_date, a.status_reason, 'baseline', NULL, SYSUTCDATETIME()
+FROM Animal a
+LEFT JOIN Species s ON s.species_id = a.fk_Species_species_id;
+
+-- One enclosure per habitat and species the seeded animals live in
+INSERT INTO Enclosure (enclosure_id, name, zone, biome, capacity, mixed_species, is_active)
+SELECT ROW_NUMBER() OVER (ORDER BY h.habitat, s.common_name_pt),
+       h.habitat + ' - ' + s.common_name_pt, NULL, h.habitat, h.animals, 0, 1
+FROM (
+    SELECT habitat, fk_Species_species_id, COUNT(*) AS animals
+    FROM Animal
+    WHERE status = 'active' AND habitat IS NOT NULL
+    GROUP BY habitat, fk_Species_species_id
+) h
+JOIN Species s ON s.species_id = h.fk_Species_species_id;
+
+INSERT INTO Enclosure_Species (fk_Enclosure_enclosure_id, fk_Species_species_id)
+SELECT e.enclosure_id, s.species_id
+FROM Enclosure e
+JOIN Species s ON e.name = e.biome + ' - ' + s.common_name_pt;
+
+INSERT INTO Enclosure_Assignment (enclosure_assignment_id, fk_Animal_animal_id, fk_Enclosure_enclosure_id, assigned_at, removed_at, reason)
+SELECT ROW_NUMBER() OVER (ORDER BY a.animal_id), a.animal_id, es.fk_Enclosure_enclosure_id, SYSUTCDATETIME(), NULL, 'Initial placement'
+FROM Animal a
+JOIN Enclosure e ON e.biome = a.habitat
+JOIN Enclosure_Species es ON es.fk_Enclosure_enclosure_id = e.enclosure_id
+                         AND es.fk_Species_species_id = a.fk_Species_species_id
+WHERE a.status = 'active';
+GO
@@ -0,0 +1,35 @@
+-- Adds versioned storage for Animal rows.
+-- Run once against an existing zoo_db created before Animal_Revision existed:
+--   ./sql/run-sql.sh sql/migrations/001_animal_revisions.sql
+USE zoo_db;
+GO
+
+CREATE TABLE Animal_Revision (
+    animal_revision_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    revision_number INT,
+    name VARCHAR(250),
+    description TEXT,
+    date_of_birth DATE,
+    specie VARCHAR(250),
+    habitat VARCHAR(250),
+    country_of_origin VARCHAR(250),
+    is_active BIT,
+    change_type VARCHAR(50),
+    reverted_from_revision INT,
+    changed_at DATETIME2
+)
+
+ALTER TABLE Animal_Revision ADD CONSTRAINT FK_Animal_Revision_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+CREATE UNIQUE INDEX UX_Animal_Revision_animal_revision
+    ON Animal_Revision (fk_Animal_animal_id, revision_number)
+GO
+
+-- Every existing animal starts with a baseline revision of its current state
+INSERT INTO Animal_Revision (animal_revision_id, fk_Animal_animal_id, revision_number, name, description, date_of_birth, specie, habitat, country_of_origin, is_active, change_type, reverted_from_revision, changed_at)
+SELECT ROW_NUMBER() OVER (ORDER BY animal_id), animal_id, 1, name, description, date_of_birth, specie, habitat, country_of_origin, is_active, 'baseline', NULL, SYSUTCDATETIME()
+FROM Animal;
+GO
@@ -0,0 +1,7 @@
+-- Adds row versions used for ETag / If-Match optimistic concurrency.
+USE zoo_db;
+GO
+
+ALTER TABLE Animal ADD row_version ROWVERSION;
+ALTER TABLE Cares ADD row_version ROWVERSION;
+GO
@@ -0,0 +1,15 @@
+-- Adds storage for Idempotency-Key replay on POST create endpoints.
+USE zoo_db;
+GO
+
+CREATE TABLE Idempotency_Key (
+    idempotency_key VARCHAR(255),
+    endpoint VARCHAR(100),
+    request_fingerprint CHAR(64),
+    status_code INT,
+    response_body NVARCHAR(MAX),
+    created_at DATETIME2,
+    expires_at DATETIME2,
+    PRIMARY KEY (idempotency_key, endpoint)
+)
+GO
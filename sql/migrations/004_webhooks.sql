@@ -0,0 +1,34 @@
+-- Adds webhook subscriptions and the persistent delivery queue.
+USE zoo_db;
+GO
+
+CREATE TABLE Webhook_Subscription (
+    webhook_subscription_id INT PRIMARY KEY,
+    target_url VARCHAR(2048),
+    secret VARCHAR(255),
+    event_types VARCHAR(500),
+    is_active BIT,
+    created_at DATETIME2
+)
+CREATE TABLE Webhook_Delivery (
+    webhook_delivery_id INT PRIMARY KEY,
+    fk_Webhook_Subscription_id INT,
+    event_type VARCHAR(100),
+    payload NVARCHAR(MAX),
+    status VARCHAR(20),
+    attempt_count INT,
+    next_attempt_at DATETIME2,
+    last_attempt_at DATETIME2,
+    last_response_status INT,
+    last_error NVARCHAR(1000),
+    created_at DATETIME2,
+    delivered_at DATETIME2
+)
+
+ALTER TABLE Webhook_Delivery ADD CONSTRAINT FK_Webhook_Delivery_1
+    FOREIGN KEY (fk_Webhook_Subscription_id)
+    REFERENCES Webhook_Subscription (webhook_subscription_id)
+
+CREATE INDEX IX_Webhook_Delivery_due
+    ON Webhook_Delivery (status, next_attempt_at)
+GO
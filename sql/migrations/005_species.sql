@@ -0,0 +1,108 @@
+-- Replaces the free-text Animal.specie with a Species catalogue.
+--
+-- Every existing specie string becomes an alias of a species, so spellings
+-- that meant the same animal ('Leoa' and 'Leão', 'Urso' and 'Urso Pardo')
+-- end up on one row, and the API keeps accepting the old names. Strings not
+-- listed below get a species of their own, named after the string.
+USE zoo_db;
+GO
+
+CREATE TABLE Species (
+    species_id INT PRIMARY KEY,
+    common_name_pt VARCHAR(250) NOT NULL,
+    common_name_en VARCHAR(250),
+    scientific_name VARCHAR(250),
+    taxonomic_class VARCHAR(250),
+    taxonomic_order VARCHAR(250),
+    family VARCHAR(250),
+    conservation_status VARCHAR(2),
+    typical_lifespan_years INT
+)
+CREATE TABLE Species_Alias (
+    alias VARCHAR(250) PRIMARY KEY,
+    fk_Species_species_id INT
+)
+
+CREATE UNIQUE INDEX UX_Species_common_name_pt
+    ON Species (common_name_pt)
+
+ALTER TABLE Species_Alias ADD CONSTRAINT FK_Species_Alias_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Animal ADD fk_Species_species_id INT;
+ALTER TABLE Animal_Revision ADD fk_Species_species_id INT;
+GO
+
+INSERT INTO Species (species_id, common_name_pt, common_name_en, scientific_name, taxonomic_class, taxonomic_order, family, conservation_status, typical_lifespan_years) VALUES
+(1, 'Leão', 'Lion', 'Panthera leo', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 15),
+(2, 'Tigre', 'Tiger', 'Panthera tigris', 'Mammalia', 'Carnivora', 'Felidae', 'EN', 15),
+(3, 'Onça-pintada', 'Jaguar', 'Panthera onca', 'Mammalia', 'Carnivora', 'Felidae', 'NT', 15),
+(4, 'Leopardo', 'Leopard', 'Panthera pardus', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 15),
+-- A melanistic leopard or jaguar; move each animal to the right species once known
+(5, 'Pantera-negra', 'Black panther', NULL, 'Mammalia', 'Carnivora', 'Felidae', NULL, 15),
+(6, 'Urso-pardo', 'Brown bear', 'Ursus arctos', 'Mammalia', 'Carnivora', 'Ursidae', 'LC', 25),
+(7, 'Urso-polar', 'Polar bear', 'Ursus maritimus', 'Mammalia', 'Carnivora', 'Ursidae', 'VU', 25),
+(8, 'Guepardo', 'Cheetah', 'Acinonyx jubatus', 'Mammalia', 'Carnivora', 'Felidae', 'VU', 12),
+(9, 'Lince-euroasiático', 'Eurasian lynx', 'Lynx lynx', 'Mammalia', 'Carnivora', 'Felidae', 'LC', 17),
+(10, 'Lobo-cinzento', 'Grey wolf', 'Canis lupus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 13),
+(11, 'Lobo-ártico', 'Arctic wolf', 'Canis lupus arctos', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 12),
+(12, 'Raposa-vermelha', 'Red fox', 'Vulpes vulpes', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 8),
+(13, 'Raposa-do-ártico', 'Arctic fox', 'Vulpes lagopus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 8),
+(14, 'Chacal-dourado', 'Golden jackal', 'Canis aureus', 'Mammalia', 'Carnivora', 'Canidae', 'LC', 12);
+
+INSERT INTO Species_Alias (alias, fk_Species_species_id) VALUES
+('Leão', 1),
+('Leoa', 1),
+('Tigre', 2),
+('Onça', 3),
+('Leopardo', 4),
+('Pantera', 5),
+('Pantera Negra', 5),
+('Urso', 6),
+('Urso Pardo', 6),
+('Urso Polar', 7),
+('Guepardo', 8),
+('Lince', 9),
+('Lobo', 10),
+('Lobo Cinzento', 10),
+('Lobo Ártico', 11),
+('Raposa', 12),
+('Raposa do Ártico', 13),
+('Chacal', 14);
+
+-- Anything else already in use becomes its own species
+INSERT INTO Species (species_id, common_name_pt)
+SELECT 14 + ROW_NUMBER() OVER (ORDER BY u.specie), u.specie
+FROM (
+    SELECT DISTINCT LTRIM(RTRIM(specie)) AS specie FROM Animal
+    UNION
+    SELECT DISTINCT LTRIM(RTRIM(specie)) FROM Animal_Revision
+) u
+WHERE u.specie IS NOT NULL AND u.specie <> ''
+  AND NOT EXISTS (SELECT 1 FROM Species_Alias sa WHERE sa.alias = u.specie)
+  AND NOT EXISTS (SELECT 1 FROM Species s WHERE s.common_name_pt = u.specie);
+
+INSERT INTO Species_Alias (alias, fk_Species_species_id)
+SELECT s.common_name_pt, s.species_id
+FROM Species s
+WHERE s.species_id > 14
+  AND NOT EXISTS (SELECT 1 FROM Species_Alias sa WHERE sa.alias = s.common_name_pt);
+
+UPDATE a SET fk_Species_species_id = sa.fk_Species_species_id
+FROM Animal a
+JOIN Species_Alias sa ON sa.alias = LTRIM(RTRIM(a.specie));
+
+UPDATE r SET fk_Species_species_id = sa.fk_Species_species_id
+FROM Animal_Revision r
+JOIN Species_Alias sa ON sa.alias = LTRIM(RTRIM(r.specie));
+GO
+
+-- Animal_Revision keeps its specie column as the name at the time of the
+-- revision; Animal now only has the reference.
+ALTER TABLE Animal DROP COLUMN specie;
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Species_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+GO
@@ -0,0 +1,81 @@
+-- Adds enclosures and moves animals into them.
+--
+-- Animal.habitat mixes biome and location, and animals of different species
+-- share the same value ('Floresta' holds wolves, foxes and big cats). Each
+-- existing habitat and species pair becomes an enclosure of its own, holding
+-- only that species and sized for the animals already in it, so no existing
+-- assignment breaks the rules. Rename, merge and resize them afterwards.
+USE zoo_db;
+GO
+
+CREATE TABLE Enclosure (
+    enclosure_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    zone VARCHAR(250),
+    biome VARCHAR(250),
+    capacity INT NOT NULL,
+    mixed_species BIT NOT NULL,
+    is_active BIT NOT NULL
+)
+CREATE TABLE Enclosure_Species (
+    fk_Enclosure_enclosure_id INT,
+    fk_Species_species_id INT,
+    PRIMARY KEY (fk_Enclosure_enclosure_id, fk_Species_species_id)
+)
+CREATE TABLE Enclosure_Assignment (
+    enclosure_assignment_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    fk_Enclosure_enclosure_id INT,
+    assigned_at DATETIME2,
+    removed_at DATETIME2,
+    reason VARCHAR(250)
+)
+
+-- Names only have to be unique among enclosures still in use
+CREATE UNIQUE INDEX UX_Enclosure_name
+    ON Enclosure (name) WHERE is_active = 1
+
+ALTER TABLE Enclosure_Species ADD CONSTRAINT FK_Enclosure_Species_1
+    FOREIGN KEY (fk_Enclosure_enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+ALTER TABLE Enclosure_Species ADD CONSTRAINT FK_Enclosure_Species_2
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Enclosure_Assignment ADD CONSTRAINT FK_Enclosure_Assignment_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Enclosure_Assignment ADD CONSTRAINT FK_Enclosure_Assignment_2
+    FOREIGN KEY (fk_Enclosure_enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+CREATE INDEX IX_Enclosure_Assignment_current
+    ON Enclosure_Assignment (fk_Enclosure_enclosure_id, removed_at)
+GO
+
+INSERT INTO Enclosure (enclosure_id, name, zone, biome, capacity, mixed_species, is_active)
+SELECT ROW_NUMBER() OVER (ORDER BY h.habitat, s.common_name_pt),
+       h.habitat + ' - ' + s.common_name_pt, NULL, h.habitat, h.animals, 0, 1
+FROM (
+    SELECT LTRIM(RTRIM(habitat)) AS habitat, fk_Species_species_id, COUNT(*) AS animals
+    FROM Animal
+    WHERE is_active = 1 AND LTRIM(RTRIM(ISNULL(habitat, ''))) <> '' AND fk_Species_species_id IS NOT NULL
+    GROUP BY LTRIM(RTRIM(habitat)), fk_Species_species_id
+) h
+JOIN Species s ON s.species_id = h.fk_Species_species_id;
+
+INSERT INTO Enclosure_Species (fk_Enclosure_enclosure_id, fk_Species_species_id)
+SELECT e.enclosure_id, s.species_id
+FROM Enclosure e
+JOIN Species s ON e.name = e.biome + ' - ' + s.common_name_pt;
+
+INSERT INTO Enclosure_Assignment (enclosure_assignment_id, fk_Animal_animal_id, fk_Enclosure_enclosure_id, assigned_at, removed_at, reason)
+SELECT ROW_NUMBER() OVER (ORDER BY a.animal_id), a.animal_id, es.fk_Enclosure_enclosure_id, SYSUTCDATETIME(), NULL, 'Migrated from habitat'
+FROM Animal a
+JOIN Enclosure e ON e.biome = LTRIM(RTRIM(a.habitat))
+JOIN Enclosure_Species es ON es.fk_Enclosure_enclosure_id = e.enclosure_id
+                         AND es.fk_Species_species_id = a.fk_Species_species_id
+WHERE a.is_active = 1;
+GO
@@ -0,0 +1,53 @@
+-- Adds the movements log: moves between enclosures and habitats, and
+-- transfers to and from other institutions.
+--
+-- Habitat changes already kept in Animal_Revision are copied in, so the
+-- places animals were before this migration aren't lost.
+USE zoo_db;
+GO
+
+CREATE TABLE Movement (
+    movement_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    movement_type VARCHAR(20),
+    fk_From_Enclosure_id INT,
+    from_location VARCHAR(250),
+    fk_To_Enclosure_id INT,
+    to_location VARCHAR(250),
+    institution VARCHAR(250),
+    is_loan BIT NOT NULL,
+    moved_on DATE,
+    reason VARCHAR(250),
+    responsible_staff VARCHAR(250),
+    recorded_at DATETIME2
+)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_2
+    FOREIGN KEY (fk_From_Enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+ALTER TABLE Movement ADD CONSTRAINT FK_Movement_3
+    FOREIGN KEY (fk_To_Enclosure_id)
+    REFERENCES Enclosure (enclosure_id)
+
+CREATE INDEX IX_Movement_animal
+    ON Movement (fk_Animal_animal_id, moved_on)
+GO
+
+INSERT INTO Movement (movement_id, fk_Animal_animal_id, movement_type, fk_From_Enclosure_id, from_location, fk_To_Enclosure_id, to_location, institution, is_loan, moved_on, reason, responsible_staff, recorded_at)
+SELECT ROW_NUMBER() OVER (ORDER BY r.changed_at, r.animal_revision_id),
+       r.fk_Animal_animal_id, 'internal', NULL, r.previous_habitat, NULL, r.habitat, NULL, 0,
+       CAST(r.changed_at AS DATE), NULL, NULL, r.changed_at
+FROM (
+    SELECT animal_revision_id, fk_Animal_animal_id, habitat, changed_at,
+           LAG(habitat) OVER (PARTITION BY fk_Animal_animal_id ORDER BY revision_number) AS previous_habitat,
+           revision_number
+    FROM Animal_Revision
+) r
+WHERE r.revision_number > 1
+  AND ISNULL(r.habitat, '') <> ISNULL(r.previous_habitat, '');
+GO
@@ -0,0 +1,81 @@
+-- Replaces Animal.is_active with a lifecycle status: arrived, born, active,
+-- on_loan, transferred, released, deceased or removed.
+--
+-- Active animals become 'active', or 'on_loan' when their last movement is
+-- a loan to another institution. Inactive ones become 'transferred' when
+-- their last movement sent them away for good, and 'removed' otherwise,
+-- since nothing recorded why they were deactivated. Animal_Revision keeps
+-- is_active for old revisions and gets the status next to it, worked out
+-- the same way from the last movement recorded before each revision, so
+-- `as_of` reads show a transfer or a loan as it stood then.
+USE zoo_db;
+GO
+
+ALTER TABLE Animal ADD
+    status VARCHAR(20),
+    status_date DATE,
+    status_reason VARCHAR(250)
+
+ALTER TABLE Animal_Revision ADD
+    status VARCHAR(20),
+    status_date DATE,
+    status_reason VARCHAR(250)
+GO
+
+UPDATE a
+SET status = CASE
+        WHEN a.is_active = 1 AND last_move.movement_type = 'transfer_out' AND last_move.is_loan = 1 THEN 'on_loan'
+        WHEN a.is_active = 1 THEN 'active'
+        WHEN last_move.movement_type = 'transfer_out' AND last_move.is_loan = 0 THEN 'transferred'
+        ELSE 'removed'
+    END,
+    status_date = CASE
+        WHEN last_move.movement_type = 'transfer_out' THEN last_move.moved_on
+    END,
+    status_reason = CASE
+        WHEN last_move.movement_type = 'transfer_out' THEN last_move.reason
+    END
+FROM Animal a
+OUTER APPLY (
+    SELECT TOP 1 m.movement_type, m.is_loan, m.moved_on, m.reason
+    FROM Movement m
+    WHERE m.fk_Animal_animal_id = a.animal_id
+    ORDER BY m.moved_on DESC, m.movement_id DESC
+) last_move;
+
+UPDATE Animal
+SET status_date = NULL,
+    status_reason = 'Deactivated before lifecycle statuses existed'
+WHERE status = 'removed';
+
+UPDATE r
+SET status = CASE
+        WHEN r.is_active = 1 AND last_move.movement_type = 'transfer_out' AND last_move.is_loan = 1 THEN 'on_loan'
+        WHEN r.is_active = 1 THEN 'active'
+        WHEN last_move.movement_type = 'transfer_out' AND last_move.is_loan = 0 THEN 'transferred'
+        ELSE 'removed'
+    END,
+    status_date = CASE
+        WHEN last_move.movement_type = 'transfer_out' THEN last_move.moved_on
+    END,
+    status_reason = CASE
+        WHEN last_move.movement_type = 'transfer_out' THEN last_move.reason
+        WHEN r.is_active = 0 THEN 'Deactivated before lifecycle statuses existed'
+    END
+FROM Animal_Revision r
+OUTER APPLY (
+    SELECT TOP 1 m.movement_type, m.is_loan, m.moved_on, m.reason
+    FROM Movement m
+    WHERE m.fk_Animal_animal_id = r.fk_Animal_animal_id
+      AND m.recorded_at <= r.changed_at
+    ORDER BY m.recorded_at DESC, m.movement_id DESC
+) last_move;
+GO
+
+ALTER TABLE Animal ALTER COLUMN status VARCHAR(20) NOT NULL
+
+ALTER TABLE Animal ADD CONSTRAINT CK_Animal_status
+    CHECK (status IN ('arrived', 'born', 'active', 'on_loan', 'transferred', 'released', 'deceased', 'removed'))
+
+ALTER TABLE Animal DROP COLUMN is_active
+GO
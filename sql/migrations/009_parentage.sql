@@ -0,0 +1,53 @@
+-- Adds the sire and dam of animals. A parent is either another animal of
+-- the zoo or an External_Animal: one of another institution, recorded only
+-- as a parent. Existing animals are left with unknown parents.
+USE zoo_db;
+GO
+
+CREATE TABLE External_Animal (
+    external_animal_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    fk_Species_species_id INT NOT NULL,
+    institution VARCHAR(250),
+    studbook_number VARCHAR(50),
+    date_of_birth DATE
+)
+
+ALTER TABLE Animal ADD
+    fk_Sire_Animal_id INT,
+    fk_Sire_External_Animal_id INT,
+    fk_Dam_Animal_id INT,
+    fk_Dam_External_Animal_id INT
+
+ALTER TABLE Animal_Revision ADD
+    fk_Sire_Animal_id INT,
+    fk_Sire_External_Animal_id INT,
+    fk_Dam_Animal_id INT,
+    fk_Dam_External_Animal_id INT
+GO
+
+ALTER TABLE External_Animal ADD CONSTRAINT FK_External_Animal_Species_1
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Sire_1
+    FOREIGN KEY (fk_Sire_Animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Sire_2
+    FOREIGN KEY (fk_Sire_External_Animal_id)
+    REFERENCES External_Animal (external_animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Dam_1
+    FOREIGN KEY (fk_Dam_Animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Animal ADD CONSTRAINT FK_Animal_Dam_2
+    FOREIGN KEY (fk_Dam_External_Animal_id)
+    REFERENCES External_Animal (external_animal_id)
+
+-- A parent is either one of ours or an external one
+ALTER TABLE Animal ADD CONSTRAINT CK_Animal_parents
+    CHECK ((fk_Sire_Animal_id IS NULL OR fk_Sire_External_Animal_id IS NULL)
+       AND (fk_Dam_Animal_id IS NULL OR fk_Dam_External_Animal_id IS NULL))
+GO
@@ -0,0 +1,57 @@
+-- Adds veterinary medical records: examinations of an animal, optionally
+-- prompted by a care, with their diagnosis codes and the metadata of
+-- attached files.
+USE zoo_db;
+GO
+
+CREATE TABLE Medical_Examination (
+    medical_examination_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Cares_cares_id INT,
+    examined_on DATE NOT NULL,
+    veterinarian VARCHAR(250) NOT NULL,
+    reason VARCHAR(250),
+    findings TEXT,
+    treatment_notes TEXT,
+    follow_up_on DATE,
+    recorded_by VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Medical_Diagnosis (
+    fk_Medical_Examination_id INT,
+    position INT,
+    diagnosis_code VARCHAR(20) NOT NULL,
+    PRIMARY KEY (fk_Medical_Examination_id, position)
+)
+CREATE TABLE Medical_Attachment (
+    medical_attachment_id INT PRIMARY KEY,
+    fk_Medical_Examination_id INT NOT NULL,
+    file_name VARCHAR(250) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_reference VARCHAR(2048) NOT NULL,
+    uploaded_at DATETIME2
+)
+
+ALTER TABLE Medical_Examination ADD CONSTRAINT FK_Medical_Examination_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Medical_Examination ADD CONSTRAINT FK_Medical_Examination_2
+    FOREIGN KEY (fk_Cares_cares_id)
+    REFERENCES Cares (cares_id)
+
+ALTER TABLE Medical_Diagnosis ADD CONSTRAINT FK_Medical_Diagnosis_1
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+ALTER TABLE Medical_Attachment ADD CONSTRAINT FK_Medical_Attachment_1
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+CREATE INDEX IX_Medical_Examination_animal
+    ON Medical_Examination (fk_Animal_animal_id, examined_on)
+
+CREATE INDEX IX_Medical_Examination_follow_up
+    ON Medical_Examination (follow_up_on)
+GO
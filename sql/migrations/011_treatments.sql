@@ -0,0 +1,61 @@
+-- Adds treatment plans: a drug given at a weight-based dose every so many
+-- hours for a number of days, with a row per scheduled dose and per extra
+-- dose given outside the schedule.
+USE zoo_db;
+GO
+
+CREATE TABLE Treatment_Plan (
+    treatment_plan_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Medical_Examination_id INT,
+    drug VARCHAR(250) NOT NULL,
+    dose_per_kg FLOAT NOT NULL,
+    dose_unit VARCHAR(20) NOT NULL,
+    weight_kg FLOAT NOT NULL,
+    dose FLOAT NOT NULL,
+    route VARCHAR(20) NOT NULL,
+    interval_hours INT NOT NULL,
+    duration_days INT NOT NULL,
+    starts_at DATETIME2 NOT NULL,
+    ends_at DATETIME2 NOT NULL,
+    prescribed_by VARCHAR(250) NOT NULL,
+    notes TEXT,
+    stopped_at DATETIME2,
+    stop_reason VARCHAR(250),
+    recorded_by VARCHAR(250),
+    recorded_at DATETIME2
+)
+CREATE TABLE Treatment_Dose (
+    treatment_dose_id INT PRIMARY KEY,
+    fk_Treatment_Plan_id INT NOT NULL,
+    scheduled_at DATETIME2,
+    administered_at DATETIME2,
+    administered_by VARCHAR(250),
+    amount FLOAT,
+    notes VARCHAR(250)
+)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT FK_Treatment_Plan_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT FK_Treatment_Plan_2
+    FOREIGN KEY (fk_Medical_Examination_id)
+    REFERENCES Medical_Examination (medical_examination_id)
+
+ALTER TABLE Treatment_Plan ADD CONSTRAINT CK_Treatment_Plan_route
+    CHECK (route IN ('oral', 'intramuscular', 'intravenous', 'subcutaneous', 'topical', 'ophthalmic', 'inhaled', 'rectal'))
+
+ALTER TABLE Treatment_Dose ADD CONSTRAINT FK_Treatment_Dose_1
+    FOREIGN KEY (fk_Treatment_Plan_id)
+    REFERENCES Treatment_Plan (treatment_plan_id)
+
+ALTER TABLE Treatment_Dose ADD CONSTRAINT CK_Treatment_Dose_given
+    CHECK (scheduled_at IS NOT NULL OR administered_at IS NOT NULL)
+
+CREATE INDEX IX_Treatment_Plan_animal
+    ON Treatment_Plan (fk_Animal_animal_id, starts_at)
+
+CREATE INDEX IX_Treatment_Dose_plan
+    ON Treatment_Dose (fk_Treatment_Plan_id, scheduled_at)
+GO
@@ -0,0 +1,27 @@
+-- Adds measurements taken of animals over time: weight in kg, length in cm
+-- and body condition score from 1 to 9.
+USE zoo_db;
+GO
+
+CREATE TABLE Measurement (
+    measurement_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    kind VARCHAR(30) NOT NULL,
+    value FLOAT NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    measured_at DATETIME2 NOT NULL,
+    measured_by VARCHAR(250),
+    notes VARCHAR(250),
+    recorded_at DATETIME2
+)
+
+ALTER TABLE Measurement ADD CONSTRAINT FK_Measurement_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Measurement ADD CONSTRAINT CK_Measurement_kind
+    CHECK (kind IN ('weight', 'length', 'body_condition_score'))
+
+CREATE INDEX IX_Measurement_animal_kind
+    ON Measurement (fk_Animal_animal_id, kind, measured_at)
+GO
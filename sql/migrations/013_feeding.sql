@@ -0,0 +1,88 @@
+-- Adds diet management: food items, diet plans for an animal or a whole
+-- species with the feeds of each day, and records of what was actually
+-- given and refused.
+USE zoo_db;
+GO
+
+CREATE TABLE Food_Item (
+    food_item_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    notes VARCHAR(250)
+)
+CREATE TABLE Diet_Plan (
+    diet_plan_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT,
+    fk_Species_species_id INT,
+    name VARCHAR(250) NOT NULL,
+    valid_from DATE NOT NULL,
+    valid_to DATE,
+    notes VARCHAR(250)
+)
+CREATE TABLE Diet_Plan_Item (
+    diet_plan_item_id INT PRIMARY KEY,
+    fk_Diet_Plan_id INT NOT NULL,
+    fk_Food_Item_id INT NOT NULL,
+    quantity FLOAT NOT NULL,
+    feeding_time TIME NOT NULL,
+    feeding_method VARCHAR(30) NOT NULL,
+    notes VARCHAR(250)
+)
+CREATE TABLE Feeding_Record (
+    feeding_record_id INT PRIMARY KEY,
+    fk_Animal_animal_id INT NOT NULL,
+    fk_Diet_Plan_Item_id INT,
+    fk_Food_Item_id INT NOT NULL,
+    fed_on DATE NOT NULL,
+    quantity_given FLOAT NOT NULL,
+    quantity_refused FLOAT NOT NULL,
+    fed_by VARCHAR(250),
+    notes VARCHAR(250),
+    recorded_at DATETIME2
+)
+
+CREATE UNIQUE INDEX UX_Food_Item_name
+    ON Food_Item (name)
+
+ALTER TABLE Food_Item ADD CONSTRAINT CK_Food_Item_unit
+    CHECK (unit IN ('kg', 'g', 'l', 'ml', 'unit'))
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT FK_Diet_Plan_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT FK_Diet_Plan_2
+    FOREIGN KEY (fk_Species_species_id)
+    REFERENCES Species (species_id)
+
+ALTER TABLE Diet_Plan ADD CONSTRAINT CK_Diet_Plan_target
+    CHECK ((fk_Animal_animal_id IS NULL AND fk_Species_species_id IS NOT NULL)
+        OR (fk_Animal_animal_id IS NOT NULL AND fk_Species_species_id IS NULL))
+
+ALTER TABLE Diet_Plan_Item ADD CONSTRAINT FK_Diet_Plan_Item_1
+    FOREIGN KEY (fk_Diet_Plan_id)
+    REFERENCES Diet_Plan (diet_plan_id)
+
+ALTER TABLE Diet_Plan_Item ADD CONSTRAINT FK_Diet_Plan_Item_2
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_1
+    FOREIGN KEY (fk_Animal_animal_id)
+    REFERENCES Animal (animal_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_2
+    FOREIGN KEY (fk_Diet_Plan_Item_id)
+    REFERENCES Diet_Plan_Item (diet_plan_item_id)
+
+ALTER TABLE Feeding_Record ADD CONSTRAINT FK_Feeding_Record_3
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+CREATE UNIQUE INDEX UX_Feeding_Record_planned
+    ON Feeding_Record (fk_Animal_animal_id, fk_Diet_Plan_Item_id, fed_on)
+    WHERE fk_Diet_Plan_Item_id IS NOT NULL
+
+CREATE INDEX IX_Feeding_Record_fed_on
+    ON Feeding_Record (fed_on)
+GO
@@ -0,0 +1,85 @@
+-- Adds the inventory: food, medication and supplies received in lots with
+-- their expiry dates, and every movement of their stock. Feeding records and
+-- treatment doses take from it.
+USE zoo_db;
+GO
+
+CREATE TABLE Inventory_Item (
+    inventory_item_id INT PRIMARY KEY,
+    name VARCHAR(250) NOT NULL,
+    category VARCHAR(20) NOT NULL,
+    unit VARCHAR(10) NOT NULL,
+    reorder_level FLOAT NOT NULL,
+    fk_Food_Item_id INT,
+    drug_name VARCHAR(250),
+    notes VARCHAR(250)
+)
+CREATE TABLE Inventory_Lot (
+    inventory_lot_id INT PRIMARY KEY,
+    fk_Inventory_Item_id INT NOT NULL,
+    lot_number VARCHAR(100),
+    expires_on DATE,
+    received_on DATE NOT NULL,
+    quantity_received FLOAT NOT NULL,
+    quantity_on_hand FLOAT NOT NULL,
+    supplier VARCHAR(250),
+    notes VARCHAR(250)
+)
+CREATE TABLE Stock_Movement (
+    stock_movement_id INT PRIMARY KEY,
+    fk_Inventory_Item_id INT NOT NULL,
+    fk_Inventory_Lot_id INT,
+    movement_type VARCHAR(20) NOT NULL,
+    quantity FLOAT NOT NULL,
+    moved_at DATETIME2 NOT NULL,
+    source VARCHAR(30),
+    source_id INT,
+    reason VARCHAR(250),
+    recorded_by VARCHAR(250)
+)
+
+CREATE UNIQUE INDEX UX_Inventory_Item_name
+    ON Inventory_Item (name)
+
+CREATE UNIQUE INDEX UX_Inventory_Item_food_item
+    ON Inventory_Item (fk_Food_Item_id)
+    WHERE fk_Food_Item_id IS NOT NULL
+
+CREATE UNIQUE INDEX UX_Inventory_Item_drug_name
+    ON Inventory_Item (drug_name)
+    WHERE drug_name IS NOT NULL
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT CK_Inventory_Item_category
+    CHECK (category IN ('food', 'medication', 'enrichment', 'other'))
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT CK_Inventory_Item_unit
+    CHECK (unit IN ('kg', 'g', 'mg', 'l', 'ml', 'unit'))
+
+ALTER TABLE Inventory_Item ADD CONSTRAINT FK_Inventory_Item_1
+    FOREIGN KEY (fk_Food_Item_id)
+    REFERENCES Food_Item (food_item_id)
+
+ALTER TABLE Inventory_Lot ADD CONSTRAINT FK_Inventory_Lot_1
+    FOREIGN KEY (fk_Inventory_Item_id)
+    REFERENCES Inventory_Item (inventory_item_id)
+
+ALTER TABLE Inventory_Lot ADD CONSTRAINT CK_Inventory_Lot_quantity
+    CHECK (quantity_on_hand >= 0)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT FK_Stock_Movement_1
+    FOREIGN KEY (fk_Inventory_Item_id)
+    REFERENCES Inventory_Item (inventory_item_id)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT FK_Stock_Movement_2
+    FOREIGN KEY (fk_Inventory_Lot_id)
+    REFERENCES Inventory_Lot (inventory_lot_id)
+
+ALTER TABLE Stock_Movement ADD CONSTRAINT CK_Stock_Movement_type
+    CHECK (movement_type IN ('received', 'consumed', 'adjusted', 'discarded', 'returned'))
+
+CREATE INDEX IX_Inventory_Lot_expires
+    ON Inventory_Lot (expires_on)
+
+CREATE INDEX IX_Stock_Movement_source
+    ON Stock_Movement (source, source_id)
+GO